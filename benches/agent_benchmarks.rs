@@ -4,6 +4,7 @@
 //!   - Tool dispatch (XML parsing, native parsing)
 //!   - Memory store/recall cycles (SQLite backend)
 //!   - Agent turn cycle (full orchestration loop)
+//!   - String truncation (short-input fast path and char-boundary cut)
 //!
 //! Run: `cargo bench`
 //!
@@ -21,6 +22,7 @@ use rain_labs::memory::{Memory, MemoryCategory};
 use rain_labs::observability::{NoopObserver, Observer};
 use rain_labs::providers::{ChatRequest, ChatResponse, Provider, ToolCall};
 use rain_labs::tools::{Tool, ToolResult};
+use rain_labs::truncate_with_ellipsis_cow;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -241,5 +243,27 @@ fn bench_agent_turn(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_memory_operations, bench_agent_turn,);
+// ─────────────────────────────────────────────────────────────────────────────
+// Benchmark: String truncation
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn bench_truncation(c: &mut Criterion) {
+    let short = "short status line";
+    let long = "Hello 🦀 World — ".repeat(64);
+
+    c.bench_function("truncate_short_input", |b| {
+        b.iter(|| truncate_with_ellipsis_cow(black_box(short), 80));
+    });
+
+    c.bench_function("truncate_long_input", |b| {
+        b.iter(|| truncate_with_ellipsis_cow(black_box(&long), 80));
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_memory_operations,
+    bench_agent_turn,
+    bench_truncation,
+);
 criterion_main!(benches);
@@ -5,6 +5,7 @@ use std::time::SystemTime;
 
 fn main() {
     let dist_dir = Path::new("web/dist");
+    let vendor_dir = Path::new("web/vendor");
     let web_dir = Path::new("web");
 
     // Tell Cargo to re-run this script when web sources or bundled assets change.
@@ -19,6 +20,11 @@ fn main() {
     println!("cargo:rerun-if-changed=web/tsconfig.node.json");
     println!("cargo:rerun-if-changed=web/vite.config.ts");
     println!("cargo:rerun-if-changed=web/dist");
+    println!("cargo:rerun-if-changed=web/vendor");
+
+    // Vendored third-party assets are embedded alongside web/dist; files in
+    // web/dist win on collisions (see src/gateway/static_files.rs).
+    ensure_embed_dir(vendor_dir);
 
     // Attempt to build the web frontend if npm is available and web/dist is
     // missing or stale.  The build is best-effort: when Node.js is not
@@ -48,13 +54,13 @@ fn main() {
                         .status();
                     if !matches!(fallback, Ok(s) if s.success()) {
                         eprintln!("cargo:warning=npm install failed — skipping web build");
-                        ensure_dist_dir(dist_dir);
+                        ensure_embed_dir(dist_dir);
                         return;
                     }
                 }
                 Err(e) => {
                     eprintln!("cargo:warning=Could not run npm: {e} — skipping web build");
-                    ensure_dist_dir(dist_dir);
+                    ensure_embed_dir(dist_dir);
                     return;
                 }
             }
@@ -83,7 +89,7 @@ fn main() {
         }
     }
 
-    ensure_dist_dir(dist_dir);
+    ensure_embed_dir(dist_dir);
     ensure_dashboard_assets(dist_dir);
 }
 
@@ -130,11 +136,12 @@ fn latest_modified(path: &Path) -> Option<SystemTime> {
     latest
 }
 
-/// Ensure the dist directory exists so `rust-embed` does not fail at compile
-/// time even when the web frontend is not built.
-fn ensure_dist_dir(dist_dir: &Path) {
-    if !dist_dir.exists() {
-        std::fs::create_dir_all(dist_dir).expect("failed to create web/dist/");
+/// Ensure an embedded asset directory exists so `rust-embed` does not fail at
+/// compile time even when the web frontend is not built or nothing is vendored.
+fn ensure_embed_dir(dir: &Path) {
+    if !dir.exists() {
+        std::fs::create_dir_all(dir)
+            .unwrap_or_else(|e| panic!("failed to create {}: {e}", dir.display()));
     }
 }
 
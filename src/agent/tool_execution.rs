@@ -8,7 +8,7 @@ use crate::agent::tool_call_parser::ParsedToolCall;
 use crate::approval::ApprovalManager;
 use crate::observability::{Observer, ObserverEvent};
 use crate::tools::{Tool, ToolboxManager};
-use crate::util::truncate_with_ellipsis;
+use crate::util::truncate_json_string;
 use anyhow::Result;
 use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
@@ -133,7 +133,7 @@ pub(crate) async fn execute_one_tool(
     observer: &dyn Observer,
     cancellation_token: Option<&CancellationToken>,
 ) -> Result<ToolExecutionOutcome> {
-    let args_summary = truncate_with_ellipsis(&call_arguments.to_string(), 300);
+    let args_summary = truncate_json_string(&call_arguments.to_string(), 300);
     observer.record_event(&ObserverEvent::ToolCallStart {
         tool: call_name.to_string(),
         arguments: Some(args_summary),
@@ -127,7 +127,9 @@ use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
 use crate::security::{AutonomyLevel, SecurityPolicy};
 use crate::tools::{self, Tool};
-use crate::util::truncate_with_ellipsis;
+use crate::util::{
+    EllipsisPlacement, truncate_json_string, truncate_with_ellipsis, truncate_with_ellipsis_placed,
+};
 use anyhow::{Context, Result};
 use portable_atomic::{AtomicU64, Ordering};
 use serde::Deserialize;
@@ -169,18 +171,22 @@ impl Observer for ChannelNotifyObserver {
                         if let Some(cmd) = v.get("command").and_then(|c| c.as_str()) {
                             format!(": `{}`", truncate_with_ellipsis(cmd, 200))
                         } else if let Some(q) = v.get("query").and_then(|c| c.as_str()) {
-                            format!(": {}", truncate_with_ellipsis(q, 200))
+                            // Queries are user text, often right-to-left.
+                            let q = truncate_with_ellipsis_placed(
+                                q,
+                                200,
+                                EllipsisPlacement::FollowScript,
+                            );
+                            format!(": {q}")
                         } else if let Some(p) = v.get("path").and_then(|c| c.as_str()) {
                             format!(": {p}")
                         } else if let Some(u) = v.get("url").and_then(|c| c.as_str()) {
                             format!(": {u}")
                         } else {
-                            let s = args.to_string();
-                            format!(": {}", truncate_with_ellipsis(&s, 120))
+                            format!(": {}", truncate_json_string(args, 120))
                         }
                     } else {
-                        let s = args.to_string();
-                        format!(": {}", truncate_with_ellipsis(&s, 120))
+                        format!(": {}", truncate_json_string(args, 120))
                     }
                 }
                 _ => String::new(),
@@ -1334,7 +1340,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
     let observer: Arc<dyn Observer> =
         Arc::from(observability::create_observer(&config.observability));
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
-        Arc::from(runtime::create_runtime(&config.runtime)?);
+        Arc::from(runtime::detect_runtime(&config.runtime)?);
     let security = Arc::new(SecurityPolicy::from_config(
         &config.autonomy,
         &config.workspace_dir,
@@ -3291,6 +3291,53 @@ fn channel_notify_observer_truncates_utf8_arguments_safely() {
     assert!(emitted.is_char_boundary(emitted.len()));
 }
 
+#[test]
+fn channel_notify_observer_keeps_json_escapes_whole() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let observer = ChannelNotifyObserver {
+        inner: Arc::new(NoopObserver),
+        tx,
+        tools_used: AtomicBool::new(false),
+    };
+
+    // `{"text":"` then six-char `\u0007` escapes: char 120 lands mid-escape.
+    let payload = serde_json::json!({ "text": "\u{7}".repeat(40) }).to_string();
+    observer.record_event(
+        &crate::observability::traits::ObserverEvent::ToolCallStart {
+            tool: "file_write".to_string(),
+            arguments: Some(payload),
+        },
+    );
+
+    let emitted = rx.try_recv().expect("observer should emit notify message");
+    assert!(emitted.ends_with("\\u0007..."), "{emitted}");
+}
+
+#[test]
+fn channel_notify_observer_keeps_ellipsis_after_rtl_queries() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let observer = ChannelNotifyObserver {
+        inner: Arc::new(NoopObserver),
+        tx,
+        tools_used: AtomicBool::new(false),
+    };
+
+    for (query, marker) in [
+        ("مرحبا بالعالم ".repeat(30), "\u{200F}..."),
+        ("hello world ".repeat(30), " wo..."),
+    ] {
+        let arguments = serde_json::json!({ "query": query }).to_string();
+        observer.record_event(
+            &crate::observability::traits::ObserverEvent::ToolCallStart {
+                tool: "web_search".to_string(),
+                arguments: Some(arguments),
+            },
+        );
+        let emitted = rx.try_recv().expect("observer should emit notify message");
+        assert!(emitted.ends_with(marker), "{emitted}");
+    }
+}
+
 #[test]
 fn conversation_memory_key_uses_message_id() {
     let msg = traits::ChannelMessage {
@@ -605,6 +605,59 @@ impl Config {
             }
         }
 
+        if !crate::gateway::static_files::is_safe_asset_path(&self.gateway.index_document) {
+            anyhow::bail!(
+                "gateway.index_document must be a relative asset path without '..' segments"
+            );
+        }
+
+        for (i, prefix) in self.gateway.asset_prefixes.iter().enumerate() {
+            if self.gateway.asset_prefixes[..i].contains(prefix) {
+                anyhow::bail!("gateway.asset_prefixes lists '{prefix}' more than once");
+            }
+            if !crate::gateway::static_files::is_valid_asset_prefix(prefix) {
+                anyhow::bail!(
+                    "gateway.asset_prefixes entry '{prefix}' must start and end with '/' \
+                     and must not shadow /api/, /ws/ or /admin/"
+                );
+            }
+        }
+        if self.gateway.max_api_body_bytes == 0 {
+            anyhow::bail!("gateway.max_api_body_bytes must be greater than 0");
+        }
+        if !(1..=9).contains(&self.gateway.compression.gzip_level) {
+            anyhow::bail!("gateway.compression.gzip_level must be between 1 and 9");
+        }
+        if self.gateway.compression.brotli_quality > 11 {
+            anyhow::bail!("gateway.compression.brotli_quality must be between 0 and 11");
+        }
+        if self.gateway.max_archive_bytes == 0 {
+            anyhow::bail!("gateway.max_archive_bytes must be greater than 0");
+        }
+        if self.gateway.readiness_check_interval_secs == 0 {
+            anyhow::bail!("gateway.readiness_check_interval_secs must be greater than 0");
+        }
+        if self.gateway.header_read_timeout_secs == 0 {
+            anyhow::bail!("gateway.header_read_timeout_secs must be greater than 0");
+        }
+        if self.gateway.max_connections == Some(0) {
+            anyhow::bail!("gateway.max_connections must be greater than 0");
+        }
+        if self.gateway.max_header_count == 0 {
+            anyhow::bail!("gateway.max_header_count must be greater than 0");
+        }
+        if self.gateway.max_header_bytes == 0 {
+            anyhow::bail!("gateway.max_header_bytes must be greater than 0");
+        }
+        for (pattern, secs) in &self.gateway.route_timeouts {
+            if !pattern.starts_with('/') {
+                anyhow::bail!("gateway.route_timeouts pattern {pattern:?} must start with '/'");
+            }
+            if *secs == 0 {
+                anyhow::bail!("gateway.route_timeouts.{pattern:?} must be greater than 0");
+            }
+        }
+
         // Autonomy
         if self.autonomy.max_actions_per_hour == 0 {
             anyhow::bail!("autonomy.max_actions_per_hour must be greater than 0");
@@ -1199,6 +1199,94 @@ pub struct GatewayConfig {
     /// Pairing dashboard configuration
     #[serde(default)]
     pub pairing_dashboard: PairingDashboardConfig,
+
+    /// Suppress non-error logging from the gateway and runtime (default: false).
+    /// Also enabled by the `ZEROCLAW_QUIET` env var.
+    #[serde(default)]
+    pub quiet: bool,
+
+    /// Serve the dashboard document for unmatched non-API GET requests
+    /// (default: true). Disable when running purely as an API backend so
+    /// unknown routes return a plain `404`.
+    #[serde(default = "default_true")]
+    pub spa_fallback: bool,
+
+    /// Embedded document served by the SPA fallback (default: `index.html`).
+    #[serde(default = "default_gateway_index_document")]
+    pub index_document: String,
+
+    /// Seconds `/readyz` waits for the runtime health probe (default: 5).
+    #[serde(default = "default_gateway_readiness_timeout_secs")]
+    pub readiness_timeout_secs: u64,
+
+    /// Seconds between background runtime readiness checks; `/readyz` serves
+    /// the latest result (default: 30, jittered by ±10%).
+    #[serde(default = "default_gateway_readiness_check_interval_secs")]
+    pub readiness_check_interval_secs: u64,
+
+    /// Seconds a client has to send a request's complete headers before the
+    /// connection is dropped, guarding against slowloris stalls (default: 10).
+    #[serde(default = "default_gateway_header_read_timeout_secs")]
+    pub header_read_timeout_secs: u64,
+
+    /// Seconds an idle keep-alive connection is held open awaiting another
+    /// request (default: 60). `0` disables keep-alive.
+    #[serde(default = "default_gateway_keep_alive_timeout_secs")]
+    pub keep_alive_timeout_secs: u64,
+
+    /// Cap on open HTTP connections; connections accepted beyond it are
+    /// closed immediately. Default: unlimited.
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+
+    /// Most header fields a request may carry; more get 431 (default: 100).
+    #[serde(default = "default_gateway_max_header_count")]
+    pub max_header_count: usize,
+
+    /// Most bytes a request's header fields may total; more get 431
+    /// (default: 32 KiB).
+    #[serde(default = "default_gateway_max_header_bytes")]
+    pub max_header_bytes: usize,
+
+    /// Per-route request timeouts in seconds, keyed by path or `*`-suffixed
+    /// prefix (e.g. `"/api/doctor" = 120`, `"/api/*" = 10`). Overruns get 504;
+    /// other routes keep the gateway-wide timeout. SSE and WebSocket routes
+    /// are exempt. Default: none.
+    #[serde(default)]
+    pub route_timeouts: HashMap<String, u64>,
+
+    /// Cap on concurrent command-spawning requests; excess requests get 503.
+    /// Default: the runtime's `max_concurrent_processes`.
+    #[serde(default)]
+    pub max_concurrent_commands: Option<usize>,
+
+    /// URL prefixes static dashboard assets are served under (default: `["/_app/"]`).
+    /// Each must start and end with `/`.
+    #[serde(default = "default_gateway_asset_prefixes")]
+    pub asset_prefixes: Vec<String>,
+
+    /// Largest file `POST /api/workspace/upload` accepts, in bytes (default: 512 MiB).
+    #[serde(default = "default_gateway_max_upload_bytes")]
+    pub max_upload_bytes: u64,
+
+    /// Largest request body `/api/*` endpoints accept, in bytes (default: 2 MiB).
+    /// Workspace uploads are bounded by `max_upload_bytes` instead.
+    #[serde(default = "default_gateway_max_api_body_bytes")]
+    pub max_api_body_bytes: usize,
+
+    /// Response compression configuration
+    #[serde(default)]
+    pub compression: CompressionConfig,
+
+    /// Largest total file size `GET /api/workspace/archive` packs, in bytes
+    /// (default: 1 GiB).
+    #[serde(default = "default_gateway_max_archive_bytes")]
+    pub max_archive_bytes: u64,
+
+    /// File and directory names left out of workspace archives
+    /// (default: `[".git", "node_modules", ".uploads"]`).
+    #[serde(default = "default_gateway_archive_exclude")]
+    pub archive_exclude: Vec<String>,
 }
 
 fn default_gateway_port() -> u16 {
@@ -1229,6 +1317,54 @@ fn default_gateway_idempotency_max_keys() -> usize {
     10_000
 }
 
+fn default_gateway_index_document() -> String {
+    "index.html".into()
+}
+
+fn default_gateway_readiness_timeout_secs() -> u64 {
+    5
+}
+
+fn default_gateway_readiness_check_interval_secs() -> u64 {
+    30
+}
+
+fn default_gateway_header_read_timeout_secs() -> u64 {
+    10
+}
+
+fn default_gateway_keep_alive_timeout_secs() -> u64 {
+    60
+}
+
+fn default_gateway_max_header_count() -> usize {
+    100
+}
+
+fn default_gateway_max_header_bytes() -> usize {
+    32 * 1024
+}
+
+fn default_gateway_asset_prefixes() -> Vec<String> {
+    vec!["/_app/".into()]
+}
+
+fn default_gateway_max_upload_bytes() -> u64 {
+    crate::gateway::api_workspace::MAX_UPLOAD_TOTAL_BYTES
+}
+
+fn default_gateway_max_api_body_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_gateway_max_archive_bytes() -> u64 {
+    crate::gateway::api_workspace::MAX_ARCHIVE_BYTES
+}
+
+fn default_gateway_archive_exclude() -> Vec<String> {
+    vec![".git".into(), "node_modules".into(), ".uploads".into()]
+}
+
 pub(crate) fn default_true() -> bool {
     true
 }
@@ -1255,6 +1391,24 @@ impl Default for GatewayConfig {
             session_persistence: true,
             session_ttl_hours: 0,
             pairing_dashboard: PairingDashboardConfig::default(),
+            quiet: false,
+            spa_fallback: true,
+            index_document: default_gateway_index_document(),
+            readiness_timeout_secs: default_gateway_readiness_timeout_secs(),
+            readiness_check_interval_secs: default_gateway_readiness_check_interval_secs(),
+            header_read_timeout_secs: default_gateway_header_read_timeout_secs(),
+            keep_alive_timeout_secs: default_gateway_keep_alive_timeout_secs(),
+            max_connections: None,
+            max_header_count: default_gateway_max_header_count(),
+            max_header_bytes: default_gateway_max_header_bytes(),
+            route_timeouts: HashMap::new(),
+            max_concurrent_commands: None,
+            asset_prefixes: default_gateway_asset_prefixes(),
+            max_upload_bytes: default_gateway_max_upload_bytes(),
+            max_api_body_bytes: default_gateway_max_api_body_bytes(),
+            compression: CompressionConfig::default(),
+            max_archive_bytes: default_gateway_max_archive_bytes(),
+            archive_exclude: default_gateway_archive_exclude(),
         }
     }
 }
@@ -1307,6 +1461,39 @@ impl Default for PairingDashboardConfig {
     }
 }
 
+/// Gateway response compression (`[gateway.compression]`).
+///
+/// Brotli is preferred when the client accepts it, then gzip.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CompressionConfig {
+    /// Compress eligible responses (default: true)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// gzip level, 1 (fastest) to 9 (smallest) (default: 6)
+    #[serde(default = "default_compression_gzip_level")]
+    pub gzip_level: u32,
+    /// Brotli quality, 0 (fastest) to 11 (smallest) (default: 4)
+    #[serde(default = "default_compression_brotli_quality")]
+    pub brotli_quality: u32,
+}
+
+fn default_compression_gzip_level() -> u32 {
+    6
+}
+fn default_compression_brotli_quality() -> u32 {
+    4
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            gzip_level: default_compression_gzip_level(),
+            brotli_quality: default_compression_brotli_quality(),
+        }
+    }
+}
+
 /// Secure transport configuration for inter-node communication (`[node_transport]`).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NodeTransportConfig {
@@ -3289,7 +3476,7 @@ impl Default for AutonomyConfig {
 /// Runtime adapter configuration (`[runtime]` section).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RuntimeConfig {
-    /// Runtime kind (`native` | `docker`).
+    /// Runtime kind (`native` | `docker` | `firejail`).
     #[serde(default = "default_runtime_kind")]
     pub kind: String,
 
@@ -3331,6 +3518,11 @@ pub struct DockerRuntimeConfig {
     #[serde(default = "default_true")]
     pub read_only_rootfs: bool,
 
+    /// Mount a writable tmpfs at `/tmp`, giving tools scratch space when the
+    /// root filesystem is read-only.
+    #[serde(default)]
+    pub writable_tmp: bool,
+
     /// Mount configured workspace into `/workspace`.
     #[serde(default = "default_true")]
     pub mount_workspace: bool,
@@ -3368,6 +3560,7 @@ impl Default for DockerRuntimeConfig {
             memory_limit_mb: default_docker_memory_limit_mb(),
             cpu_limit: default_docker_cpu_limit(),
             read_only_rootfs: true,
+            writable_tmp: false,
             mount_workspace: true,
             allowed_workspace_roots: Vec::new(),
         }
@@ -414,6 +414,11 @@ pub struct AuditConfig {
     #[serde(default = "default_audit_max_size_mb")]
     pub max_size_mb: u32,
 
+    /// Rotated log files to keep (`audit.log.1` … `audit.log.N`); older ones
+    /// are deleted
+    #[serde(default = "default_audit_max_files")]
+    pub max_files: u32,
+
     /// Sign events with HMAC for tamper evidence
     #[serde(default)]
     pub sign_events: bool,
@@ -431,12 +436,17 @@ fn default_audit_max_size_mb() -> u32 {
     100
 }
 
+fn default_audit_max_files() -> u32 {
+    10
+}
+
 impl Default for AuditConfig {
     fn default() -> Self {
         Self {
             enabled: default_audit_enabled(),
             log_path: default_audit_log_path(),
             max_size_mb: default_audit_max_size_mb(),
+            max_files: default_audit_max_files(),
             sign_events: false,
         }
     }
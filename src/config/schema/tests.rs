@@ -225,6 +225,7 @@ async fn runtime_config_default() {
     assert_eq!(r.docker.memory_limit_mb, Some(512));
     assert_eq!(r.docker.cpu_limit, Some(1.0));
     assert!(r.docker.read_only_rootfs);
+    assert!(!r.docker.writable_tmp);
     assert!(r.docker.mount_workspace);
 }
 
@@ -1784,6 +1785,28 @@ async fn checklist_gateway_serde_roundtrip() {
         session_persistence: true,
         session_ttl_hours: 0,
         pairing_dashboard: PairingDashboardConfig::default(),
+        quiet: false,
+        spa_fallback: false,
+        index_document: "app.html".into(),
+        readiness_timeout_secs: 2,
+        readiness_check_interval_secs: 90,
+        header_read_timeout_secs: 5,
+        keep_alive_timeout_secs: 0,
+        max_connections: Some(256),
+        max_header_count: 64,
+        max_header_bytes: 8192,
+        route_timeouts: HashMap::from([("/api/doctor".to_string(), 120)]),
+        max_concurrent_commands: Some(3),
+        asset_prefixes: vec!["/_app/".into(), "/static/".into()],
+        max_upload_bytes: 1024,
+        max_api_body_bytes: 4096,
+        compression: CompressionConfig {
+            enabled: false,
+            gzip_level: 9,
+            brotli_quality: 11,
+        },
+        max_archive_bytes: 8192,
+        archive_exclude: vec!["target".into()],
     };
     let toml_str = toml::to_string(&g).unwrap();
     let parsed: GatewayConfig = toml::from_str(&toml_str).unwrap();
@@ -1796,6 +1819,25 @@ async fn checklist_gateway_serde_roundtrip() {
     assert_eq!(parsed.webhook_rate_limit_per_minute, 80);
     assert!(parsed.trust_forwarded_headers);
     assert_eq!(parsed.path_prefix.as_deref(), Some("/R.A.I.N."));
+    assert!(!parsed.spa_fallback);
+    assert_eq!(parsed.index_document, "app.html");
+    assert_eq!(parsed.readiness_timeout_secs, 2);
+    assert_eq!(parsed.readiness_check_interval_secs, 90);
+    assert_eq!(parsed.header_read_timeout_secs, 5);
+    assert_eq!(parsed.keep_alive_timeout_secs, 0);
+    assert_eq!(parsed.max_connections, Some(256));
+    assert_eq!(parsed.max_header_count, 64);
+    assert_eq!(parsed.max_header_bytes, 8192);
+    assert_eq!(parsed.route_timeouts["/api/doctor"], 120);
+    assert_eq!(parsed.max_concurrent_commands, Some(3));
+    assert_eq!(parsed.asset_prefixes, ["/_app/", "/static/"]);
+    assert_eq!(parsed.max_upload_bytes, 1024);
+    assert_eq!(parsed.max_api_body_bytes, 4096);
+    assert!(!parsed.compression.enabled);
+    assert_eq!(parsed.compression.gzip_level, 9);
+    assert_eq!(parsed.compression.brotli_quality, 11);
+    assert_eq!(parsed.max_archive_bytes, 8192);
+    assert_eq!(parsed.archive_exclude, ["target"]);
     assert_eq!(parsed.rate_limit_max_keys, 2048);
     assert_eq!(parsed.idempotency_ttl_secs, 600);
     assert_eq!(parsed.idempotency_max_keys, 4096);
@@ -6,8 +6,14 @@
 
 use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Default for [`WalkPolicy::max_depth`].
+pub const DEFAULT_MAX_WALK_DEPTH: usize = 64;
 
 /// A single client workspace profile.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +72,60 @@ impl WorkspaceProfile {
     }
 }
 
+/// Size, modification time and content hash of one workspace file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// Hex-encoded SHA-256 of the file contents.
+    pub hash: String,
+}
+
+/// Every regular file under a workspace root at one point in time, keyed by
+/// path relative to that root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceSnapshot {
+    pub files: BTreeMap<PathBuf, FileFingerprint>,
+    /// Directories left out because they lie deeper than
+    /// [`WalkPolicy::max_depth`], relative to the root.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Relative paths that changed between two [`WorkspaceSnapshot`]s, each sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WorkspaceDiff {
+    pub added: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+}
+
+impl WorkspaceDiff {
+    /// Whether no file was added, modified, or deleted.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// How the size and snapshot walks traverse a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkPolicy {
+    /// Directory levels below the root to descend into; deeper directories
+    /// are skipped and reported. `0` only visits files directly in the root.
+    pub max_depth: usize,
+    /// Follow symlinks that resolve inside the workspace. Links pointing
+    /// outside it, or back into a directory already walked, are never followed.
+    pub follow_symlinks: bool,
+}
+
+impl Default for WalkPolicy {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_WALK_DEPTH,
+            follow_symlinks: false,
+        }
+    }
+}
+
 /// Manages loading and switching between client workspace profiles.
 #[derive(Debug, Clone)]
 pub struct WorkspaceManager {
@@ -75,6 +135,8 @@ pub struct WorkspaceManager {
     profiles: HashMap<String, WorkspaceProfile>,
     /// Currently active workspace name.
     active: Option<String>,
+    /// Traversal limits for size and snapshot walks.
+    walk_policy: WalkPolicy,
 }
 
 impl WorkspaceManager {
@@ -84,9 +146,16 @@ impl WorkspaceManager {
             workspaces_dir,
             profiles: HashMap::new(),
             active: None,
+            walk_policy: WalkPolicy::default(),
         }
     }
 
+    /// Use `policy` for [`enforce_quota`](Self::enforce_quota) and snapshot walks.
+    pub fn with_walk_policy(mut self, policy: WalkPolicy) -> Self {
+        self.walk_policy = policy;
+        self
+    }
+
     /// Load all workspace profiles from disk.
     ///
     /// Each subdirectory of `workspaces_dir` that contains a `profile.toml`
@@ -239,6 +308,240 @@ impl WorkspaceManager {
     pub fn workspaces_dir(&self) -> &Path {
         &self.workspaces_dir
     }
+
+    /// Fail if the active workspace (or, with none active, all workspaces)
+    /// holds more than `max_bytes`, so a runaway agent cannot fill the disk.
+    ///
+    /// Call before and after command execution. The walk stops as soon as the
+    /// limit is passed and follows the manager's [`WalkPolicy`]. Directories
+    /// beyond its depth cannot be measured, so their presence also fails the
+    /// check rather than letting files hide below the limit. Returns the bytes
+    /// in use when within quota.
+    pub async fn enforce_quota(&self, max_bytes: u64) -> Result<u64> {
+        let root = self.snapshot_root();
+        let walk_root = root.clone();
+        let policy = self.walk_policy;
+        let (used, exceeded, skipped) = tokio::task::spawn_blocking(move || {
+            directory_size_capped(&walk_root, max_bytes, policy)
+        })
+        .await
+        .context("workspace size walk panicked")?
+        .with_context(|| format!("measuring workspace size: {}", root.display()))?;
+        if exceeded {
+            bail!(
+                "workspace {} exceeds its quota of {max_bytes} bytes",
+                root.display()
+            );
+        }
+        if !skipped.is_empty() {
+            let skipped: Vec<_> = skipped
+                .iter()
+                .map(|dir| dir.strip_prefix(&root).unwrap_or(dir).display().to_string())
+                .collect();
+            bail!(
+                "workspace {} has directories deeper than the walk depth limit of {}, so its \
+                 quota cannot be checked: {}",
+                root.display(),
+                policy.max_depth,
+                skipped.join(", ")
+            );
+        }
+        Ok(used)
+    }
+
+    /// Fingerprint every file in the active workspace (or, with none active,
+    /// all workspaces), e.g. before running a command.
+    ///
+    /// The walk follows the manager's [`WalkPolicy`]; directories beyond its
+    /// depth are listed in [`WorkspaceSnapshot::skipped`].
+    pub async fn snapshot(&self) -> Result<WorkspaceSnapshot> {
+        self.snapshot_since(&WorkspaceSnapshot::default()).await
+    }
+
+    /// Like [`snapshot`](Self::snapshot), but reuse `previous` hashes for
+    /// files whose size and mtime are unchanged, so only touched files are
+    /// read.
+    pub async fn snapshot_since(&self, previous: &WorkspaceSnapshot) -> Result<WorkspaceSnapshot> {
+        let root = self.snapshot_root();
+        let walk_root = root.clone();
+        let previous = previous.clone();
+        let policy = self.walk_policy;
+        tokio::task::spawn_blocking(move || fingerprint_tree(&walk_root, &previous, policy))
+            .await
+            .context("workspace snapshot walk panicked")?
+            .with_context(|| format!("snapshotting workspace: {}", root.display()))
+    }
+
+    /// Paths added, modified, or deleted between `before` and `after`.
+    ///
+    /// Files with equal size and mtime count as unchanged; otherwise a
+    /// same-sized file is compared by hash, so a bare `touch` is not reported.
+    pub fn diff(before: &WorkspaceSnapshot, after: &WorkspaceSnapshot) -> WorkspaceDiff {
+        let mut diff = WorkspaceDiff::default();
+        for (path, new) in &after.files {
+            match before.files.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(old) if !same_contents(old, new) => diff.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.deleted = before
+            .files
+            .keys()
+            .filter(|path| !after.files.contains_key(*path))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    fn snapshot_root(&self) -> PathBuf {
+        match self.active_name() {
+            Some(name) => self.workspace_dir(name),
+            None => self.workspaces_dir.clone(),
+        }
+    }
+}
+
+fn same_contents(old: &FileFingerprint, new: &FileFingerprint) -> bool {
+    if old.size != new.size {
+        return false;
+    }
+    (old.modified.is_some() && old.modified == new.modified) || old.hash == new.hash
+}
+
+/// Fingerprint every regular file under `root`, hashing only files whose size
+/// or mtime differ from `previous`. A missing root is an empty snapshot.
+fn fingerprint_tree(
+    root: &Path,
+    previous: &WorkspaceSnapshot,
+    policy: WalkPolicy,
+) -> std::io::Result<WorkspaceSnapshot> {
+    let mut files = BTreeMap::new();
+    let skipped = walk_files(root, policy, |path, metadata| {
+        let size = metadata.len();
+        let modified = metadata.modified().ok();
+        let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        let hash = match previous.files.get(&relative) {
+            Some(old) if old.size == size && modified.is_some() && old.modified == modified => {
+                old.hash.clone()
+            }
+            _ => hash_file(path)?,
+        };
+        files.insert(
+            relative,
+            FileFingerprint {
+                size,
+                modified,
+                hash,
+            },
+        );
+        Ok(ControlFlow::Continue(()))
+    })?;
+    let skipped = skipped
+        .into_iter()
+        .map(|path| {
+            path.strip_prefix(root)
+                .map(Path::to_path_buf)
+                .unwrap_or(path)
+        })
+        .collect();
+    Ok(WorkspaceSnapshot { files, skipped })
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Sum file sizes under `root`, stopping early once `limit` is passed.
+///
+/// Returns the bytes counted, whether the limit was exceeded, and the
+/// directories skipped for depth. A missing root counts as empty.
+fn directory_size_capped(
+    root: &Path,
+    limit: u64,
+    policy: WalkPolicy,
+) -> std::io::Result<(u64, bool, Vec<PathBuf>)> {
+    let mut total = 0u64;
+    let skipped = walk_files(root, policy, |_, metadata| {
+        total = total.saturating_add(metadata.len());
+        Ok(if total > limit {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        })
+    })?;
+    Ok((total, total > limit, skipped))
+}
+
+/// Call `visit` with the path and metadata of each regular file under `root`
+/// until it breaks, traversing as `policy` allows.
+///
+/// Returns the directories skipped for being deeper than
+/// [`WalkPolicy::max_depth`]. A missing root has no files.
+fn walk_files(
+    root: &Path,
+    policy: WalkPolicy,
+    mut visit: impl FnMut(&Path, &std::fs::Metadata) -> std::io::Result<ControlFlow<()>>,
+) -> std::io::Result<Vec<PathBuf>> {
+    let canonical_root = match root.canonicalize() {
+        Ok(root) => root,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut skipped = Vec::new();
+    // Only symlinks can lead back into a directory, so loops need tracking
+    // only when they are followed.
+    let mut visited = HashSet::from([canonical_root.clone()]);
+    let mut pending = vec![(root.to_path_buf(), 0usize)];
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = if entry.file_type()?.is_symlink() {
+                if !policy.follow_symlinks {
+                    continue;
+                }
+                match path.canonicalize() {
+                    Ok(target) if target.starts_with(&canonical_root) => {
+                        std::fs::metadata(&target)?
+                    }
+                    // Dangling, or pointing out of the workspace.
+                    _ => continue,
+                }
+            } else {
+                entry.metadata()?
+            };
+
+            if metadata.is_dir() {
+                if depth >= policy.max_depth {
+                    skipped.push(path);
+                    continue;
+                }
+                if policy.follow_symlinks && !visited.insert(path.canonicalize()?) {
+                    continue;
+                }
+                pending.push((path, depth + 1));
+            } else if metadata.is_file() && visit(&path, &metadata)?.is_break() {
+                return Ok(skipped);
+            }
+        }
+    }
+    if !skipped.is_empty() {
+        tracing::warn!(
+            root = %root.display(),
+            skipped = skipped.len(),
+            max_depth = policy.max_depth,
+            "workspace walk skipped directories beyond the depth limit"
+        );
+    }
+    Ok(skipped)
 }
 
 #[cfg(test)]
@@ -379,4 +682,143 @@ mod tests {
         assert!(exported.contains("***"));
         assert!(!exported.contains("secret-cred-id"));
     }
+
+    #[tokio::test]
+    async fn workspace_snapshot_diff_detects_created_modified_and_deleted_files() {
+        let tmp = TempDir::new().unwrap();
+        let mut mgr = WorkspaceManager::new(tmp.path().to_path_buf());
+        mgr.create("client_a").await.unwrap();
+        mgr.switch("client_a").unwrap();
+        let dir = mgr.workspace_dir("client_a");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/keep.rs"), "fn keep() {}").unwrap();
+        std::fs::write(dir.join("src/edit.rs"), "fn v1() {}").unwrap();
+        std::fs::write(dir.join("old.txt"), "bye").unwrap();
+
+        let before = mgr.snapshot().await.unwrap();
+        std::fs::write(dir.join("src/edit.rs"), "fn v2() {}").unwrap();
+        std::fs::write(dir.join("new.txt"), "hi").unwrap();
+        std::fs::remove_file(dir.join("old.txt")).unwrap();
+        let after = mgr.snapshot_since(&before).await.unwrap();
+
+        let diff = WorkspaceManager::diff(&before, &after);
+        assert_eq!(diff.added, [PathBuf::from("new.txt")]);
+        assert_eq!(diff.modified, [PathBuf::from("src/edit.rs")]);
+        assert_eq!(diff.deleted, [PathBuf::from("old.txt")]);
+        assert!(WorkspaceManager::diff(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn workspace_diff_ignores_touched_but_identical_files() {
+        let fingerprint = |modified_secs: u64| FileFingerprint {
+            size: 3,
+            modified: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(modified_secs)),
+            hash: "abc".into(),
+        };
+        let before = WorkspaceSnapshot {
+            files: BTreeMap::from([(PathBuf::from("a.txt"), fingerprint(1))]),
+            ..WorkspaceSnapshot::default()
+        };
+        let after = WorkspaceSnapshot {
+            files: BTreeMap::from([(PathBuf::from("a.txt"), fingerprint(2))]),
+            ..WorkspaceSnapshot::default()
+        };
+        assert!(WorkspaceManager::diff(&before, &after).is_empty());
+    }
+
+    #[tokio::test]
+    async fn workspace_manager_quota_allows_workspace_under_limit() {
+        let tmp = TempDir::new().unwrap();
+        let mut mgr = WorkspaceManager::new(tmp.path().to_path_buf());
+        mgr.create("client_a").await.unwrap();
+        mgr.switch("client_a").unwrap();
+        let nested = mgr.workspace_dir("client_a").join("out");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("small.bin"), vec![0u8; 1024]).unwrap();
+
+        let used = mgr.enforce_quota(1024 * 1024).await.unwrap();
+        assert!(used >= 1024);
+    }
+
+    #[tokio::test]
+    async fn workspace_manager_quota_rejects_workspace_over_limit() {
+        let tmp = TempDir::new().unwrap();
+        let mut mgr = WorkspaceManager::new(tmp.path().to_path_buf());
+        mgr.create("client_a").await.unwrap();
+        mgr.switch("client_a").unwrap();
+        let nested = mgr.workspace_dir("client_a").join("deep/er");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("big.bin"), vec![0u8; 4096]).unwrap();
+
+        let err = mgr.enforce_quota(1000).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds its quota"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn workspace_walk_terminates_on_symlink_loops() {
+        let tmp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "nope").unwrap();
+        let root = tmp.path().join("client_a");
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/b/file.txt"), "data").unwrap();
+        std::os::unix::fs::symlink("../..", root.join("a/b/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("alias")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("escape")).unwrap();
+
+        for follow_symlinks in [false, true] {
+            let mut mgr =
+                WorkspaceManager::new(tmp.path().to_path_buf()).with_walk_policy(WalkPolicy {
+                    follow_symlinks,
+                    ..WalkPolicy::default()
+                });
+            mgr.create("client_a").await.unwrap();
+            mgr.switch("client_a").unwrap();
+
+            let snapshot = mgr.snapshot().await.unwrap();
+            let files: Vec<&Path> = snapshot
+                .files
+                .keys()
+                .map(PathBuf::as_path)
+                .filter(|path| !path.ends_with("profile.toml"))
+                .collect();
+            // The target directory is walked once, via whichever path reached it first.
+            assert_eq!(
+                files.len(),
+                1,
+                "follow_symlinks={follow_symlinks}: {files:?}"
+            );
+            assert!(files[0].ends_with("b/file.txt"));
+            assert!(mgr.enforce_quota(1024).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn workspace_walk_respects_max_depth() {
+        let tmp = TempDir::new().unwrap();
+        let mut mgr =
+            WorkspaceManager::new(tmp.path().to_path_buf()).with_walk_policy(WalkPolicy {
+                max_depth: 2,
+                ..WalkPolicy::default()
+            });
+        mgr.create("client_a").await.unwrap();
+        mgr.switch("client_a").unwrap();
+        let dir = mgr.workspace_dir("client_a");
+        std::fs::create_dir_all(dir.join("d1/d2/d3/d4")).unwrap();
+        std::fs::write(dir.join("d1/one.txt"), "1").unwrap();
+        std::fs::write(dir.join("d1/d2/two.txt"), "2").unwrap();
+        std::fs::write(dir.join("d1/d2/d3/three.bin"), vec![0u8; 4096]).unwrap();
+
+        let snapshot = mgr.snapshot().await.unwrap();
+        assert!(snapshot.files.contains_key(Path::new("d1/one.txt")));
+        assert!(snapshot.files.contains_key(Path::new("d1/d2/two.txt")));
+        assert!(!snapshot.files.contains_key(Path::new("d1/d2/d3/three.bin")));
+        assert_eq!(snapshot.skipped, [PathBuf::from("d1/d2/d3")]);
+
+        // The oversized file is beyond the depth limit, so the quota cannot
+        // be vouched for.
+        let err = mgr.enforce_quota(1024).await.unwrap_err().to_string();
+        assert!(err.contains("d1/d2/d3"), "{err}");
+    }
 }
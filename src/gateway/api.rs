@@ -3,6 +3,7 @@
 //! All `/api/*` routes require bearer token authentication (PairingGuard).
 
 use super::AppState;
+use crate::util::{NumberFormat, humanize_bytes, relativize};
 use axum::{
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode, header},
@@ -23,7 +24,7 @@ fn extract_bearer_token(headers: &HeaderMap) -> Option<&str> {
 }
 
 /// Verify bearer token against PairingGuard. Returns error response if unauthorized.
-fn require_auth(
+pub(super) fn require_auth(
     state: &AppState,
     headers: &HeaderMap,
 ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
@@ -787,8 +788,194 @@ pub async fn handle_api_health(
     Json(serde_json::json!({"health": snapshot})).into_response()
 }
 
+/// GET /api/processes — running agent commands
+pub async fn handle_api_processes_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let processes = crate::runtime::ProcessRegistry::global().list();
+    Json(serde_json::json!({"processes": processes})).into_response()
+}
+
+/// DELETE /api/processes/:id — kill a running command's process group
+pub async fn handle_api_process_kill(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    if crate::runtime::ProcessRegistry::global().kill(id) {
+        Json(serde_json::json!({"killed": true, "id": id})).into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Process not found"})),
+        )
+            .into_response()
+    }
+}
+
+/// GET /api/version — build name and version (cacheable via `ETag`)
+pub async fn handle_api_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let body = serde_json::json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+    });
+    json_with_etag(&body, &headers)
+}
+
+/// GET /api/runtime — capabilities of the configured runtime (cacheable via `ETag`)
+pub async fn handle_api_runtime(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let runtime_config = state.config.lock().runtime.clone();
+    let runtime = match crate::runtime::detect_runtime(&runtime_config) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Runtime unavailable: {e}")})),
+            )
+                .into_response();
+        }
+    };
+
+    let body = serde_json::json!({
+        "name": runtime.name(),
+        "shell_access": runtime.has_shell_access(),
+        "filesystem_access": runtime.has_filesystem_access(),
+        "network_access": runtime.has_network_access(),
+        "writable_tmp": runtime.has_writable_tmp(),
+        "long_running": runtime.supports_long_running(),
+        "memory_budget": runtime.memory_budget(),
+        "max_concurrent_processes": runtime.max_concurrent_processes(),
+        "default_command_timeout_secs": runtime
+            .default_command_timeout()
+            .map(|timeout| timeout.as_secs()),
+    });
+    json_with_etag(&body, &headers)
+}
+
+/// GET /api/runtime/storage — free space at the runtime storage path.
+///
+/// Kept out of `/api/runtime` so that response stays static and cacheable;
+/// this reading changes constantly and is never cached. The humanized size
+/// follows the request's `Accept-Language`, and the path is shown relative
+/// to the workspace when inside it.
+pub async fn handle_api_runtime_storage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let (runtime_config, workspace_dir) = {
+        let config = state.config.lock();
+        (config.runtime.clone(), config.workspace_dir.clone())
+    };
+    let runtime = match crate::runtime::detect_runtime(&runtime_config) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Runtime unavailable: {e}")})),
+            )
+                .into_response();
+        }
+    };
+
+    let locale = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split([',', ';']).next())
+        .unwrap_or_default();
+    let format = NumberFormat::for_locale(locale.trim());
+    let available = runtime.storage_available_bytes();
+    (
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(serde_json::json!({
+            "storage_path": relativize(&runtime.storage_path(), &workspace_dir),
+            "storage_available_bytes": available,
+            "storage_available": available.map(|bytes| humanize_bytes(bytes, &format)),
+        })),
+    )
+        .into_response()
+}
+
+/// GET /api/runtime/env — environment commands receive, secrets masked
+pub async fn handle_api_runtime_env(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let (runtime_config, workspace_dir) = {
+        let config = state.config.lock();
+        (config.runtime.clone(), config.workspace_dir.clone())
+    };
+    let runtime = match crate::runtime::detect_runtime(&runtime_config) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Runtime unavailable: {e}")})),
+            )
+                .into_response();
+        }
+    };
+
+    let env: Vec<serde_json::Value> = runtime
+        .effective_environment(&workspace_dir)
+        .into_iter()
+        .map(|(name, value)| serde_json::json!({"name": name, "value": value}))
+        .collect();
+    Json(serde_json::json!({"runtime": runtime.name(), "env": env})).into_response()
+}
+
 // ── Helpers ─────────────────────────────────────────────────────
 
+/// Serve `body` as JSON with an `ETag` hashed from its bytes, answering
+/// `304 Not Modified` when `If-None-Match` already names that tag.
+fn json_with_etag(body: &serde_json::Value, headers: &HeaderMap) -> axum::response::Response {
+    use sha2::{Digest, Sha256};
+
+    let bytes = serde_json::to_vec(body).unwrap_or_default();
+    let etag = format!("\"{}\"", hex::encode(&Sha256::digest(&bytes)[..16]));
+    if super::static_files::etag_matches(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+    (
+        [
+            (header::ETAG, etag),
+            (header::CONTENT_TYPE, "application/json".to_string()),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
 fn is_masked_secret(value: &str) -> bool {
     value == MASKED_SECRET
 }
@@ -1438,6 +1625,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(crate::gateway::readiness::ReadinessCache::default()),
             path_prefix: String::new(),
         }
     }
@@ -2040,4 +2228,179 @@ mod tests {
         let config = state.config.lock().clone();
         assert!(crate::cron::list_jobs(&config).unwrap().is_empty());
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_stream_enforces_policy_and_streams_output() {
+        use crate::gateway::sse::{CommandStreamRequest, handle_command_stream};
+
+        let mut config = crate::config::Config::default();
+        config.workspace_dir = std::env::temp_dir();
+        let state = test_state(config);
+        let request = |command: &str| {
+            Json(CommandStreamRequest {
+                command: command.to_string(),
+            })
+        };
+
+        let denied =
+            handle_command_stream(State(state.clone()), HeaderMap::new(), request("rm -rf /"))
+                .await
+                .into_response();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+
+        let response =
+            handle_command_stream(State(state), HeaderMap::new(), request("echo streamed"))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("event: output"), "{body}");
+        assert!(body.contains("streamed"), "{body}");
+        assert!(body.contains("event: done"), "{body}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn processes_api_lists_and_kills_running_command() {
+        let state = test_state(crate::config::Config::default());
+        let command = "sleep 30 # processes_api_lists_and_kills_running_command";
+        let running = tokio::spawn(async move {
+            crate::runtime::spawn_streaming(
+                &crate::runtime::NativeRuntime::new(),
+                command,
+                &std::env::temp_dir(),
+                &crate::runtime::StreamOptions::default(),
+                |_| {},
+            )
+            .await
+        });
+
+        let listed = async {
+            loop {
+                let response = handle_api_processes_list(State(state.clone()), HeaderMap::new())
+                    .await
+                    .into_response();
+                let json = response_json(response).await;
+                let found = json["processes"]
+                    .as_array()
+                    .expect("processes array")
+                    .iter()
+                    .find(|p| p["command"] == command)
+                    .cloned();
+                if let Some(found) = found {
+                    return found;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
+        let process = tokio::time::timeout(Duration::from_secs(5), listed)
+            .await
+            .expect("command should be listed");
+        assert_eq!(process["runtime"], "native");
+        assert!(process["pid"].is_u64());
+        let id = process["id"].as_u64().unwrap();
+
+        let response = handle_api_process_kill(State(state.clone()), HeaderMap::new(), Path(id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let result = tokio::time::timeout(Duration::from_secs(5), running)
+            .await
+            .expect("killed command should exit promptly")
+            .unwrap();
+        assert!(!result.success());
+        assert!(
+            crate::runtime::ProcessRegistry::global()
+                .list()
+                .iter()
+                .all(|p| p.id != id)
+        );
+
+        let response = handle_api_process_kill(State(state), HeaderMap::new(), Path(id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn version_and_runtime_answer_304_for_matching_etag() {
+        let state = test_state(crate::config::Config::default());
+
+        let first = handle_api_version(State(state.clone()), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].clone();
+        let json = response_json(first).await;
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.clone());
+        let repeat = handle_api_version(State(state.clone()), headers)
+            .await
+            .into_response();
+        assert_eq!(repeat.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(repeat.headers()[header::ETAG], etag);
+        assert!(
+            repeat
+                .into_body()
+                .collect()
+                .await
+                .unwrap()
+                .to_bytes()
+                .is_empty()
+        );
+
+        let runtime = handle_api_runtime(State(state.clone()), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(runtime.status(), StatusCode::OK);
+        let runtime_etag = runtime.headers()[header::ETAG].clone();
+        assert_ne!(runtime_etag, etag);
+        let json = response_json(runtime).await;
+        assert!(json.get("storage_available_bytes").is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, runtime_etag);
+        let repeat = handle_api_runtime(State(state.clone()), headers)
+            .await
+            .into_response();
+        assert_eq!(repeat.status(), StatusCode::NOT_MODIFIED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            axum::http::HeaderValue::from_static("de-DE,de;q=0.9,en;q=0.8"),
+        );
+        let storage = handle_api_runtime_storage(State(state), headers)
+            .await
+            .into_response();
+        assert_eq!(storage.status(), StatusCode::OK);
+        assert!(storage.headers().get(header::ETAG).is_none());
+        assert_eq!(storage.headers()[header::CACHE_CONTROL], "no-store");
+        let json = response_json(storage).await;
+        let free = json["storage_available_bytes"].as_u64().unwrap();
+        assert!(free > 0);
+        assert_eq!(
+            json["storage_available"],
+            humanize_bytes(free, &NumberFormat::COMMA_DECIMAL)
+        );
+    }
+
+    #[tokio::test]
+    async fn runtime_storage_path_is_relative_to_the_workspace() {
+        use crate::runtime::RuntimeAdapter;
+
+        let storage_path = crate::runtime::NativeRuntime::new().storage_path();
+        let mut config = crate::config::Config::default();
+        config.workspace_dir = storage_path.parent().unwrap().to_path_buf();
+        let response = handle_api_runtime_storage(State(test_state(config)), HeaderMap::new())
+            .await
+            .into_response();
+        let json = response_json(response).await;
+        assert_eq!(json["storage_path"], ".R.A.I.N.");
+    }
 }
@@ -0,0 +1,889 @@
+//! Workspace file transfer API for the web dashboard.
+//!
+//! Large files are uploaded in `Content-Range` chunks so a single request never
+//! has to outlive the gateway request timeout. Downloads honour `Range` so
+//! interrupted artifact transfers can resume, and `If-None-Match` /
+//! `If-Modified-Since` so unchanged artifacts are not re-transferred. The
+//! whole workspace can be downloaded as a tar archive streamed as it is built.
+
+use super::AppState;
+use super::api::require_auth;
+use super::safe_path::{PathError, safe_join};
+use super::static_files::{ByteRange, etag_matches, parse_byte_range};
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path as AxumPath, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio_util::io::ReaderStream;
+
+/// Default maximum assembled size of a single uploaded file (512 MiB);
+/// overridden by `[gateway] max_upload_bytes`.
+pub const MAX_UPLOAD_TOTAL_BYTES: u64 = 512 * 1024 * 1024;
+/// Maximum size of a single upload chunk request body (8 MiB).
+pub const MAX_UPLOAD_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+/// Workspace subdirectory holding partially uploaded files.
+const UPLOAD_STAGING_DIR: &str = ".uploads";
+/// Default cap on the total file size packed into a workspace archive (1 GiB);
+/// overridden by `[gateway] max_archive_bytes`.
+pub const MAX_ARCHIVE_BYTES: u64 = 1024 * 1024 * 1024;
+/// Size of the body chunks a workspace archive is streamed in (64 KiB).
+const ARCHIVE_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    pub name: String,
+}
+
+/// Parsed `Content-Range: bytes <start>-<end>/<total>` request header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkRange {
+    pub start: u64,
+    pub end: u64,
+    pub total: u64,
+}
+
+impl ChunkRange {
+    /// Parse an upload `Content-Range` header. The total size must be known.
+    pub fn parse(value: &str) -> Option<Self> {
+        let rest = value.trim().strip_prefix("bytes ")?;
+        let (span, total) = rest.split_once('/')?;
+        let (start, end) = span.split_once('-')?;
+        let range = Self {
+            start: start.trim().parse().ok()?,
+            end: end.trim().parse().ok()?,
+            total: total.trim().parse().ok()?,
+        };
+        (range.start <= range.end && range.end < range.total).then_some(range)
+    }
+
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Progress of an upload after a chunk has been stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub name: String,
+    pub received: u64,
+    pub total: u64,
+    pub complete: bool,
+}
+
+/// Reduce a client-supplied file name to a single safe path component.
+///
+/// Returns `None` for names that are empty, contain path separators or NUL,
+/// or are `.`/`..`, so uploads can never escape the workspace directory.
+pub fn sanitize_filename(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\', '\0'])
+        || name.chars().any(char::is_control)
+    {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Store one chunk of an upload and promote the file into the workspace once
+/// every byte has arrived.
+///
+/// Chunks are written into `<workspace>/.uploads/<name>.part`. A chunk may
+/// re-send bytes already received (so interrupted clients can resume) but may
+/// not leave a gap. A chunk that would take the file past `max_total` is
+/// rejected with `413` and the partial file is deleted.
+pub async fn store_upload_chunk(
+    workspace_dir: &Path,
+    name: &str,
+    range: ChunkRange,
+    data: &[u8],
+    max_total: u64,
+) -> Result<UploadProgress, (StatusCode, String)> {
+    let name = sanitize_filename(name).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid upload file name: {name:?}"),
+        )
+    })?;
+    tokio::fs::create_dir_all(workspace_dir)
+        .await
+        .map_err(internal_error)?;
+    let staging_dir = safe_join(workspace_dir, UPLOAD_STAGING_DIR).map_err(path_error)?;
+    let part_path = staging_dir.join(format!("{name}.part"));
+    if range.total > max_total {
+        // Earlier chunks may have declared a smaller total; drop what they left.
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Upload exceeds maximum size of {max_total} bytes"),
+        ));
+    }
+    if range.len() != data.len() as u64 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Content-Range covers {} bytes but body has {}",
+                range.len(),
+                data.len()
+            ),
+        ));
+    }
+
+    tokio::fs::create_dir_all(&staging_dir)
+        .await
+        .map_err(internal_error)?;
+
+    let received = match tokio::fs::metadata(&part_path).await {
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    };
+    if range.start > received {
+        return Err((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            format!(
+                "Chunk starts at {} but only {received} bytes received",
+                range.start
+            ),
+        ));
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&part_path)
+        .await
+        .map_err(internal_error)?;
+    file.seek(std::io::SeekFrom::Start(range.start))
+        .await
+        .map_err(internal_error)?;
+    file.write_all(data).await.map_err(internal_error)?;
+    file.flush().await.map_err(internal_error)?;
+    drop(file);
+
+    let received = received.max(range.end + 1);
+    let complete = received == range.total;
+    if complete {
+        let final_path: PathBuf = safe_join(workspace_dir, &name).map_err(path_error)?;
+        tokio::fs::rename(&part_path, &final_path)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    Ok(UploadProgress {
+        name,
+        received,
+        total: range.total,
+        complete,
+    })
+}
+
+fn internal_error(err: std::io::Error) -> (StatusCode, String) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Workspace file operation failed: {err}"),
+    )
+}
+
+fn path_error(err: PathError) -> (StatusCode, String) {
+    match err {
+        PathError::RootUnavailable(err) | PathError::Io(err) => internal_error(err),
+        _ => (
+            StatusCode::FORBIDDEN,
+            "Path escapes the workspace".to_string(),
+        ),
+    }
+}
+
+/// POST /api/workspace/upload?name=<file> — store one upload chunk
+///
+/// Requests without `Content-Range` are treated as a single-chunk upload.
+pub async fn handle_api_workspace_upload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let range = match headers.get(header::CONTENT_RANGE) {
+        Some(value) => match value.to_str().ok().and_then(ChunkRange::parse) {
+            Some(range) => range,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "Malformed Content-Range header"})),
+                )
+                    .into_response();
+            }
+        },
+        None if body.is_empty() => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Upload body is empty"})),
+            )
+                .into_response();
+        }
+        None => ChunkRange {
+            start: 0,
+            end: body.len() as u64 - 1,
+            total: body.len() as u64,
+        },
+    };
+
+    let (workspace_dir, max_upload_bytes) = {
+        let config = state.config.lock();
+        (
+            config.workspace_dir.clone(),
+            config.gateway.max_upload_bytes,
+        )
+    };
+    match store_upload_chunk(&workspace_dir, &query.name, range, &body, max_upload_bytes).await {
+        Ok(progress) => Json(serde_json::json!({
+            "name": progress.name,
+            "received": progress.received,
+            "total": progress.total,
+            "complete": progress.complete,
+        }))
+        .into_response(),
+        Err((status, message)) => {
+            (status, Json(serde_json::json!({"error": message}))).into_response()
+        }
+    }
+}
+
+/// Resolve `relative` to an existing file inside `workspace_dir`.
+///
+/// Paths that could reach outside the workspace, lexically or through a
+/// symlink, are forbidden even when the target does not exist.
+fn resolve_workspace_file(
+    workspace_dir: &Path,
+    relative: &str,
+) -> Result<PathBuf, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, format!("File not found: {relative}"));
+    let resolved = match safe_join(workspace_dir, relative) {
+        Ok(resolved) => resolved,
+        Err(PathError::RootUnavailable(_) | PathError::Io(_)) => return Err(not_found()),
+        Err(err) => return Err(path_error(err)),
+    };
+    if !resolved.is_file() {
+        return Err(not_found());
+    }
+    Ok(resolved)
+}
+
+/// Validators derived from file metadata: an `ETag` built from size and
+/// mtime, and the mtime itself for `Last-Modified`.
+struct FileValidators {
+    etag: String,
+    modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl FileValidators {
+    fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        let modified = metadata.modified().ok();
+        let mtime_nanos = modified
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        Self {
+            etag: format!("\"{:x}-{mtime_nanos:x}\"", metadata.len()),
+            modified: modified.map(chrono::DateTime::<chrono::Utc>::from),
+        }
+    }
+
+    fn last_modified(&self) -> Option<String> {
+        self.modified
+            .map(|time| time.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+    }
+
+    /// Whether the request's conditional headers show the client copy is current.
+    ///
+    /// `If-None-Match` takes precedence over `If-Modified-Since` (RFC 9110 §13.2.2).
+    fn not_modified(&self, headers: &HeaderMap) -> bool {
+        if headers.contains_key(header::IF_NONE_MATCH) {
+            return etag_matches(headers, &self.etag);
+        }
+        let since = headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok());
+        match (since, self.modified) {
+            // HTTP dates have one-second resolution.
+            (Some(since), Some(modified)) => modified.timestamp() <= since.timestamp(),
+            _ => false,
+        }
+    }
+}
+
+/// Serve a workspace file, honouring conditional headers and a single-span
+/// `Range` request.
+pub async fn serve_workspace_file(
+    workspace_dir: &Path,
+    relative: &str,
+    headers: &HeaderMap,
+) -> Response {
+    let path = match resolve_workspace_file(workspace_dir, relative) {
+        Ok(path) => path,
+        Err((status, message)) => {
+            return (status, Json(serde_json::json!({"error": message}))).into_response();
+        }
+    };
+
+    let result: std::io::Result<Response> = async {
+        let mut file = tokio::fs::File::open(&path).await?;
+        let metadata = file.metadata().await?;
+        let len = metadata.len();
+        let validators = FileValidators::from_metadata(&metadata);
+        let mut validator_headers = HeaderMap::new();
+        if let Ok(value) = validators.etag.parse() {
+            validator_headers.insert(header::ETAG, value);
+        }
+        if let Some(value) = validators.last_modified().and_then(|v| v.parse().ok()) {
+            validator_headers.insert(header::LAST_MODIFIED, value);
+        }
+        if validators.not_modified(headers) {
+            return Ok((StatusCode::NOT_MODIFIED, validator_headers).into_response());
+        }
+        let range_header = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().replace(['"', '\\'], "_"))
+            .unwrap_or_default();
+        let mime = mime_guess::from_path(&path)
+            .first_or_octet_stream()
+            .to_string();
+        let disposition = format!("attachment; filename=\"{file_name}\"");
+
+        let (status, start, end) = match parse_byte_range(range_header, len) {
+            ByteRange::Full => (StatusCode::OK, 0, len.saturating_sub(1)),
+            ByteRange::Partial { start, end } => (StatusCode::PARTIAL_CONTENT, start, end),
+            ByteRange::Unsatisfiable => {
+                return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{len}"))],
+                )
+                    .into_response());
+            }
+        };
+
+        // Stream the selected bytes rather than buffering them, so a range
+        // covering a multi-gigabyte file costs no more memory than a small one.
+        let body_len = if len == 0 { 0 } else { end - start + 1 };
+        file.seek(std::io::SeekFrom::Start(start)).await?;
+        let body = Body::from_stream(ReaderStream::new(file.take(body_len)));
+
+        let mut response = (
+            status,
+            validator_headers,
+            [
+                (header::CONTENT_TYPE, mime),
+                (header::CONTENT_DISPOSITION, disposition),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+                (header::CONTENT_LENGTH, body_len.to_string()),
+            ],
+            body,
+        )
+            .into_response();
+        if status == StatusCode::PARTIAL_CONTENT {
+            if let Ok(value) = format!("bytes {start}-{end}/{len}").parse() {
+                response.headers_mut().insert(header::CONTENT_RANGE, value);
+            }
+        }
+        Ok(response)
+    }
+    .await;
+
+    result.unwrap_or_else(|e| {
+        let (status, message) = internal_error(e);
+        (status, Json(serde_json::json!({"error": message}))).into_response()
+    })
+}
+
+/// GET /api/workspace/file/*path — download a workspace file
+pub async fn handle_api_workspace_file(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    AxumPath(path): AxumPath<String>,
+) -> Response {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let workspace_dir = state.config.lock().workspace_dir.clone();
+    serve_workspace_file(&workspace_dir, &path, &headers).await
+}
+
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+    /// Gzip the archive (`.tar.gz`) instead of sending a plain tar.
+    #[serde(default)]
+    pub gzip: bool,
+}
+
+/// Workspace files to archive, relative to the root, and their total size.
+///
+/// Symlinks are skipped, as is anything whose name is in `exclude`.
+fn collect_archive_files(root: &Path, exclude: &[String]) -> std::io::Result<(Vec<PathBuf>, u64)> {
+    let mut files = Vec::new();
+    let mut total: u64 = 0;
+    let mut pending = vec![PathBuf::new()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(root.join(&dir))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if exclude.iter().any(|excluded| name == excluded.as_str()) {
+                continue;
+            }
+            let file_type = entry.file_type()?;
+            let relative = dir.join(&name);
+            if file_type.is_dir() {
+                pending.push(relative);
+            } else if file_type.is_file() {
+                total = total.saturating_add(entry.metadata()?.len());
+                files.push(relative);
+            }
+        }
+    }
+    files.sort();
+    Ok((files, total))
+}
+
+/// [`Write`] adapter that forwards buffered chunks to a response body stream.
+///
+/// Fails with `BrokenPipe` once the client has gone away, which stops the
+/// archive from being built any further.
+struct ChannelWriter {
+    tx: mpsc::Sender<std::io::Result<Bytes>>,
+    buf: Vec<u8>,
+}
+
+impl ChannelWriter {
+    fn send_buffered(&mut self) -> std::io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::take(&mut self.buf));
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= ARCHIVE_CHUNK_BYTES {
+            self.send_buffered()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send_buffered()
+    }
+}
+
+/// Append `files` (relative to `root`) to a tar stream written to `writer`.
+fn write_archive<W: Write>(writer: W, root: &Path, files: &[PathBuf]) -> std::io::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    for relative in files {
+        match builder.append_path_with_name(root.join(relative), relative) {
+            Ok(()) => {}
+            // Deleted since the listing; leave it out.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    builder.into_inner()
+}
+
+/// Stream `workspace_dir` as a tar (or `.tar.gz`) archive.
+///
+/// The file listing is checked against `max_bytes` up front so an oversized
+/// workspace is refused with `413` before any body is sent; the archive itself
+/// is produced on a blocking thread and never held in memory.
+pub async fn serve_workspace_archive(
+    workspace_dir: &Path,
+    exclude: Vec<String>,
+    max_bytes: u64,
+    gzip: bool,
+) -> Response {
+    let error = |status: StatusCode, message: String| {
+        (status, Json(serde_json::json!({"error": message}))).into_response()
+    };
+    let root = match safe_join(workspace_dir, "") {
+        Ok(root) => root,
+        Err(_) => {
+            return error(
+                StatusCode::NOT_FOUND,
+                "Workspace directory not found".to_string(),
+            );
+        }
+    };
+
+    let listing_root = root.clone();
+    let listing =
+        tokio::task::spawn_blocking(move || collect_archive_files(&listing_root, &exclude)).await;
+    let files = match listing {
+        Ok(Ok((_, total))) if total > max_bytes => {
+            return error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Workspace is {total} bytes; archives are limited to {max_bytes} bytes"),
+            );
+        }
+        Ok(Ok((files, _))) => files,
+        Ok(Err(err)) => {
+            let (status, message) = internal_error(err);
+            return error(status, message);
+        }
+        Err(err) => {
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Workspace archive listing failed: {err}"),
+            );
+        }
+    };
+
+    let (tx, rx) = mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        let writer = ChannelWriter {
+            tx: tx.clone(),
+            buf: Vec::with_capacity(ARCHIVE_CHUNK_BYTES),
+        };
+        let result = if gzip {
+            let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            write_archive(encoder, &root, &files).and_then(flate2::write::GzEncoder::finish)
+        } else {
+            write_archive(writer, &root, &files)
+        };
+        let finished = result.and_then(|mut writer| writer.flush());
+        if let Err(err) = finished {
+            if err.kind() != std::io::ErrorKind::BrokenPipe {
+                tracing::warn!(error = %err, "workspace archive failed");
+                // Surface the failure as a body error so the client sees a
+                // truncated download rather than a short but valid archive.
+                let _ = tx.blocking_send(Err(err));
+            }
+        }
+    });
+
+    let (content_type, file_name) = if gzip {
+        ("application/gzip", "workspace.tar.gz")
+    } else {
+        ("application/x-tar", "workspace.tar")
+    };
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+/// GET /api/workspace/archive[?gzip=true] — download the workspace as a tar archive
+pub async fn handle_api_workspace_archive(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ArchiveQuery>,
+) -> Response {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let (workspace_dir, exclude, max_bytes) = {
+        let config = state.config.lock();
+        (
+            config.workspace_dir.clone(),
+            config.gateway.archive_exclude.clone(),
+            config.gateway.max_archive_bytes,
+        )
+    };
+    serve_workspace_archive(&workspace_dir, exclude, max_bytes, query.gzip).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .to_vec()
+    }
+
+    #[test]
+    fn chunk_range_parses_and_validates() {
+        assert_eq!(
+            ChunkRange::parse("bytes 0-4/10"),
+            Some(ChunkRange {
+                start: 0,
+                end: 4,
+                total: 10
+            })
+        );
+        assert_eq!(ChunkRange::parse("bytes 5-4/10"), None);
+        assert_eq!(ChunkRange::parse("bytes 0-10/10"), None);
+        assert_eq!(ChunkRange::parse("bytes 0-4/*"), None);
+    }
+
+    #[tokio::test]
+    async fn two_chunk_upload_assembles_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+
+        let first = store_upload_chunk(
+            tmp.path(),
+            "report.txt",
+            ChunkRange::parse("bytes 0-5/11").unwrap(),
+            b"hello ",
+            MAX_UPLOAD_TOTAL_BYTES,
+        )
+        .await
+        .unwrap();
+        assert!(!first.complete);
+        assert_eq!(first.received, 6);
+        assert!(!tmp.path().join("report.txt").exists());
+
+        let second = store_upload_chunk(
+            tmp.path(),
+            "report.txt",
+            ChunkRange::parse("bytes 6-10/11").unwrap(),
+            b"world",
+            MAX_UPLOAD_TOTAL_BYTES,
+        )
+        .await
+        .unwrap();
+        assert!(second.complete);
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("report.txt")).unwrap(),
+            "hello world"
+        );
+        assert!(!tmp.path().join(".uploads/report.txt.part").exists());
+    }
+
+    #[tokio::test]
+    async fn upload_rejects_traversal_name() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let err = store_upload_chunk(
+            tmp.path(),
+            "../escape.txt",
+            ChunkRange::parse("bytes 0-1/2").unwrap(),
+            b"hi",
+            MAX_UPLOAD_TOTAL_BYTES,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+        assert!(!tmp.path().parent().unwrap().join("escape.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn upload_enforces_max_total_size() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let err = store_upload_chunk(
+            tmp.path(),
+            "big.bin",
+            ChunkRange::parse("bytes 0-1/100").unwrap(),
+            b"hi",
+            10,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn upload_over_limit_removes_partial_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let part = tmp.path().join(".uploads/grow.bin.part");
+
+        let first = store_upload_chunk(
+            tmp.path(),
+            "grow.bin",
+            ChunkRange::parse("bytes 0-3/10").unwrap(),
+            b"abcd",
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.received, 4);
+        assert!(part.exists());
+
+        let err = store_upload_chunk(
+            tmp.path(),
+            "grow.bin",
+            ChunkRange::parse("bytes 4-7/100").unwrap(),
+            b"efgh",
+            10,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(!part.exists());
+        assert!(!tmp.path().join("grow.bin").exists());
+    }
+
+    #[tokio::test]
+    async fn download_serves_whole_file_with_disposition() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("out")).unwrap();
+        std::fs::write(tmp.path().join("out/build.log"), "build ok").unwrap();
+
+        let response = serve_workspace_file(tmp.path(), "out/build.log", &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"build.log\""
+        );
+        assert_eq!(body_bytes(response).await, b"build ok");
+    }
+
+    #[tokio::test]
+    async fn download_honours_range_request() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("data.bin"), "0123456789").unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, "bytes=2-5".parse().unwrap());
+        let response = serve_workspace_file(tmp.path(), "data.bin", &headers).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(body_bytes(response).await, b"2345");
+    }
+
+    #[tokio::test]
+    async fn download_rejects_traversal_with_forbidden() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(tmp.path().join("secret.txt"), "nope").unwrap();
+
+        let response = serve_workspace_file(&workspace, "../secret.txt", &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = serve_workspace_file(&workspace, "../missing.txt", &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn download_returns_not_modified_for_unchanged_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("report.txt"), "v1").unwrap();
+
+        let response = serve_workspace_file(tmp.path(), "report.txt", &HeaderMap::new()).await;
+        let etag = response.headers()[header::ETAG].clone();
+        let last_modified = response.headers()[header::LAST_MODIFIED].clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.clone());
+        let response = serve_workspace_file(tmp.path(), "report.txt", &headers).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag);
+        assert!(body_bytes(response).await.is_empty());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MODIFIED_SINCE, last_modified);
+        let response = serve_workspace_file(tmp.path(), "report.txt", &headers).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn download_returns_ok_for_modified_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("report.txt"), "v1").unwrap();
+        let response = serve_workspace_file(tmp.path(), "report.txt", &HeaderMap::new()).await;
+        let etag = response.headers()[header::ETAG].clone();
+
+        std::fs::write(tmp.path().join("report.txt"), "version 2").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag);
+        let response = serve_workspace_file(tmp.path(), "report.txt", &headers).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_bytes(response).await, b"version 2");
+    }
+
+    fn archive_fixture() -> tempfile::TempDir {
+        let tmp = tempfile::TempDir::new().unwrap();
+        for (path, contents) in [
+            ("README.md", "hello"),
+            ("src/main.rs", "fn main() {}"),
+            (".git/HEAD", "ref: refs/heads/main"),
+            ("web/node_modules/pkg/index.js", "module.exports = 1;"),
+        ] {
+            let path = tmp.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        tmp
+    }
+
+    fn archive_entries(reader: impl std::io::Read) -> Vec<(String, String)> {
+        let mut archive = tar::Archive::new(reader);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut contents = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+                (path, contents)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn archive_contains_files_and_skips_excluded() {
+        let tmp = archive_fixture();
+        let exclude = vec![".git".to_string(), "node_modules".to_string()];
+
+        let response = serve_workspace_archive(tmp.path(), exclude.clone(), 1024, false).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-tar"
+        );
+        let expected = [
+            ("README.md".to_string(), "hello".to_string()),
+            ("src/main.rs".to_string(), "fn main() {}".to_string()),
+        ];
+        assert_eq!(archive_entries(&body_bytes(response).await[..]), expected);
+
+        let response = serve_workspace_archive(tmp.path(), exclude, 1024, true).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let gzipped = body_bytes(response).await;
+        assert_eq!(
+            archive_entries(flate2::read::GzDecoder::new(&gzipped[..])),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn archive_over_size_cap_is_rejected() {
+        let tmp = archive_fixture();
+        let response = serve_workspace_archive(tmp.path(), Vec::new(), 16, false).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
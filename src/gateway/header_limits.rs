@@ -0,0 +1,109 @@
+//! Request header limits.
+//!
+//! Requests carrying more headers than `[gateway] max_header_count`, or more
+//! header bytes than `max_header_bytes`, are answered with `431 Request
+//! Header Fields Too Large` before reaching any handler. The HTTP/1 parser in
+//! [`server`](super::server) enforces the same count while reading, so an
+//! oversized header block is never fully buffered.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+/// Caps on the headers of a single request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderLimits {
+    /// Most header fields a request may carry.
+    pub max_count: usize,
+    /// Most bytes the header fields may total, counting each as
+    /// `name: value\r\n`.
+    pub max_bytes: usize,
+}
+
+impl HeaderLimits {
+    /// Whether `headers` stays within both limits.
+    pub fn allows(&self, headers: &HeaderMap) -> bool {
+        if headers.len() > self.max_count {
+            return false;
+        }
+        let bytes: usize = headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len() + 4)
+            .sum();
+        bytes <= self.max_bytes
+    }
+}
+
+/// Middleware rejecting requests whose headers exceed [`HeaderLimits`].
+pub async fn header_limits_middleware(
+    State(limits): State<Arc<HeaderLimits>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if limits.allows(request.headers()) {
+        return next.run(request).await;
+    }
+    tracing::debug!(
+        path = request.uri().path(),
+        count = request.headers().len(),
+        "gateway request headers exceed limits"
+    );
+    StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new().route("/", get(|| async { "ok" })).layer(
+            axum::middleware::from_fn_with_state(
+                Arc::new(HeaderLimits {
+                    max_count: 10,
+                    max_bytes: 256,
+                }),
+                header_limits_middleware,
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn excessive_headers_get_431_while_normal_requests_pass() {
+        let mut flood = axum::http::Request::get("/");
+        for i in 0..20 {
+            flood = flood.header(format!("x-flood-{i}"), "1");
+        }
+        let response = app()
+            .oneshot(flood.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+
+        let oversized = axum::http::Request::get("/")
+            .header("cookie", "a".repeat(512))
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(oversized).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+
+        let normal = axum::http::Request::get("/")
+            .header("accept", "text/html")
+            .header("user-agent", "test")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(normal).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
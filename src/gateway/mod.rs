@@ -11,9 +11,15 @@ pub mod api;
 pub mod api_pairing;
 #[cfg(feature = "plugins-wasm")]
 pub mod api_plugins;
+pub mod api_workspace;
+pub mod header_limits;
 pub mod nodes;
+pub mod readiness;
+pub mod safe_path;
+pub mod server;
 pub mod sse;
 pub mod static_files;
+pub mod timeouts;
 pub mod ws;
 
 use crate::agent::loop_::ModelSwitchState;
@@ -35,18 +41,22 @@ use anyhow::{Context, Result};
 use axum::{
     Router,
     body::Bytes,
-    extract::{ConnectInfo, Query, State},
-    http::{HeaderMap, StatusCode, header},
-    response::{IntoResponse, Json},
+    extract::{ConnectInfo, Query, Request, State},
+    http::{HeaderMap, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
     routing::{delete, get, post, put},
 };
 use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tower_http::CompressionLevel;
+use tower_http::compression::CompressionLayer;
+use tower_http::compression::predicate::{DefaultPredicate, NotForContentType, Predicate};
 use tower_http::limit::RequestBodyLimitLayer;
-use tower_http::timeout::TimeoutLayer;
 use uuid::Uuid;
 
 /// Maximum request body size (64KB) — prevents memory exhaustion
@@ -66,6 +76,150 @@ pub fn gateway_request_timeout_secs() -> u64 {
         .and_then(|v| v.parse().ok())
         .unwrap_or(REQUEST_TIMEOUT_SECS)
 }
+
+/// Environment variable naming a directory of recorded JSON fixtures.
+///
+/// When set, the gateway runs in "dry" mode: see [`FixtureGateway`].
+pub const FIXTURES_DIR_ENV: &str = "rain_GATEWAY_FIXTURES_DIR";
+
+/// "Dry gateway" mode that answers API requests from recorded fixtures.
+///
+/// Lets the web dashboard be developed offline without a live agent. A `GET`
+/// for `/api/runtime` is served from `<dir>/api/runtime.json` when that file
+/// exists; every other request falls through to the real handlers.
+#[derive(Debug, Clone)]
+pub struct FixtureGateway {
+    dir: PathBuf,
+}
+
+impl FixtureGateway {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Enable fixture mode from [`FIXTURES_DIR_ENV`], if set and non-empty.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(FIXTURES_DIR_ENV)
+            .ok()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty())
+            .map(Self::new)
+    }
+
+    /// Map a request path to its fixture file through [`safe_path::safe_join`],
+    /// so neither `..` segments nor symlinks can leave the fixture directory.
+    fn fixture_path(&self, route: &str) -> Option<PathBuf> {
+        let route = route.trim_matches('/');
+        if route.is_empty() {
+            return None;
+        }
+        safe_path::safe_join(&self.dir, &format!("{route}.json")).ok()
+    }
+
+    /// Return the recorded body for `route`, if a fixture exists.
+    async fn lookup(&self, route: &str) -> Option<Vec<u8>> {
+        tokio::fs::read(self.fixture_path(route)?).await.ok()
+    }
+}
+
+/// Middleware serving [`FixtureGateway`] responses ahead of the real handlers.
+async fn fixture_middleware(
+    State(fixtures): State<Arc<FixtureGateway>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() == Method::GET {
+        if let Some(body) = fixtures.lookup(request.uri().path()).await {
+            return (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/json")],
+                body,
+            )
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Seconds a client is asked to wait when [`CommandLimiter`] is saturated.
+const COMMAND_RETRY_AFTER_SECS: u64 = 1;
+
+/// Caps how many command-spawning requests the gateway serves at once.
+///
+/// Requests over the cap are rejected with `503` and `Retry-After` rather than
+/// queued, so a burst cannot pile up unbounded processes.
+#[derive(Debug, Clone)]
+pub struct CommandLimiter {
+    permits: Arc<tokio::sync::Semaphore>,
+}
+
+impl CommandLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            permits: Arc::new(tokio::sync::Semaphore::new(limit.max(1))),
+        }
+    }
+
+    /// Size from `[gateway] max_concurrent_commands`, falling back to the
+    /// configured runtime's `max_concurrent_processes`.
+    pub fn from_config(config: &Config) -> Self {
+        let limit = config.gateway.max_concurrent_commands.unwrap_or_else(|| {
+            crate::runtime::detect_runtime(&config.runtime)
+                .map_or(1, |runtime| runtime.max_concurrent_processes())
+        });
+        Self::new(limit)
+    }
+}
+
+/// Middleware enforcing [`CommandLimiter`] on the routes it wraps.
+async fn command_limit_middleware(
+    State(limiter): State<CommandLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = limiter.permits.try_acquire() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, COMMAND_RETRY_AFTER_SECS.to_string())],
+            "Too many concurrent commands; retry shortly",
+        )
+            .into_response();
+    };
+    next.run(request).await
+}
+
+/// Middleware logging each request and recording it in the runtime metrics.
+///
+/// Requests are labelled by matched route template so path parameters do not
+/// explode metric cardinality; unmatched requests share the `fallback` label.
+async fn request_metrics_middleware(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map_or_else(|| "fallback".to_string(), |path| path.as_str().to_string());
+    let method = request.method().clone();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let elapsed = started.elapsed();
+    let status = response.status().as_u16();
+    tracing::debug!(
+        %method,
+        route = %route,
+        status,
+        elapsed = ?elapsed,
+        "gateway request"
+    );
+    crate::observability::runtime_metrics::record_http_request(
+        &route,
+        method.as_str(),
+        status,
+        elapsed,
+    );
+    response
+}
+
 /// Sliding window used by gateway rate limiting.
 pub const RATE_LIMIT_WINDOW_SECS: u64 = 60;
 /// Fallback max distinct client keys tracked in gateway rate limiter.
@@ -312,6 +466,47 @@ fn normalize_max_keys(configured: usize, fallback: usize) -> usize {
     }
 }
 
+/// Compress responses according to `[gateway.compression]`.
+///
+/// A `CompressionLayer` applies one quality to every algorithm, so brotli and
+/// gzip get a layer each. The outer gzip layer leaves responses that the inner
+/// brotli layer already encoded untouched.
+fn with_compression<S>(router: Router<S>, config: &crate::config::CompressionConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if !config.enabled {
+        return router;
+    }
+    let level = |value: u32| CompressionLevel::Precise(i32::try_from(value).unwrap_or(i32::MAX));
+    // Gzipped workspace archives gain nothing from a second pass.
+    let predicate = DefaultPredicate::new().and(NotForContentType::const_new("application/gzip"));
+    router
+        .layer(
+            CompressionLayer::new()
+                .no_gzip()
+                .quality(level(config.brotli_quality))
+                .compress_when(predicate.clone()),
+        )
+        .layer(
+            CompressionLayer::new()
+                .no_br()
+                .quality(level(config.gzip_level))
+                .compress_when(predicate),
+        )
+}
+
+/// Serve the dashboard document for unmatched GET requests, unless disabled
+/// by `[gateway] spa_fallback`, in which case unmatched routes get axum's
+/// plain `404`.
+fn with_spa_fallback(router: Router<AppState>, enabled: bool) -> Router<AppState> {
+    if enabled {
+        router.fallback(get(static_files::handle_spa_fallback))
+    } else {
+        router
+    }
+}
+
 /// Shared state for all axum handlers
 #[derive(Clone)]
 pub struct AppState {
@@ -357,11 +552,27 @@ pub struct AppState {
     pub device_registry: Option<Arc<api_pairing::DeviceRegistry>>,
     /// Pending pairing request store
     pub pending_pairings: Option<Arc<api_pairing::PairingStore>>,
+    /// Last runtime readiness check, refreshed in the background for `/readyz`
+    pub readiness: Arc<readiness::ReadinessCache>,
+}
+
+/// Enable quiet logging when `[gateway] quiet` or `ZEROCLAW_QUIET` asks for it.
+///
+/// Only ever turns quiet mode on, so the env var cannot be overridden by a
+/// config that leaves `quiet` at its default.
+pub fn apply_quiet_mode(config: &crate::config::GatewayConfig) {
+    if config.quiet || crate::observability::quiet::quiet_requested_by_env() {
+        crate::observability::quiet::set_quiet(true);
+    }
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
 #[allow(clippy::too_many_lines)]
 pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
+    apply_quiet_mode(&config.gateway);
+    if let Err(err) = static_files::verify_assets() {
+        tracing::error!("{err}; the web dashboard may be served corrupted");
+    }
     // ── Security: refuse public bind without tunnel or explicit opt-in ──
     if is_public_bind(host) && config.tunnel.provider == "none" && !config.gateway.allow_public_bind
     {
@@ -417,8 +628,19 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    let runtime_config = runtime::resolve_runtime_config(&config.runtime)?;
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
-        Arc::from(runtime::create_runtime(&config.runtime)?);
+        Arc::from(runtime::create_runtime(&runtime_config)?);
+    if runtime_config.kind == "docker" {
+        // Pull the image in the background so the first command is not slowed
+        // by the download; failures resurface when a command actually runs.
+        let docker = runtime::DockerRuntime::new(runtime_config.docker.clone());
+        tokio::spawn(async move {
+            if let Err(e) = docker.prewarm().await {
+                tracing::warn!("Docker runtime prewarm failed: {e:#}");
+            }
+        });
+    }
     let security = Arc::new(SecurityPolicy::from_config(
         &config.autonomy,
         &config.workspace_dir,
@@ -815,14 +1037,67 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         session_backend,
         device_registry,
         pending_pairings,
+        readiness: Arc::new(readiness::ReadinessCache::default()),
         path_prefix: path_prefix.unwrap_or("").to_string(),
     };
 
+    // Re-check runtime readiness in the background so `/readyz` stays cheap.
+    let readiness_config = state.config.clone();
+    readiness::spawn_refresh(
+        state.readiness.clone(),
+        Duration::from_secs(config.gateway.readiness_check_interval_secs),
+        state.shutdown_tx.subscribe(),
+        move || {
+            let config = readiness_config.clone();
+            async move { readiness::check_runtime(&config).await }
+        },
+    );
+
     // Config PUT needs larger body limit (1MB)
     let config_put_router = Router::new()
         .route("/api/config", put(api::handle_api_config_put))
         .layer(RequestBodyLimitLayer::new(1_048_576));
 
+    let asset_router =
+        config
+            .gateway
+            .asset_prefixes
+            .iter()
+            .fold(Router::new(), |router, prefix| {
+                router.route(
+                    &format!("{prefix}{{*path}}"),
+                    get(static_files::handle_static),
+                )
+            });
+
+    // Command-spawning routes share one concurrency cap
+    let command_limiter = CommandLimiter::from_config(&config);
+    let command_router = Router::new().route("/webhook", post(handle_webhook)).layer(
+        axum::middleware::from_fn_with_state(command_limiter.clone(), command_limit_middleware),
+    );
+    let api_command_router = Router::new()
+        .route(
+            "/api/doctor",
+            get(api::handle_api_doctor).post(api::handle_api_doctor),
+        )
+        .route("/api/cli-tools", get(api::handle_api_cli_tools))
+        .route("/api/commands/stream", post(sse::handle_command_stream))
+        .layer(axum::middleware::from_fn_with_state(
+            command_limiter,
+            command_limit_middleware,
+        ));
+
+    // Workspace uploads arrive in chunks larger than the default body limit
+    let workspace_upload_router = Router::new()
+        .route(
+            "/api/workspace/upload",
+            post(api_workspace::handle_api_workspace_upload),
+        )
+        .with_state(state.clone())
+        .layer(RequestBodyLimitLayer::new(
+            api_workspace::MAX_UPLOAD_CHUNK_BYTES,
+        ));
+
     // Build router with middleware
     let inner = Router::new()
         // ── Admin routes (for CLI management) ──
@@ -831,16 +1106,19 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/admin/paircode/new", post(handle_admin_paircode_new))
         // ── Existing routes ──
         .route("/health", get(handle_health))
+        .route("/readyz", get(handle_readyz))
         .route("/metrics", get(handle_metrics))
+        .route("/favicon.ico", get(static_files::handle_favicon))
         .route("/pair", post(handle_pair))
-        .route("/webhook", post(handle_webhook))
         .route("/whatsapp", get(handle_whatsapp_verify))
         .route("/whatsapp", post(handle_whatsapp_message))
         .route("/linq", post(handle_linq_webhook))
         .route("/wati", get(handle_wati_verify))
         .route("/wati", post(handle_wati_webhook))
-        .route("/nextcloud-talk", post(handle_nextcloud_talk_webhook))
-        // ── Web Dashboard API routes ──
+        .route("/nextcloud-talk", post(handle_nextcloud_talk_webhook));
+
+    // ── Web Dashboard API routes (own body limit, see below) ──
+    let api_router = Router::new()
         .route("/api/status", get(api::handle_api_status))
         .route("/api/config", get(api::handle_api_config_get))
         .route("/api/tools", get(api::handle_api_tools))
@@ -860,18 +1138,27 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
             "/api/integrations/settings",
             get(api::handle_api_integrations_settings),
         )
-        .route(
-            "/api/doctor",
-            get(api::handle_api_doctor).post(api::handle_api_doctor),
-        )
         .route("/api/memory", get(api::handle_api_memory_list))
         .route("/api/memory", post(api::handle_api_memory_store))
         .route("/api/memory/{key}", delete(api::handle_api_memory_delete))
         .route("/api/cost", get(api::handle_api_cost))
-        .route("/api/cli-tools", get(api::handle_api_cli_tools))
         .route("/api/health", get(api::handle_api_health))
+        .route("/api/version", get(api::handle_api_version))
+        .route("/api/runtime", get(api::handle_api_runtime))
+        .route("/api/runtime/env", get(api::handle_api_runtime_env))
+        .route("/api/runtime/storage", get(api::handle_api_runtime_storage))
+        .route("/api/processes", get(api::handle_api_processes_list))
+        .route("/api/processes/{id}", delete(api::handle_api_process_kill))
         .route("/api/sessions", get(api::handle_api_sessions_list))
         .route("/api/sessions/{id}", delete(api::handle_api_session_delete))
+        .route(
+            "/api/workspace/file/{*path}",
+            get(api_workspace::handle_api_workspace_file),
+        )
+        .route(
+            "/api/workspace/archive",
+            get(api_workspace::handle_api_workspace_archive),
+        )
         // ── Pairing + Device management API ──
         .route("/api/pairing/initiate", post(api_pairing::initiate_pairing))
         .route("/api/pair", post(api_pairing::submit_pairing_enhanced))
@@ -880,35 +1167,69 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route(
             "/api/devices/{id}/token/rotate",
             post(api_pairing::rotate_token),
-        );
+        )
+        // ── SSE event stream ──
+        .route("/api/events", get(sse::handle_sse_events))
+        // ── Config PUT with larger body limit ──
+        .merge(config_put_router)
+        // ── Command-spawning API routes behind the concurrency cap ──
+        .merge(api_command_router);
 
     // ── Plugin management API (requires plugins-wasm feature) ──
     #[cfg(feature = "plugins-wasm")]
-    let inner = inner.route(
+    let api_router = api_router.route(
         "/api/plugins",
         get(api_plugins::plugin_routes::list_plugins),
     );
 
+    let api_router = api_router
+        .with_state(state.clone())
+        .layer(axum::middleware::from_fn(request_metrics_middleware))
+        .layer(RequestBodyLimitLayer::new(
+            config.gateway.max_api_body_bytes,
+        ));
+
     let inner = inner
-        // ── SSE event stream ──
-        .route("/api/events", get(sse::handle_sse_events))
         // ── WebSocket agent chat ──
         .route("/ws/chat", get(ws::handle_ws_chat))
         // ── WebSocket node discovery ──
         .route("/ws/nodes", get(nodes::handle_ws_nodes))
-        // ── Static assets (web dashboard) ──
-        .route("/_app/{*path}", get(static_files::handle_static))
-        // ── Config PUT with larger body limit ──
-        .merge(config_put_router)
-        // ── SPA fallback: non-API GET requests serve index.html ──
-        .fallback(get(static_files::handle_spa_fallback))
+        // ── Webhook behind the command concurrency cap ──
+        .merge(command_router)
+        // ── Static assets (web dashboard), one route per configured prefix ──
+        .merge(asset_router);
+    // ── SPA fallback: non-API GET requests serve index.html ──
+    let inner = with_spa_fallback(inner, config.gateway.spa_fallback)
         .with_state(state)
+        .layer(axum::middleware::from_fn(request_metrics_middleware))
         .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
-        .layer(TimeoutLayer::with_status_code(
-            StatusCode::REQUEST_TIMEOUT,
-            Duration::from_secs(gateway_request_timeout_secs()),
+        // ── API routes (configurable body limit, merged after the default one) ──
+        .merge(api_router)
+        // ── Workspace uploads (own body limit, merged after the default one) ──
+        .merge(workspace_upload_router)
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(timeouts::RouteTimeouts::new(
+                Duration::from_secs(gateway_request_timeout_secs()),
+                &config.gateway.route_timeouts,
+            )),
+            timeouts::route_timeout_middleware,
         ));
 
+    // ── Dry gateway: answer matching routes from recorded fixtures ──
+    let inner = match FixtureGateway::from_env() {
+        Some(fixtures) => {
+            tracing::warn!(
+                "Gateway fixture mode enabled ({FIXTURES_DIR_ENV}={}); matching GET routes return recorded responses",
+                fixtures.dir.display()
+            );
+            inner.layer(axum::middleware::from_fn_with_state(
+                Arc::new(fixtures),
+                fixture_middleware,
+            ))
+        }
+        None => inner,
+    };
+
     // Nest under path prefix when configured (axum strips prefix before routing).
     // nest() at "/prefix" handles both "/prefix" and "/prefix/*" but not "/prefix/"
     // with a trailing slash, so we add a fallback redirect for that case.
@@ -921,16 +1242,40 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     } else {
         inner
     };
+    let app = with_compression(app, &config.gateway.compression).layer(
+        axum::middleware::from_fn_with_state(
+            Arc::new(header_limits::HeaderLimits {
+                max_count: config.gateway.max_header_count,
+                max_bytes: config.gateway.max_header_bytes,
+            }),
+            header_limits::header_limits_middleware,
+        ),
+    );
+
+    let connection_timeouts = server::ConnectionTimeouts {
+        header_read: Duration::from_secs(config.gateway.header_read_timeout_secs),
+        keep_alive: match config.gateway.keep_alive_timeout_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        },
+    };
+
+    let connection_limits = server::ConnectionLimits {
+        max_connections: config.gateway.max_connections,
+        max_headers: config.gateway.max_header_count,
+    };
 
     // Run the server with graceful shutdown
-    axum::serve(
+    server::serve(
         listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
+        app,
+        connection_timeouts,
+        connection_limits,
+        async move {
+            let _ = shutdown_rx.changed().await;
+            tracing::info!("🦀 R.A.I.N. Gateway shutting down...");
+        },
     )
-    .with_graceful_shutdown(async move {
-        let _ = shutdown_rx.changed().await;
-        tracing::info!("🦀 R.A.I.N. Gateway shutting down...");
-    })
     .await?;
 
     Ok(())
@@ -951,6 +1296,32 @@ async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
     Json(body)
 }
 
+/// GET /readyz — runtime readiness as healthy, degraded or unhealthy (public)
+///
+/// Unhealthy runtimes answer 503; a probe that times out is only degraded.
+/// Answers from the background check's cached result, probing inline only
+/// until the first check has completed.
+async fn handle_readyz(State(state): State<AppState>) -> impl IntoResponse {
+    let snapshot = match state.readiness.latest() {
+        Some(snapshot) => snapshot,
+        None => state
+            .readiness
+            .record(readiness::check_runtime(&state.config).await),
+    };
+    let status = snapshot.status;
+    let code = if matches!(status, crate::runtime::HealthStatus::Unhealthy(_)) {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    let body = serde_json::json!({
+        "status": status.as_str(),
+        "reason": status.reason(),
+        "checked_at": snapshot.checked_at.to_rfc3339(),
+    });
+    (code, Json(body))
+}
+
 /// Prometheus content type for text exposition format.
 const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
 
@@ -960,7 +1331,7 @@ fn prometheus_disabled_hint() -> String {
     )
 }
 
-/// GET /metrics — Prometheus text exposition format
+/// GET /metrics — Prometheus text exposition format (unauthenticated, like /health)
 async fn handle_metrics(State(state): State<AppState>) -> impl IntoResponse {
     let body = {
         #[cfg(feature = "observability-prometheus")]
@@ -982,6 +1353,7 @@ async fn handle_metrics(State(state): State<AppState>) -> impl IntoResponse {
             prometheus_disabled_hint()
         }
     };
+    let body = body + &crate::observability::runtime_metrics::encode();
 
     (
         StatusCode::OK,
@@ -2021,6 +2393,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -2038,6 +2411,112 @@ mod tests {
         assert!(text.contains("Prometheus backend not enabled"));
     }
 
+    #[tokio::test]
+    async fn readyz_serves_cached_state_without_rechecking() {
+        let state = AppState {
+            config: Arc::new(Mutex::new(Config::default())),
+            provider: Arc::new(MockProvider::default()),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            wati: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(Vec::new()),
+            cost_tracker: None,
+            event_tx: tokio::sync::broadcast::channel(16).0,
+            shutdown_tx: tokio::sync::watch::channel(false).0,
+            node_registry: Arc::new(nodes::NodeRegistry::new(16)),
+            path_prefix: String::new(),
+            session_backend: None,
+            device_registry: None,
+            pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
+        };
+        // The default native runtime would probe healthy, so a 503 below
+        // can only come from the cached result.
+        let recorded = state
+            .readiness
+            .record(crate::runtime::HealthStatus::Unhealthy(
+                "docker down".into(),
+            ));
+
+        for _ in 0..2 {
+            let response = handle_readyz(State(state.clone())).await.into_response();
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["status"], "unhealthy");
+            assert_eq!(json["reason"], "docker down");
+            assert_eq!(json["checked_at"], recorded.checked_at.to_rfc3339());
+        }
+        assert_eq!(state.readiness.latest(), Some(recorded));
+    }
+
+    #[tokio::test]
+    async fn disabled_spa_fallback_returns_plain_not_found() {
+        use tower::ServiceExt;
+
+        let state = AppState {
+            config: Arc::new(Mutex::new(Config::default())),
+            provider: Arc::new(MockProvider::default()),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            wati: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(Vec::new()),
+            cost_tracker: None,
+            event_tx: tokio::sync::broadcast::channel(16).0,
+            shutdown_tx: tokio::sync::watch::channel(false).0,
+            node_registry: Arc::new(nodes::NodeRegistry::new(16)),
+            path_prefix: String::new(),
+            session_backend: None,
+            device_registry: None,
+            pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
+        };
+        let request = || {
+            axum::http::Request::get("/some/dashboard/page")
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+
+        let app = with_spa_fallback(Router::new(), true).with_state(state.clone());
+        let response = app.oneshot(request()).await.unwrap();
+        assert_ne!(response.status(), StatusCode::NOT_FOUND);
+
+        let app = with_spa_fallback(Router::new(), false).with_state(state);
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty());
+    }
+
     #[cfg(feature = "observability-prometheus")]
     #[tokio::test]
     async fn metrics_endpoint_renders_prometheus_output() {
@@ -2077,6 +2556,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -2087,6 +2567,260 @@ mod tests {
         assert!(text.contains("rain_heartbeat_ticks_total 1"));
     }
 
+    #[cfg(feature = "observability-prometheus")]
+    #[tokio::test]
+    async fn request_metrics_middleware_counts_requests() {
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/metrics-test/{id}", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(request_metrics_middleware));
+        for id in ["a", "b"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    axum::http::Request::get(format!("/metrics-test/{id}"))
+                        .body(axum::body::Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let output = crate::observability::runtime_metrics::encode();
+        let counter = output
+            .lines()
+            .find(|line| {
+                line.starts_with("rain_gateway_requests_total")
+                    && line.contains(r#"route="/metrics-test/{id}""#)
+            })
+            .expect("request counter for test route");
+        let value: u64 = counter.rsplit(' ').next().unwrap().parse().unwrap();
+        assert_eq!(value, 2);
+        assert!(output.contains("rain_gateway_request_duration_seconds"));
+    }
+
+    #[tokio::test]
+    async fn fixture_gateway_overrides_real_route_and_falls_through() {
+        use tower::ServiceExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("api")).unwrap();
+        std::fs::write(tmp.path().join("api/runtime.json"), r#"{"name":"fixture"}"#).unwrap();
+
+        let app = Router::new()
+            .route(
+                "/api/runtime",
+                get(|| async { Json(serde_json::json!({"name": "native"})) }),
+            )
+            .route(
+                "/api/status",
+                get(|| async { Json(serde_json::json!({"real": true})) }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(FixtureGateway::new(tmp.path())),
+                fixture_middleware,
+            ));
+
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::get("/api/runtime")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"name":"fixture"}"#);
+
+        let response = app
+            .oneshot(
+                axum::http::Request::get("/api/status")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["real"], true);
+    }
+
+    #[tokio::test]
+    async fn api_body_limit_replaces_default_limit_for_api_routes() {
+        use tower::ServiceExt;
+
+        let echo_len = |body: axum::body::Bytes| async move { body.len().to_string() };
+        let api =
+            Router::new()
+                .route("/api/echo", post(echo_len))
+                .layer(RequestBodyLimitLayer::new(
+                    crate::config::GatewayConfig::default().max_api_body_bytes,
+                ));
+        let app = Router::new()
+            .route("/echo", post(echo_len))
+            .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
+            .merge(api);
+        let post_bytes = |path: &str, len: usize| {
+            axum::http::Request::post(path)
+                .header(header::CONTENT_LENGTH, len)
+                .body(axum::body::Body::from(vec![b'x'; len]))
+                .unwrap()
+        };
+
+        // Over the default limit, but within the API limit.
+        let normal = app
+            .clone()
+            .oneshot(post_bytes("/api/echo", MAX_BODY_SIZE * 2))
+            .await
+            .unwrap();
+        assert_eq!(normal.status(), StatusCode::OK);
+
+        let oversized = app
+            .clone()
+            .oneshot(post_bytes("/api/echo", 3 * 1024 * 1024))
+            .await
+            .unwrap();
+        assert_eq!(oversized.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let outside_api = app
+            .oneshot(post_bytes("/echo", MAX_BODY_SIZE * 2))
+            .await
+            .unwrap();
+        assert_eq!(outside_api.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn compression_level_setting_takes_effect() {
+        use tower::ServiceExt;
+
+        let fixture: String = (0..2000)
+            .map(|i| {
+                format!(
+                    "{{\"id\":{i},\"name\":\"item-{}\",\"ok\":true}}\n",
+                    i * 7919 % 1000
+                )
+            })
+            .collect();
+        let compressed_len = |encoding: &'static str, gzip_level: u32, brotli_quality: u32| {
+            let fixture = fixture.clone();
+            async move {
+                let config = crate::config::CompressionConfig {
+                    enabled: true,
+                    gzip_level,
+                    brotli_quality,
+                };
+                let app = with_compression(
+                    Router::new().route("/data", get(move || async move { fixture })),
+                    &config,
+                );
+                let response = app
+                    .oneshot(
+                        axum::http::Request::get("/data")
+                            .header(header::ACCEPT_ENCODING, encoding)
+                            .body(axum::body::Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(response.headers()[header::CONTENT_ENCODING], encoding);
+                response
+                    .into_body()
+                    .collect()
+                    .await
+                    .unwrap()
+                    .to_bytes()
+                    .len()
+            }
+        };
+
+        let gzip_fast = compressed_len("gzip", 1, 4).await;
+        let gzip_best = compressed_len("gzip", 9, 4).await;
+        assert!(
+            gzip_best <= gzip_fast,
+            "gzip 9 ({gzip_best}) > gzip 1 ({gzip_fast})"
+        );
+        assert!(gzip_fast < fixture.len());
+
+        let br_fast = compressed_len("br", 6, 0).await;
+        let br_best = compressed_len("br", 6, 11).await;
+        assert!(
+            br_best <= br_fast,
+            "brotli 11 ({br_best}) > brotli 0 ({br_fast})"
+        );
+    }
+
+    #[tokio::test]
+    async fn command_limiter_rejects_requests_over_the_limit() {
+        use tower::ServiceExt;
+
+        let release = Arc::new(tokio::sync::Notify::new());
+        let entered = Arc::new(tokio::sync::Notify::new());
+        let app = Router::new()
+            .route(
+                "/run",
+                post({
+                    let (release, entered) = (release.clone(), entered.clone());
+                    move || {
+                        let (release, entered) = (release.clone(), entered.clone());
+                        async move {
+                            entered.notify_one();
+                            release.notified().await;
+                            "done"
+                        }
+                    }
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                CommandLimiter::new(1),
+                command_limit_middleware,
+            ));
+        let request = || {
+            axum::http::Request::post("/run")
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+
+        let first = tokio::spawn(app.clone().oneshot(request()));
+        entered.notified().await;
+
+        let rejected = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(rejected.headers()[header::RETRY_AFTER], "1");
+
+        release.notify_one();
+        let first = first.await.unwrap().unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+
+        let after = tokio::spawn(app.oneshot(request()));
+        entered.notified().await;
+        release.notify_one();
+        assert_eq!(after.await.unwrap().unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn fixture_gateway_rejects_traversal_routes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let fixtures = FixtureGateway::new(tmp.path());
+        assert_eq!(
+            fixtures.fixture_path("/api/runtime"),
+            Some(tmp.path().canonicalize().unwrap().join("api/runtime.json"))
+        );
+        assert_eq!(fixtures.fixture_path("/api/../../etc/passwd"), None);
+        assert_eq!(fixtures.fixture_path("/"), None);
+
+        #[cfg(unix)]
+        {
+            let outside = tempfile::tempdir().unwrap();
+            std::fs::write(outside.path().join("secret.json"), "{}").unwrap();
+            std::os::unix::fs::symlink(outside.path(), tmp.path().join("api")).unwrap();
+            assert_eq!(fixtures.fixture_path("/api/secret"), None);
+        }
+    }
+
     #[test]
     fn gateway_rate_limiter_blocks_after_limit() {
         let limiter = GatewayRateLimiter::new(2, 2, 100);
@@ -2463,6 +3197,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -2533,6 +3268,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let headers = HeaderMap::new();
@@ -2615,6 +3351,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let response = handle_webhook(
@@ -2669,6 +3406,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -2728,6 +3466,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -2792,6 +3531,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let response = Box::pin(handle_nextcloud_talk_webhook(
@@ -2852,6 +3592,7 @@ mod tests {
             session_backend: None,
             device_registry: None,
             pending_pairings: None,
+            readiness: Arc::new(readiness::ReadinessCache::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -3291,4 +4032,36 @@ mod tests {
         let err = require_localhost(&peer).unwrap_err();
         assert_eq!(err.0, StatusCode::FORBIDDEN);
     }
+
+    #[test]
+    fn quiet_mode_rejects_info_events_from_gateway() {
+        use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+        #[derive(Clone, Default)]
+        struct CapturedLevels(Arc<Mutex<Vec<tracing::Level>>>);
+
+        impl<S: tracing::Subscriber> Layer<S> for CapturedLevels {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                self.0.lock().push(*event.metadata().level());
+            }
+        }
+
+        let captured = CapturedLevels::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(crate::observability::quiet::quiet_filter())
+            .with(captured.clone());
+        let config = crate::config::GatewayConfig {
+            quiet: true,
+            ..crate::config::GatewayConfig::default()
+        };
+
+        tracing::subscriber::with_default(subscriber, || {
+            apply_quiet_mode(&config);
+            tracing::info!("suppressed");
+            tracing::error!("kept");
+        });
+        crate::observability::quiet::set_quiet(false);
+
+        assert_eq!(*captured.0.lock(), [tracing::Level::ERROR]);
+    }
 }
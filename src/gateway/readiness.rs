@@ -0,0 +1,146 @@
+//! Cached runtime readiness for `/readyz`.
+//!
+//! Probing the runtime can be slow (a Docker daemon ping, for instance), so
+//! `/readyz` does not probe on every hit. A background task re-runs the check
+//! on a jittered interval and stores the result in a [`ReadinessCache`],
+//! which the handler reads.
+
+use crate::config::Config;
+use crate::runtime::HealthStatus;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fraction by which each re-check interval is randomly lengthened or
+/// shortened, so replicas started together do not probe in lockstep.
+const READINESS_JITTER: f64 = 0.1;
+
+/// One completed readiness check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadinessSnapshot {
+    pub status: HealthStatus,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// The most recent readiness check, shared between the refresh task and
+/// the `/readyz` handler.
+#[derive(Debug, Default)]
+pub struct ReadinessCache {
+    latest: RwLock<Option<ReadinessSnapshot>>,
+}
+
+impl ReadinessCache {
+    /// The last recorded check, if one has completed.
+    pub fn latest(&self) -> Option<ReadinessSnapshot> {
+        self.latest.read().clone()
+    }
+
+    /// Store `status` as the current readiness, stamped with the current time.
+    pub fn record(&self, status: HealthStatus) -> ReadinessSnapshot {
+        let snapshot = ReadinessSnapshot {
+            status,
+            checked_at: Utc::now(),
+        };
+        *self.latest.write() = Some(snapshot.clone());
+        snapshot
+    }
+}
+
+/// Probe the runtime described by the current config, the way `/readyz`
+/// used to on every request.
+pub async fn check_runtime(config: &Mutex<Config>) -> HealthStatus {
+    let (runtime_config, timeout) = {
+        let config = config.lock();
+        (
+            config.runtime.clone(),
+            Duration::from_secs(config.gateway.readiness_timeout_secs),
+        )
+    };
+    match crate::runtime::detect_runtime(&runtime_config) {
+        Ok(runtime) => crate::runtime::health_check(runtime.as_ref(), timeout).await,
+        Err(e) => HealthStatus::Unhealthy(e.to_string()),
+    }
+}
+
+/// `interval` randomly stretched or shrunk by up to [`READINESS_JITTER`].
+fn jittered(interval: Duration) -> Duration {
+    interval.mul_f64(1.0 + READINESS_JITTER * (2.0 * rand::random::<f64>() - 1.0))
+}
+
+/// Run `check` now and then every `interval` (jittered), recording each
+/// result in `cache`, until `shutdown` flips to `true`.
+pub fn spawn_refresh<F, Fut>(
+    cache: Arc<ReadinessCache>,
+    interval: Duration,
+    mut shutdown: tokio::sync::watch::Receiver<bool>,
+    check: F,
+) -> tokio::task::JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = HealthStatus> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            let snapshot = cache.record(check().await);
+            if let Some(reason) = snapshot.status.reason() {
+                tracing::warn!(
+                    status = snapshot.status.as_str(),
+                    reason,
+                    "runtime not ready"
+                );
+            }
+            tokio::select! {
+                () = tokio::time::sleep(jittered(interval)) => {}
+                _ = shutdown.changed() => return,
+            }
+            if *shutdown.borrow() {
+                return;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn jittered_interval_stays_within_bounds() {
+        let interval = Duration::from_secs(30);
+        for _ in 0..100 {
+            let delay = jittered(interval);
+            assert!(delay >= Duration::from_secs(27) && delay <= Duration::from_secs(33));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refresh_task_rechecks_and_stops_on_shutdown() {
+        let cache = Arc::new(ReadinessCache::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        let counter = calls.clone();
+        let task = spawn_refresh(
+            cache.clone(),
+            Duration::from_secs(10),
+            shutdown_rx,
+            move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                async move { HealthStatus::Degraded(format!("check {n}")) }
+            },
+        );
+
+        tokio::time::sleep(Duration::from_secs(25)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            cache.latest().unwrap().status,
+            HealthStatus::Degraded("check 2".into())
+        );
+
+        shutdown_tx.send(true).unwrap();
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! Resolve client-supplied paths beneath a directory on disk.
+//!
+//! [`is_safe_asset_path`](super::static_files::is_safe_asset_path) vets paths
+//! into the embedded dashboard bundle lexically. Files on disk also need
+//! symlinks resolved, so file endpoints go through [`safe_join`] instead.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Why a client-supplied path was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum PathError {
+    /// The path is absolute or, on Windows, names a drive, UNC share or stream.
+    #[error("absolute paths are not allowed: {0:?}")]
+    Absolute(String),
+    /// The path contains a `..` segment.
+    #[error("path traversal is not allowed: {0:?}")]
+    Traversal(String),
+    /// The path contains NUL or other control characters.
+    #[error("path contains control characters: {0:?}")]
+    Invalid(String),
+    /// The path resolves, through a symlink, to somewhere outside the root.
+    #[error("path escapes its root: {0:?}")]
+    EscapesRoot(String),
+    /// The root itself could not be resolved.
+    #[error("root directory is unavailable: {0}")]
+    RootUnavailable(#[source] std::io::Error),
+    /// Resolving the path failed for a reason other than it not existing.
+    #[error("failed to resolve path: {0}")]
+    Io(#[source] std::io::Error),
+}
+
+/// Join `user_path` onto `root`, refusing anything that could leave `root`.
+///
+/// `user_path` is `/`- or `\`-separated and relative; `.` and empty segments
+/// are ignored, while `..` and absolute paths are rejected outright. The
+/// result is canonical and, after following symlinks, stays inside the
+/// canonicalized `root`. The target need not exist yet, so upload destinations
+/// can be resolved too; a dangling symlink on the way is treated as an escape.
+pub fn safe_join(root: &Path, user_path: &str) -> Result<PathBuf, PathError> {
+    if user_path.chars().any(char::is_control) {
+        return Err(PathError::Invalid(user_path.to_string()));
+    }
+    let lexical = Path::new(user_path);
+    if user_path.starts_with(['/', '\\'])
+        || lexical.is_absolute()
+        || matches!(lexical.components().next(), Some(Component::Prefix(_)))
+        || (cfg!(windows) && user_path.contains(':'))
+    {
+        return Err(PathError::Absolute(user_path.to_string()));
+    }
+    let segments: Vec<&str> = user_path
+        .split(['/', '\\'])
+        .filter(|segment| !matches!(*segment, "" | "."))
+        .collect();
+    if segments.contains(&"..") {
+        return Err(PathError::Traversal(user_path.to_string()));
+    }
+
+    let root = root.canonicalize().map_err(PathError::RootUnavailable)?;
+    let escapes = || PathError::EscapesRoot(user_path.to_string());
+
+    // Canonicalize the longest existing prefix; the rest does not exist yet
+    // and so cannot contain symlinks.
+    let mut resolved = root.clone();
+    let mut remaining = segments.iter();
+    for segment in remaining.by_ref() {
+        let next = resolved.join(segment);
+        match next.canonicalize() {
+            Ok(canonical) => {
+                if !canonical.starts_with(&root) {
+                    return Err(escapes());
+                }
+                resolved = canonical;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                if next.symlink_metadata().is_ok() {
+                    return Err(escapes());
+                }
+                resolved = next;
+                break;
+            }
+            Err(err) => return Err(PathError::Io(err)),
+        }
+    }
+    resolved.extend(remaining);
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_join_resolves_valid_nested_paths() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("reports/2024")).unwrap();
+        std::fs::write(root.path().join("reports/2024/q1.csv"), "a,b").unwrap();
+        let canonical = root.path().canonicalize().unwrap();
+
+        assert_eq!(
+            safe_join(root.path(), "reports/2024/q1.csv").unwrap(),
+            canonical.join("reports/2024/q1.csv")
+        );
+        assert_eq!(
+            safe_join(root.path(), "./reports//2024/new/out.txt").unwrap(),
+            canonical.join("reports/2024/new/out.txt")
+        );
+        assert_eq!(safe_join(root.path(), "").unwrap(), canonical);
+    }
+
+    #[test]
+    fn safe_join_rejects_traversal() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("sub")).unwrap();
+        for path in ["..", "../secret.txt", "sub/../../etc/passwd", "sub\\..\\x"] {
+            assert!(
+                matches!(safe_join(root.path(), path), Err(PathError::Traversal(_))),
+                "{path:?}"
+            );
+        }
+        assert!(matches!(
+            safe_join(root.path(), "a\0b"),
+            Err(PathError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn safe_join_rejects_absolute_paths() {
+        let root = tempfile::tempdir().unwrap();
+        for path in ["/etc/passwd", "\\\\server\\share", "\\windows"] {
+            assert!(
+                matches!(safe_join(root.path(), path), Err(PathError::Absolute(_))),
+                "{path:?}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn safe_join_rejects_symlink_escapes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("root");
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.txt"), "nope").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("gone"), root.join("dangling")).unwrap();
+        std::os::unix::fs::symlink(".", root.join("self")).unwrap();
+
+        for path in ["link/secret.txt", "link/new.txt", "link", "dangling"] {
+            assert!(
+                matches!(safe_join(&root, path), Err(PathError::EscapesRoot(_))),
+                "{path:?}"
+            );
+        }
+        // Symlinks that stay inside the root are fine.
+        assert_eq!(
+            safe_join(&root, "self/file.txt").unwrap(),
+            root.canonicalize().unwrap().join("file.txt")
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! HTTP/1 connection handling for the gateway.
+//!
+//! `axum::serve` offers no connection timeouts, so a client that trickles
+//! header bytes, or parks an idle keep-alive connection, holds a socket
+//! forever. [`serve`] drives each connection through hyper directly with a
+//! header read deadline and closes keep-alive connections that sit idle. It
+//! can also cap how many connections are open at once, so a connection flood
+//! cannot exhaust file descriptors regardless of request rate limits.
+
+use axum::Router;
+use axum::extract::Request;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioIo, TokioTimer};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tower::{Service, ServiceExt};
+
+/// Per-connection timeouts applied by [`serve`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionTimeouts {
+    /// Close the connection if a request's headers have not fully arrived
+    /// within this window.
+    pub header_read: Duration,
+    /// Close a keep-alive connection after this long with no request in
+    /// flight; `None` disables keep-alive, closing after each response.
+    pub keep_alive: Option<Duration>,
+}
+
+/// Caps on connections and request parsing applied by [`serve`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Most connections open at once; `None` means unlimited.
+    pub max_connections: Option<usize>,
+    /// Most header fields the parser accepts per request before answering
+    /// `431 Request Header Fields Too Large`.
+    pub max_headers: usize,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: None,
+            max_headers: 100,
+        }
+    }
+}
+
+/// Requests in flight on one connection and when the last one finished.
+#[derive(Debug)]
+struct Activity {
+    in_flight: AtomicUsize,
+    last_active: Mutex<Instant>,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            last_active: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn begin(&self) {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn end(&self) {
+        *self.last_active.lock() = Instant::now();
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Resolve once no request has been in flight for `idle`.
+    async fn idle_for(&self, idle: Duration) {
+        loop {
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                let deadline = *self.last_active.lock() + idle;
+                if Instant::now() >= deadline {
+                    return;
+                }
+                tokio::time::sleep_until(deadline).await;
+            } else {
+                tokio::time::sleep(idle).await;
+            }
+        }
+    }
+}
+
+/// Serve `app` on `listener` until `shutdown` resolves, then wait for open
+/// connections to finish their in-flight requests.
+///
+/// With `max_connections` set, connections accepted while that many are
+/// already open are closed straight away and counted in the gateway metrics.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    timeouts: ConnectionTimeouts,
+    limits: ConnectionLimits,
+    shutdown: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    let slots = limits
+        .max_connections
+        .map(|limit| Arc::new(Semaphore::new(limit)));
+    let mut make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let mut builder = hyper::server::conn::http1::Builder::new();
+    builder
+        .timer(TokioTimer::new())
+        .header_read_timeout(timeouts.header_read)
+        .max_headers(limits.max_headers)
+        .keep_alive(timeouts.keep_alive.is_some());
+
+    let (closing_tx, closing_rx) = tokio::sync::watch::channel(false);
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            () = &mut shutdown => break,
+            Some(_) = connections.join_next() => {}
+            accepted = listener.accept() => {
+                let (stream, remote) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        // Usually fd exhaustion; back off rather than spin.
+                        tracing::warn!("Gateway accept failed: {err}");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let permit = match slots.as_ref().map(|slots| slots.clone().try_acquire_owned()) {
+                    Some(Err(_)) => {
+                        tracing::debug!(%remote, "Gateway connection limit reached; closing");
+                        crate::observability::runtime_metrics::record_gateway_connection_rejected();
+                        drop(stream);
+                        continue;
+                    }
+                    Some(Ok(permit)) => Some(permit),
+                    None => None,
+                };
+                let Ok(service) = make_service.call(remote).await;
+                connections.spawn(serve_connection(
+                    builder.clone(),
+                    stream,
+                    service,
+                    timeouts.keep_alive,
+                    closing_rx.clone(),
+                    permit,
+                ));
+            }
+        }
+    }
+
+    drop(listener);
+    let _ = closing_tx.send(true);
+    while connections.join_next().await.is_some() {}
+    Ok(())
+}
+
+async fn serve_connection<S>(
+    builder: hyper::server::conn::http1::Builder,
+    stream: tokio::net::TcpStream,
+    service: S,
+    keep_alive: Option<Duration>,
+    mut closing: tokio::sync::watch::Receiver<bool>,
+    // Held until the connection closes, freeing its `max_connections` slot.
+    _permit: Option<OwnedSemaphorePermit>,
+) where
+    S: Service<Request, Response = axum::response::Response, Error = std::convert::Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+{
+    let activity = Arc::new(Activity::new());
+    let hyper_service = {
+        let activity = activity.clone();
+        hyper::service::service_fn(move |request: hyper::Request<Incoming>| {
+            activity.begin();
+            let response = service.clone().oneshot(request.map(axum::body::Body::new));
+            let activity = activity.clone();
+            async move {
+                let response = response.await;
+                activity.end();
+                response
+            }
+        })
+    };
+
+    let connection = builder
+        .serve_connection(TokioIo::new(stream), hyper_service)
+        .with_upgrades();
+    tokio::pin!(connection);
+    let mut shutting_down = false;
+    loop {
+        tokio::select! {
+            result = connection.as_mut() => {
+                if let Err(err) = result {
+                    tracing::debug!("Gateway connection closed: {err}");
+                }
+                return;
+            }
+            () = idle_opt(&activity, keep_alive), if !shutting_down => {
+                connection.as_mut().graceful_shutdown();
+                shutting_down = true;
+            }
+            _ = closing.changed(), if !shutting_down => {
+                connection.as_mut().graceful_shutdown();
+                shutting_down = true;
+            }
+        }
+    }
+}
+
+/// [`Activity::idle_for`], or never when keep-alive is disabled.
+async fn idle_opt(activity: &Activity, keep_alive: Option<Duration>) {
+    match keep_alive {
+        Some(idle) => activity.idle_for(idle).await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn start(timeouts: ConnectionTimeouts) -> (SocketAddr, tokio::sync::oneshot::Sender<()>) {
+        start_limited(timeouts, ConnectionLimits::default()).await
+    }
+
+    async fn start_limited(
+        timeouts: ConnectionTimeouts,
+        limits: ConnectionLimits,
+    ) -> (SocketAddr, tokio::sync::oneshot::Sender<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(serve(listener, app, timeouts, limits, async {
+            let _ = stop_rx.await;
+        }));
+        (addr, stop_tx)
+    }
+
+    /// Send a keep-alive `GET /` and read back its complete response.
+    async fn get_keep_alive(stream: &mut tokio::net::TcpStream) -> String {
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut received = Vec::new();
+        let mut buf = [0_u8; 1024];
+        while !received.ends_with(b"ok") {
+            let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+                .await
+                .expect("response should arrive")
+                .unwrap();
+            assert!(n > 0, "connection closed mid-response");
+            received.extend_from_slice(&buf[..n]);
+        }
+        String::from_utf8_lossy(&received).into_owned()
+    }
+
+    /// Read until the server closes the connection, failing after 5s.
+    async fn read_until_closed(stream: &mut tokio::net::TcpStream) -> String {
+        let mut received = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut received))
+            .await
+            .expect("server should close the connection")
+            .ok();
+        String::from_utf8_lossy(&received).into_owned()
+    }
+
+    #[tokio::test]
+    async fn slow_headers_are_dropped_after_header_timeout() {
+        let (addr, _stop) = start(ConnectionTimeouts {
+            header_read: Duration::from_millis(300),
+            keep_alive: Some(Duration::from_secs(60)),
+        })
+        .await;
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let started = std::time::Instant::now();
+
+        // Trickle header lines, never finishing the request.
+        stream.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+        let (mut reader, mut writer) = stream.into_split();
+        let trickle = tokio::spawn(async move {
+            for i in 0..50 {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let line = format!("X-Slow-{i}: 1\r\n");
+                if writer.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut received = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), reader.read_to_end(&mut received))
+            .await
+            .expect("server should close the connection")
+            .ok();
+        assert!(!String::from_utf8_lossy(&received).contains("200 OK"));
+        assert!(started.elapsed() < Duration::from_secs(3));
+        trickle.abort();
+    }
+
+    #[tokio::test]
+    async fn idle_keep_alive_connections_are_closed() {
+        let (addr, _stop) = start(ConnectionTimeouts {
+            header_read: Duration::from_secs(10),
+            keep_alive: Some(Duration::from_millis(300)),
+        })
+        .await;
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+
+        let response = read_until_closed(&mut stream).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.ends_with("ok"));
+    }
+
+    #[tokio::test]
+    async fn connections_beyond_the_cap_are_refused() {
+        let timeouts = ConnectionTimeouts {
+            header_read: Duration::from_secs(10),
+            keep_alive: Some(Duration::from_secs(60)),
+        };
+        let limits = ConnectionLimits {
+            max_connections: Some(1),
+            ..ConnectionLimits::default()
+        };
+        let (addr, _stop) = start_limited(timeouts, limits).await;
+
+        let mut first = tokio::net::TcpStream::connect(addr).await.unwrap();
+        assert!(
+            get_keep_alive(&mut first)
+                .await
+                .starts_with("HTTP/1.1 200 OK")
+        );
+
+        let mut second = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let _ = second
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await;
+        assert_eq!(read_until_closed(&mut second).await, "");
+
+        assert!(
+            get_keep_alive(&mut first)
+                .await
+                .starts_with("HTTP/1.1 200 OK")
+        );
+    }
+}
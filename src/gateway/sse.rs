@@ -3,7 +3,11 @@
 //! Wraps the broadcast channel in AppState to deliver events to web dashboard clients.
 
 use super::AppState;
+use crate::runtime::{RuntimeAdapter, StreamOptions, spawn_streaming};
+use crate::security::SecurityPolicy;
+use crate::util::{ProgressExtractor, normalize_newlines, parse_ansi};
 use axum::{
+    Json,
     extract::State,
     http::{HeaderMap, StatusCode, header},
     response::{
@@ -11,9 +15,14 @@ use axum::{
         sse::{Event, KeepAlive, Sse},
     },
 };
+use serde::Deserialize;
 use std::convert::Infallible;
-use tokio_stream::StreamExt;
-use tokio_stream::wrappers::BroadcastStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
 
 /// GET /api/events — SSE event stream
 pub async fn handle_sse_events(
@@ -57,6 +66,174 @@ pub async fn handle_sse_events(
         .into_response()
 }
 
+/// Output events buffered for a command stream before the client counts as
+/// too slow and the command is cancelled.
+pub const COMMAND_EVENT_BUFFER: usize = 256;
+
+/// Body of `POST /api/commands/stream`.
+#[derive(Debug, Deserialize)]
+pub struct CommandStreamRequest {
+    pub command: String,
+}
+
+/// POST /api/commands/stream — run a command, streaming its output as SSE
+///
+/// The command must pass the same autonomy policy as the agent's shell tool.
+pub async fn handle_command_stream(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CommandStreamRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = super::api::require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let config = state.config.lock().clone();
+    let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+    let denied = match security.validate_command_execution(&body.command, false) {
+        Err(reason) => Some(reason),
+        Ok(_) => security
+            .forbidden_path_argument(&body.command)
+            .map(|path| format!("Path blocked by security policy: {path}")),
+    };
+    if let Some(reason) = denied {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": reason})),
+        )
+            .into_response();
+    }
+    let runtime = match crate::runtime::detect_runtime(&config.runtime) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Runtime unavailable: {e}")})),
+            )
+                .into_response();
+        }
+    };
+
+    // Spinner frames only add render churn on the dashboard.
+    let options = StreamOptions {
+        suppress_spinners: true,
+        ..StreamOptions::default()
+    };
+    let events = command_output_events(
+        Arc::from(runtime),
+        body.command,
+        config.workspace_dir,
+        options,
+    );
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Stream a command's output as SSE `output` events, ending with `done`.
+///
+/// Output text has its line endings normalized to `\n` (see
+/// [`normalize_newlines`]) so CRLF from cross-platform tools is not shown
+/// double-spaced. Output carrying ANSI escapes also gets a `spans` array (see [`parse_ansi`])
+/// so the dashboard can render its colors instead of stripping them.
+/// Whenever the latest completion percentage in the output changes (see
+/// [`ProgressExtractor`]), a `progress` event follows the output carrying it,
+/// so the dashboard can drive a progress bar.
+///
+/// The command runs on its own task. When the client disconnects, axum drops
+/// the returned stream, which cancels `options.cancel` (one is created if
+/// unset) so the command is killed instead of writing into a closed
+/// connection. A disconnect is expected, so it is logged at debug level only.
+/// A client that falls [`COMMAND_EVENT_BUFFER`] events behind gets the
+/// command cancelled too, and a `done` event with `overflowed` set.
+pub fn command_output_events(
+    adapter: Arc<dyn RuntimeAdapter>,
+    command: String,
+    workspace_dir: PathBuf,
+    mut options: StreamOptions,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let cancel = options
+        .cancel
+        .get_or_insert_with(CancellationToken::new)
+        .clone();
+    let (tx, rx) = tokio::sync::mpsc::channel(COMMAND_EVENT_BUFFER);
+
+    tokio::spawn({
+        let cancel = cancel.clone();
+        async move {
+            let mut overflowed = false;
+            let mut progress = ProgressExtractor::new();
+            let mut last_percent = None;
+            let result = spawn_streaming(
+                adapter.as_ref(),
+                &command,
+                &workspace_dir,
+                &options,
+                |chunk| {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    let text = normalize_newlines(&chunk.text);
+                    let mut data = serde_json::json!({
+                        "stream": chunk.stream.as_str(),
+                        "text": text,
+                    });
+                    if text.contains('\x1b') {
+                        data["spans"] = serde_json::json!(parse_ansi(&text));
+                    }
+                    let output = Event::default().event("output").data(data.to_string());
+                    let percent = progress
+                        .feed(chunk)
+                        .filter(|&percent| Some(percent) != last_percent);
+                    let progress_event = percent.map(|percent| {
+                        last_percent = Some(percent);
+                        let data = serde_json::json!({ "percent": percent });
+                        Event::default().event("progress").data(data.to_string())
+                    });
+                    for event in std::iter::once(output).chain(progress_event) {
+                        match tx.try_send(event) {
+                            Ok(()) => {}
+                            Err(TrySendError::Full(_)) => {
+                                overflowed = true;
+                                cancel.cancel();
+                                return;
+                            }
+                            Err(TrySendError::Closed(_)) => {
+                                cancel.cancel();
+                                return;
+                            }
+                        }
+                    }
+                },
+            )
+            .await;
+            let data = serde_json::json!({
+                "exit_code": result.exit_code,
+                "timed_out": result.timed_out.map(|reason| reason.as_str()),
+                "overflowed": overflowed,
+            });
+            if overflowed {
+                tracing::debug!(command, "command stream client fell behind; cancelled");
+            }
+            if (cancel.is_cancelled() && !overflowed)
+                || tx
+                    .send(Event::default().event("done").data(data.to_string()))
+                    .await
+                    .is_err()
+            {
+                tracing::debug!(command, "client disconnected from command stream");
+            }
+        }
+    });
+
+    // Dropping the stream (and with it this guard) cancels the command.
+    let guard = cancel.drop_guard();
+    ReceiverStream::new(rx).map(move |event| {
+        let _ = &guard;
+        Ok(event)
+    })
+}
+
 /// Broadcast observer that forwards events to the SSE broadcast channel.
 pub struct BroadcastObserver {
     inner: Box<dyn crate::observability::Observer>,
@@ -156,3 +333,150 @@ impl crate::observability::Observer for BroadcastObserver {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{NativeRuntime, ProcessRegistry};
+    use parking_lot::Mutex;
+    use std::time::Duration;
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    #[derive(Clone, Default)]
+    struct CapturedLevels(Arc<Mutex<Vec<tracing::Level>>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for CapturedLevels {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            self.0.lock().push(*event.metadata().level());
+        }
+    }
+
+    /// The full SSE body of a command stream that runs to completion.
+    async fn sse_body(
+        events: impl Stream<Item = Result<Event, Infallible>> + Send + 'static,
+    ) -> String {
+        let body = Sse::new(events).into_response().into_body();
+        let body = tokio::time::timeout(
+            Duration::from_secs(10),
+            axum::body::to_bytes(body, usize::MAX),
+        )
+        .await
+        .expect("command should finish")
+        .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dropping_command_stream_cancels_command_quietly() {
+        let captured = CapturedLevels::default();
+        let _subscriber =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+        let command = "echo started; sleep 30 # sse_disconnect_test";
+        let running = || {
+            ProcessRegistry::global()
+                .list()
+                .iter()
+                .any(|info| info.command == command)
+        };
+
+        let mut stream = Box::pin(command_output_events(
+            Arc::new(NativeRuntime::new()),
+            command.to_string(),
+            std::env::temp_dir(),
+            StreamOptions::default(),
+        ));
+        assert!(stream.next().await.is_some());
+        assert!(running());
+
+        drop(stream);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while running() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("command should be cancelled after the client disconnects");
+
+        assert!(
+            !captured.0.lock().contains(&tracing::Level::ERROR),
+            "disconnect must not log errors"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn lagging_command_stream_client_cancels_command() {
+        let events = command_output_events(
+            Arc::new(NativeRuntime::new()),
+            "seq 1 100000; sleep 30 # sse_overflow_test".to_string(),
+            std::env::temp_dir(),
+            StreamOptions::default(),
+        );
+        // Nothing reads the stream yet, so the buffer overflows.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let body = Sse::new(events).into_response().into_body();
+        let body = tokio::time::timeout(
+            Duration::from_secs(10),
+            axum::body::to_bytes(body, usize::MAX),
+        )
+        .await
+        .expect("overflowing command should be cancelled")
+        .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body.matches("event: output").count(), COMMAND_EVENT_BUFFER);
+        assert!(body.contains(r#""overflowed":true"#), "{body}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_stream_reports_progress_changes() {
+        let events = command_output_events(
+            Arc::new(NativeRuntime::new()),
+            "printf 'fetch 10%%\\nfetch 47%%\\nfetch 47%%\\ndone\\n'".to_string(),
+            std::env::temp_dir(),
+            StreamOptions::default(),
+        );
+
+        let body = sse_body(events).await;
+        assert_eq!(body.matches("event: output").count(), 4, "{body}");
+        assert_eq!(body.matches("event: progress").count(), 2, "{body}");
+        assert!(body.contains(r#"{"percent":10}"#), "{body}");
+        assert!(body.contains(r#"{"percent":47}"#), "{body}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_stream_normalizes_line_endings() {
+        let events = command_output_events(
+            Arc::new(NativeRuntime::new()),
+            "printf 'crlf\\r\\nlone\\rcr\\n'".to_string(),
+            std::env::temp_dir(),
+            StreamOptions::default(),
+        );
+
+        let body = sse_body(events).await;
+        assert!(!body.contains(r"\r"), "{body}");
+        assert!(body.contains(r#""text":"crlf\n""#), "{body}");
+        assert!(body.contains(r#""text":"lone\ncr\n""#), "{body}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_stream_adds_styled_spans_for_ansi_output() {
+        let events = command_output_events(
+            Arc::new(NativeRuntime::new()),
+            "printf '\\033[1;31mred\\033[0m plain\\n'; echo uncolored".to_string(),
+            std::env::temp_dir(),
+            StreamOptions::default(),
+        );
+
+        let body = sse_body(events).await;
+        assert_eq!(body.matches(r#""spans":"#).count(), 1, "{body}");
+        assert!(
+            body.contains(r#"{"bg":null,"bold":true,"fg":{"indexed":1},"text":"red"}"#),
+            "{body}"
+        );
+    }
+}
@@ -1,13 +1,19 @@
 //! Static file serving for the embedded web dashboard.
 //!
-//! Uses `rust-embed` to bundle the `web/dist/` directory into the binary at compile time.
+//! Uses `rust-embed` to bundle the `web/dist/` and `web/vendor/` directories
+//! into the binary at compile time. Both are served from one asset namespace;
+//! when a path exists in both, the file from `web/dist/` (our own build
+//! output) wins over the vendored third-party copy.
 
 use axum::{
+    body::Body,
     extract::State,
-    http::{StatusCode, Uri, header},
+    http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
     response::{IntoResponse, Response},
 };
-use rust_embed::Embed;
+use rust_embed::{Embed, EmbeddedFile};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 
 use super::AppState;
 
@@ -15,21 +21,175 @@ use super::AppState;
 #[folder = "web/dist/"]
 struct WebAssets;
 
-/// Serve static files from `/_app/*` path
-pub async fn handle_static(uri: Uri) -> Response {
-    let path = uri
-        .path()
-        .strip_prefix("/_app/")
-        .unwrap_or(uri.path())
-        .trim_start_matches('/');
+/// Vendored third-party assets kept apart from the frontend build output.
+#[derive(Embed)]
+#[folder = "web/vendor/"]
+struct VendorAssets;
+
+/// Look up `path` in every embedded asset directory, highest precedence first.
+fn embedded_asset(path: &str) -> Option<EmbeddedFile> {
+    first_layer_match(path, &[WebAssets::get, VendorAssets::get])
+}
+
+/// Resolve `path` against `layers`, returning the hit from the first layer
+/// that has it.
+fn first_layer_match<T>(path: &str, layers: &[fn(&str) -> Option<T>]) -> Option<T> {
+    layers.iter().find_map(|lookup| lookup(path))
+}
+
+/// An embedded file's contents with the SHA-256 recorded at build time.
+struct Asset {
+    data: Cow<'static, [u8]>,
+    sha256: [u8; 32],
+}
+
+impl From<EmbeddedFile> for Asset {
+    fn from(file: EmbeddedFile) -> Self {
+        Self {
+            sha256: file.metadata.sha256_hash(),
+            data: file.data,
+        }
+    }
+}
+
+impl Asset {
+    /// Strong validator for exactly these bytes, so a precompressed variant
+    /// never shares a tag with the uncompressed file.
+    fn etag(&self) -> String {
+        format!("\"{}\"", hex::encode(&self.sha256[..16]))
+    }
+}
+
+/// Serve static files from the configured asset prefixes (`/_app/*` by
+/// default), honouring single-span `Range` requests, `If-None-Match`, and
+/// brotli-precompressed `.br` siblings for clients that accept them
+pub async fn handle_static(
+    State(state): State<AppState>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    let prefixes = state.config.lock().gateway.asset_prefixes.clone();
+
+    match strip_asset_prefix(uri.path(), &prefixes) {
+        Some(path) => serve_asset(|path| embedded_asset(path).map(Asset::from), path, &headers),
+        None => (StatusCode::NOT_FOUND, "Not found").into_response(),
+    }
+}
+
+/// Served at `/favicon.ico` when the dashboard bundle has no favicon, so
+/// browsers stop re-requesting it.
+const DEFAULT_FAVICON: &[u8] = include_bytes!("default_favicon.svg");
+
+/// `/favicon.ico`: the bundle's own favicon if it has one, else the built-in
+/// default, cached as immutable either way.
+pub async fn handle_favicon(headers: HeaderMap) -> Response {
+    serve_favicon(|path| embedded_asset(path).map(Asset::from), &headers)
+}
+
+fn serve_favicon(lookup: impl Fn(&str) -> Option<Asset>, headers: &HeaderMap) -> Response {
+    static MISSING_LOGGED: std::sync::Once = std::sync::Once::new();
+
+    let mut response = if lookup("favicon.ico").is_some() {
+        serve_asset(lookup, "favicon.ico", headers)
+    } else {
+        MISSING_LOGGED.call_once(|| {
+            tracing::debug!("Dashboard bundle has no favicon.ico; serving the built-in default");
+        });
+        let default = |path: &str| {
+            (path == "favicon.svg").then(|| Asset {
+                data: Cow::Borrowed(DEFAULT_FAVICON),
+                sha256: Sha256::digest(DEFAULT_FAVICON).into(),
+            })
+        };
+        serve_asset(default, "favicon.svg", headers)
+    };
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000, immutable"),
+    );
+    response
+}
 
-    serve_embedded_file(path)
+/// Whether `Accept-Encoding` lists `br` without `q=0`.
+fn accepts_brotli(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            params
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case("br"))
+                && !params.any(|param| {
+                    param
+                        .strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok())
+                        .is_some_and(|q| q <= 0.0)
+                })
+        })
 }
 
-/// SPA fallback: serve index.html for any non-API, non-static GET request.
+/// Whether `If-None-Match` names `etag` (weak comparison, RFC 9110 §13.1.2).
+pub(crate) fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|candidates| {
+            candidates
+                .split(',')
+                .map(str::trim)
+                .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+        })
+}
+
+/// Strip whichever of `prefixes` `path` starts with, giving the embedded path.
+fn strip_asset_prefix<'a>(path: &'a str, prefixes: &[String]) -> Option<&'a str> {
+    prefixes
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix.as_str()))
+        .map(|rest| rest.trim_start_matches('/'))
+}
+
+/// Whether `prefix` can mount static assets: `/segment/.../` with safe
+/// segments, not overlapping the API, WebSocket or admin routes.
+pub fn is_valid_asset_prefix(prefix: &str) -> bool {
+    let reserved = ["/api/", "/ws/", "/admin/"];
+    prefix
+        .strip_prefix('/')
+        .and_then(|inner| inner.strip_suffix('/'))
+        .is_some_and(is_safe_asset_path)
+        && !reserved.iter().any(|root| prefix.starts_with(root))
+}
+
+/// SPA fallback: serve the configured index document (`index.html` by default)
+/// for any non-API, non-static GET request.
 /// Injects `window.__rain_BASE__` so the frontend knows the path prefix.
 pub async fn handle_spa_fallback(State(state): State<AppState>) -> Response {
-    let Some(content) = WebAssets::get("index.html") else {
+    let index_document = state.config.lock().gateway.index_document.clone();
+    render_spa_document(
+        |path| embedded_asset(path).map(|file| file.data),
+        &index_document,
+        &state.path_prefix,
+    )
+}
+
+/// Render the SPA entry document found by `lookup`, falling back to
+/// `index.html` when the configured document is missing or unsafe.
+///
+/// The content type follows the served document's name, so e.g. an `.xhtml`
+/// entry goes out as `application/xhtml+xml`.
+fn render_spa_document(
+    lookup: impl Fn(&str) -> Option<Cow<'static, [u8]>>,
+    index_document: &str,
+    path_prefix: &str,
+) -> Response {
+    let document = Some(index_document)
+        .filter(|name| is_safe_asset_path(name))
+        .and_then(|name| Some((name, lookup(name)?)))
+        .or_else(|| Some(("index.html", lookup("index.html")?)));
+    let Some((name, content)) = document else {
         return (
             StatusCode::SERVICE_UNAVAILABLE,
             "Web dashboard not available. Build it with: cd web && npm ci && npm run build",
@@ -37,13 +197,13 @@ pub async fn handle_spa_fallback(State(state): State<AppState>) -> Response {
             .into_response();
     };
 
-    let html = String::from_utf8_lossy(&content.data);
+    let html = String::from_utf8_lossy(&content);
 
     // Inject path prefix for the SPA and rewrite asset paths in the HTML
-    let html = if state.path_prefix.is_empty() {
+    let html = if path_prefix.is_empty() {
         html.into_owned()
     } else {
-        let pfx = &state.path_prefix;
+        let pfx = path_prefix;
         // JSON-encode the prefix to safely embed in a <script> block
         let json_pfx = serde_json::to_string(pfx).unwrap_or_else(|_| "\"\"".to_string());
         let script = format!("<script>window.__rain_BASE__={json_pfx};</script>");
@@ -52,10 +212,17 @@ pub async fn handle_spa_fallback(State(state): State<AppState>) -> Response {
             .replace("<head>", &format!("<head>{script}"))
     };
 
+    let mime = content_type_for(name, html.as_bytes());
+    let content_type = if mime.starts_with("text/") && !mime.contains("charset") {
+        format!("{mime}; charset=utf-8")
+    } else {
+        mime
+    };
+
     (
         StatusCode::OK,
         [
-            (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (header::CONTENT_TYPE, content_type),
             (header::CACHE_CONTROL, "no-cache".to_string()),
         ],
         html,
@@ -63,32 +230,566 @@ pub async fn handle_spa_fallback(State(state): State<AppState>) -> Response {
         .into_response()
 }
 
-fn serve_embedded_file(path: &str) -> Response {
-    match WebAssets::get(path) {
-        Some(content) => {
-            let mime = mime_guess::from_path(path)
-                .first_or_octet_stream()
-                .to_string();
+/// Whether `path` is a plain relative asset path: non-empty, not absolute, and
+/// free of `.`/`..` segments, backslashes, drive colons and control characters.
+pub fn is_safe_asset_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && !path.contains(['\\', ':'])
+        && !path.chars().any(char::is_control)
+        && path
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."))
+}
+
+/// Embedded assets whose contents no longer match their build-time hash.
+#[derive(Debug, thiserror::Error)]
+#[error("embedded assets failed integrity check: {}", mismatched.join(", "))]
+pub struct AssetIntegrityError {
+    pub mismatched: Vec<String>,
+}
+
+/// Re-hash every embedded dashboard asset and compare it with the SHA-256
+/// manifest `rust-embed` records at build time, to catch corrupted builds.
+pub fn verify_assets() -> Result<(), AssetIntegrityError> {
+    check_asset_manifest(manifest_entries::<WebAssets>().chain(manifest_entries::<VendorAssets>()))
+}
+
+/// `(path, expected SHA-256, contents)` for every file embedded by `E`.
+fn manifest_entries<E: Embed>() -> impl Iterator<Item = (String, [u8; 32], Cow<'static, [u8]>)> {
+    E::iter().filter_map(|path| {
+        let file = E::get(&path)?;
+        Some((path.into_owned(), file.metadata.sha256_hash(), file.data))
+    })
+}
+
+/// Compare `(path, expected SHA-256, contents)` entries, collecting mismatches.
+fn check_asset_manifest<D: AsRef<[u8]>>(
+    entries: impl IntoIterator<Item = (String, [u8; 32], D)>,
+) -> Result<(), AssetIntegrityError> {
+    let mut mismatched: Vec<String> = entries
+        .into_iter()
+        .filter(|(_, expected, data)| Sha256::digest(data.as_ref()).as_slice() != expected)
+        .map(|(path, _, _)| path)
+        .collect();
+    if mismatched.is_empty() {
+        return Ok(());
+    }
+    mismatched.sort();
+    Err(AssetIntegrityError { mismatched })
+}
+
+/// Serve the asset `lookup` finds at `path`.
+///
+/// When a `{path}.br` sibling exists and the client accepts brotli, that
+/// variant is sent with `Content-Encoding: br` and its own `ETag`; ranges
+/// always address the uncompressed file. Either way `Vary: Accept-Encoding`
+/// tells caches the two representations differ.
+fn serve_asset(
+    lookup: impl Fn(&str) -> Option<Asset>,
+    path: &str,
+    headers: &HeaderMap,
+) -> Response {
+    let Some(identity) = lookup(path) else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+    let range_header = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let brotli = lookup(&format!("{path}.br"));
+    let varies = brotli.is_some();
+    let (content, encoding) = match brotli {
+        Some(brotli) if range_header.is_none() && accepts_brotli(headers) => (brotli, Some("br")),
+        _ => (identity, None),
+    };
+
+    let etag = content.etag();
+    let cache_control = if path.contains("assets/") {
+        // Hashed filenames — immutable cache
+        "public, max-age=31536000, immutable"
+    } else {
+        // index.html etc — no cache
+        "no-cache"
+    };
+    let mut response = if etag_matches(headers, &etag) {
+        (
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control.to_string()),
+            ],
+        )
+            .into_response()
+    } else {
+        let mime = content_type_for(path, &content.data);
+        let len = content.data.len() as u64;
+
+        let (status, body, content_range) = match parse_byte_range(range_header, len) {
+            ByteRange::Full => (StatusCode::OK, content.data, None),
+            ByteRange::Partial { start, end } => {
+                let (Ok(from), Ok(to)) = (usize::try_from(start), usize::try_from(end)) else {
+                    return StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+                };
+                (
+                    StatusCode::PARTIAL_CONTENT,
+                    asset_range(content.data, from, to),
+                    Some(format!("bytes {start}-{end}/{len}")),
+                )
+            }
+            ByteRange::Unsatisfiable => {
+                return (
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{len}"))],
+                )
+                    .into_response();
+            }
+        };
+
+        let mut response = (
+            status,
+            [
+                (header::CONTENT_TYPE, mime),
+                (header::CACHE_CONTROL, cache_control.to_string()),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+                (header::ETAG, etag),
+            ],
+            Body::from(body),
+        )
+            .into_response();
+        if let Some(value) = content_range.and_then(|v| v.parse().ok()) {
+            response.headers_mut().insert(header::CONTENT_RANGE, value);
+        }
+        if let Some(encoding) = encoding {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+        }
+        response
+    };
+    if varies {
+        response
+            .headers_mut()
+            .insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+    response
+}
+
+/// Content type for an embedded asset: guessed from the extension, else
+/// sniffed from the contents.
+fn content_type_for(path: &str, data: &[u8]) -> String {
+    mime_guess::from_path(path).first().map_or_else(
+        || sniff_content_type(data).to_string(),
+        |mime| mime.to_string(),
+    )
+}
+
+/// Classify `data` by its leading bytes, for assets without an extension.
+///
+/// Recognizes a few common binary signatures, then JSON, then UTF-8 text
+/// without control characters; anything else is `application/octet-stream`.
+fn sniff_content_type(data: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"\0asm", "application/wasm"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return mime;
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+
+    let Ok(text) = std::str::from_utf8(data) else {
+        return "application/octet-stream";
+    };
+    let trimmed = text.trim_start();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok()
+    {
+        return "application/json";
+    }
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+    {
+        return "application/octet-stream";
+    }
+    "text/plain; charset=utf-8"
+}
+
+/// Slice the inclusive span `start..=end` out of an embedded asset's data.
+///
+/// Release builds embed assets as `'static` bytes, so a borrowed `Cow` is
+/// sliced in place: a range request on a large asset costs nothing beyond the
+/// requested span, and the body is handed to hyper without any copy. Only the
+/// owned case (assets read from disk in debug builds) copies the span.
+fn asset_range(data: Cow<'static, [u8]>, start: usize, end: usize) -> Cow<'static, [u8]> {
+    match data {
+        Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[start..=end]),
+        Cow::Owned(bytes) => Cow::Owned(bytes[start..=end].to_vec()),
+    }
+}
+
+/// Outcome of evaluating a `Range` request header against a body length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ByteRange {
+    /// No usable range — serve the whole body.
+    Full,
+    /// Serve the inclusive byte span `start..=end`.
+    Partial { start: u64, end: u64 },
+    /// The range lies outside the body — respond with 416.
+    Unsatisfiable,
+}
+
+/// Parse a single-span `Range: bytes=...` header for a body of `len` bytes.
+///
+/// Multi-range and non-byte units are ignored (served in full), as RFC 9110 allows.
+pub(super) fn parse_byte_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    let span = if start.is_empty() {
+        // Suffix range: the last N bytes.
+        match end.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(suffix) => (len.saturating_sub(suffix), len.saturating_sub(1)),
+            Err(_) => return ByteRange::Full,
+        }
+    } else {
+        let Ok(start) = start.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        let end = if end.is_empty() {
+            len.saturating_sub(1)
+        } else {
+            match end.parse::<u64>() {
+                Ok(end) => end.min(len.saturating_sub(1)),
+                Err(_) => return ByteRange::Full,
+            }
+        };
+        (start, end)
+    };
+
+    if len == 0 || span.0 >= len || span.0 > span.1 {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial {
+        start: span.0,
+        end: span.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_assets_match_build_manifest() {
+        verify_assets().unwrap();
+    }
+
+    #[test]
+    fn asset_layers_prefer_the_first_directory_on_collisions() {
+        fn dist(path: &str) -> Option<&'static str> {
+            match path {
+                "index.html" | "lib/shared.js" => Some("dist"),
+                _ => None,
+            }
+        }
+        fn vendor(path: &str) -> Option<&'static str> {
+            match path {
+                "lib/shared.js" | "lib/vendor-only.js" => Some("vendor"),
+                _ => None,
+            }
+        }
+        let layers = [dist as fn(&str) -> Option<&'static str>, vendor];
 
+        assert_eq!(first_layer_match("lib/shared.js", &layers), Some("dist"));
+        assert_eq!(first_layer_match("index.html", &layers), Some("dist"));
+        assert_eq!(
+            first_layer_match("lib/vendor-only.js", &layers),
+            Some("vendor")
+        );
+        assert_eq!(first_layer_match("missing.js", &layers), None);
+    }
+
+    #[test]
+    fn asset_manifest_reports_mismatched_paths() {
+        let hash = |data: &[u8]| -> [u8; 32] { Sha256::digest(data).into() };
+        let manifest = [
+            (
+                "index.html".to_string(),
+                hash(b"<html>"),
+                b"<html>".as_slice(),
+            ),
             (
-                StatusCode::OK,
-                [
-                    (header::CONTENT_TYPE, mime),
-                    (
-                        header::CACHE_CONTROL,
-                        if path.contains("assets/") {
-                            // Hashed filenames — immutable cache
-                            "public, max-age=31536000, immutable".to_string()
-                        } else {
-                            // index.html etc — no cache
-                            "no-cache".to_string()
-                        },
-                    ),
-                ],
-                content.data.to_vec(),
-            )
-                .into_response()
+                "assets/app.js".to_string(),
+                hash(b"old"),
+                b"tampered".as_slice(),
+            ),
+        ];
+
+        let err = check_asset_manifest(manifest).unwrap_err();
+        assert_eq!(err.mismatched, ["assets/app.js"]);
+        assert!(err.to_string().contains("assets/app.js"));
+    }
+
+    #[test]
+    fn extensionless_assets_are_sniffed() {
+        assert_eq!(
+            content_type_for("LICENSE", "MIT License\n\nCopyright ©".as_bytes()),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            content_type_for("blob", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            "image/png"
+        );
+        assert_eq!(
+            content_type_for("manifest", br#"{"name": "rain"}"#),
+            "application/json"
+        );
+        assert_eq!(
+            content_type_for("data", b"\x00\x01\x02binary"),
+            "application/octet-stream"
+        );
+        assert_eq!(content_type_for("site.css", b"{}"), "text/css");
+    }
+
+    /// An in-memory asset, so tests do not depend on the built `web/dist` bundle.
+    fn fixture_asset(data: &'static [u8]) -> Asset {
+        Asset {
+            data: Cow::Borrowed(data),
+            sha256: Sha256::digest(data).into(),
         }
-        None => (StatusCode::NOT_FOUND, "Not found").into_response(),
+    }
+
+    #[test]
+    fn configured_asset_prefixes_resolve_to_embedded_paths() {
+        let prefixes = ["/_app/".to_string(), "/static/".to_string()];
+        assert_eq!(
+            strip_asset_prefix("/static/logo.png", &prefixes),
+            Some("logo.png")
+        );
+        assert_eq!(
+            strip_asset_prefix("/_app/logo.png", &prefixes),
+            Some("logo.png")
+        );
+        assert_eq!(strip_asset_prefix("/other/logo.png", &prefixes), None);
+
+        let lookup = |path: &str| (path == "logo.png").then(|| fixture_asset(b"\x89PNG"));
+        let served = serve_asset(lookup, "logo.png", &HeaderMap::new());
+        assert_eq!(served.status(), StatusCode::OK);
+        let missing = serve_asset(lookup, "other/logo.png", &HeaderMap::new());
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn asset_prefix_validation_rejects_reserved_and_malformed_prefixes() {
+        assert!(is_valid_asset_prefix("/_app/"));
+        assert!(is_valid_asset_prefix("/static/js/"));
+        for prefix in ["/", "static/", "/static", "/api/", "/ws/x/", "/../"] {
+            assert!(!is_valid_asset_prefix(prefix), "{prefix}");
+        }
+    }
+
+    #[test]
+    fn is_safe_asset_path_rejects_traversal() {
+        assert!(is_safe_asset_path("index.html"));
+        assert!(is_safe_asset_path("app/app.html"));
+        for path in [
+            "",
+            "/index.html",
+            "../index.html",
+            "a/../b.html",
+            "a//b",
+            "C:\\x",
+            "a\nb",
+        ] {
+            assert!(!is_safe_asset_path(path), "{path:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn spa_fallback_serves_configured_index_document() {
+        let lookup = |path: &str| -> Option<Cow<'static, [u8]>> {
+            match path {
+                "index.html" => Some(Cow::Borrowed(b"<html>index</html>")),
+                "app.html" => Some(Cow::Borrowed(b"<html>app</html>")),
+                _ => None,
+            }
+        };
+        let body = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        let content_type = |response: &Response| response.headers()[header::CONTENT_TYPE].clone();
+
+        let response = render_spa_document(lookup, "app.html", "");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(content_type(&response), "text/html; charset=utf-8");
+        assert_eq!(body(response).await, "<html>app</html>");
+
+        let missing = render_spa_document(lookup, "missing.xhtml", "");
+        assert_eq!(content_type(&missing), "text/html; charset=utf-8");
+        assert_eq!(body(missing).await, "<html>index</html>");
+    }
+
+    #[test]
+    fn spa_fallback_derives_content_type_from_index_document() {
+        let lookup = |path: &str| -> Option<Cow<'static, [u8]>> {
+            (path == "app.xhtml")
+                .then_some(Cow::Borrowed(b"<html xmlns=\"x\">app</html>".as_slice()))
+        };
+
+        let response = render_spa_document(lookup, "app.xhtml", "");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/xhtml+xml"
+        );
+    }
+
+    #[test]
+    fn brotli_variant_gets_its_own_etag_and_matching_conditionals() {
+        let lookup = |path: &str| match path {
+            "assets/app.js" => Some(fixture_asset(b"console.log('app');")),
+            "assets/app.js.br" => Some(fixture_asset(b"\x1b\x12\x00brotli")),
+            _ => None,
+        };
+        let request = |accept: Option<&str>, if_none_match: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(accept) = accept {
+                headers.insert(header::ACCEPT_ENCODING, accept.parse().unwrap());
+            }
+            if let Some(etag) = if_none_match {
+                headers.insert(header::IF_NONE_MATCH, etag.parse().unwrap());
+            }
+            serve_asset(lookup, "assets/app.js", &headers)
+        };
+
+        let plain = request(None, None);
+        let compressed = request(Some("gzip, br"), None);
+        assert_eq!(plain.status(), StatusCode::OK);
+        assert_eq!(compressed.status(), StatusCode::OK);
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(compressed.headers()[header::CONTENT_ENCODING], "br");
+        assert_eq!(
+            compressed.headers()[header::CONTENT_TYPE],
+            plain.headers()[header::CONTENT_TYPE]
+        );
+        for response in [&plain, &compressed] {
+            assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        }
+        let plain_etag = plain.headers()[header::ETAG].to_str().unwrap();
+        let compressed_etag = compressed.headers()[header::ETAG].to_str().unwrap();
+        assert_ne!(plain_etag, compressed_etag);
+
+        // Each tag only revalidates the representation it came from.
+        let revalidated = request(Some("br"), Some(compressed_etag));
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(revalidated.headers()[header::ETAG], compressed_etag);
+        assert_eq!(
+            request(None, Some(compressed_etag)).status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            request(Some("br"), Some(plain_etag)).status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            request(None, Some(plain_etag)).status(),
+            StatusCode::NOT_MODIFIED
+        );
+
+        // `q=0` refuses brotli, and ranges address the uncompressed bytes.
+        let refused = request(Some("br;q=0, gzip"), None);
+        assert!(refused.headers().get(header::CONTENT_ENCODING).is_none());
+        let mut ranged = HeaderMap::new();
+        ranged.insert(header::ACCEPT_ENCODING, "br".parse().unwrap());
+        ranged.insert(header::RANGE, "bytes=0-6".parse().unwrap());
+        let partial = serve_asset(lookup, "assets/app.js", &ranged);
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert!(partial.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+
+    #[test]
+    fn favicon_is_served_with_or_without_bundle_copy() {
+        const ICO: &[u8] = b"\x00\x00\x01\x00icon";
+        let bundled = |path: &str| {
+            (path == "favicon.ico").then(|| Asset {
+                data: Cow::Borrowed(ICO),
+                sha256: Sha256::digest(ICO).into(),
+            })
+        };
+
+        for response in [
+            serve_favicon(bundled, &HeaderMap::new()),
+            serve_favicon(|_| None, &HeaderMap::new()),
+        ] {
+            assert_eq!(response.status(), StatusCode::OK);
+            let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+            assert!(content_type.starts_with("image/"), "{content_type}");
+            assert_eq!(
+                response.headers()[header::CACHE_CONTROL],
+                "public, max-age=31536000, immutable"
+            );
+        }
+        let fallback = serve_favicon(|_| None, &HeaderMap::new());
+        assert_eq!(fallback.headers()[header::CONTENT_TYPE], "image/svg+xml");
+    }
+
+    #[test]
+    fn asset_range_borrows_static_data_without_copying() {
+        static DATA: &[u8] = b"0123456789";
+
+        let slice = asset_range(Cow::Borrowed(DATA), 2, 5);
+        assert!(matches!(slice, Cow::Borrowed(_)));
+        assert_eq!(&*slice, b"2345");
+        assert_eq!(slice.as_ptr(), DATA[2..].as_ptr());
+
+        let owned = asset_range(Cow::Owned(DATA.to_vec()), 2, 5);
+        assert!(matches!(owned, Cow::Owned(_)));
+        assert_eq!(&*owned, b"2345");
+    }
+
+    #[test]
+    fn parse_byte_range_handles_common_forms() {
+        assert_eq!(parse_byte_range(None, 10), ByteRange::Full);
+        assert_eq!(
+            parse_byte_range(Some("bytes=2-5"), 10),
+            ByteRange::Partial { start: 2, end: 5 }
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=7-"), 10),
+            ByteRange::Partial { start: 7, end: 9 }
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=-3"), 10),
+            ByteRange::Partial { start: 7, end: 9 }
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=0-99"), 10),
+            ByteRange::Partial { start: 0, end: 9 }
+        );
+        assert_eq!(parse_byte_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+        assert_eq!(
+            parse_byte_range(Some("bytes=10-"), 10),
+            ByteRange::Unsatisfiable
+        );
     }
 }
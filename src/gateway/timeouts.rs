@@ -0,0 +1,145 @@
+//! Per-route request timeouts.
+//!
+//! Every request gets the gateway-wide timeout (`408 Request Timeout`) unless
+//! `[gateway.route_timeouts]` names its path, in which case that duration
+//! applies instead and overruns answer `504 Gateway Timeout`. Either way the
+//! handler future is dropped, cancelling it. Only producing the response is
+//! timed, and streaming routes never take a per-route override.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Long-lived SSE and WebSocket routes, which per-route timeouts skip.
+pub const STREAMING_ROUTES: &[&str] = &["/api/events", "/ws/chat", "/ws/nodes"];
+
+/// Timeouts keyed by route pattern, with a fallback for everything else.
+#[derive(Debug, Clone)]
+pub struct RouteTimeouts {
+    /// `(pattern, timeout)`, most specific pattern first.
+    routes: Vec<(String, Duration)>,
+    default: Duration,
+}
+
+impl RouteTimeouts {
+    /// Build from `[gateway.route_timeouts]` (pattern → seconds).
+    ///
+    /// A pattern is an exact path such as `/api/doctor`, or a prefix ending
+    /// in `*` such as `/api/cron/*`. When several match, exact patterns win,
+    /// then the longest prefix.
+    pub fn new(default: Duration, overrides: &HashMap<String, u64>) -> Self {
+        let mut routes: Vec<(String, Duration)> = overrides
+            .iter()
+            .map(|(pattern, secs)| (pattern.clone(), Duration::from_secs(*secs)))
+            .collect();
+        routes.sort_by(|(a, _), (b, _)| {
+            a.ends_with('*')
+                .cmp(&b.ends_with('*'))
+                .then_with(|| b.len().cmp(&a.len()))
+                .then_with(|| a.cmp(b))
+        });
+        Self { routes, default }
+    }
+
+    /// The per-route override for `path`, if any pattern names it.
+    pub fn route_timeout(&self, path: &str) -> Option<Duration> {
+        if STREAMING_ROUTES.contains(&path) {
+            return None;
+        }
+        self.routes
+            .iter()
+            .find(|(pattern, _)| pattern_matches(pattern, path))
+            .map(|(_, timeout)| *timeout)
+    }
+}
+
+/// Whether `path` is `pattern`, or starts with it when it ends in `*`.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => pattern == path,
+    }
+}
+
+/// Middleware bounding how long a handler may take to produce its response.
+pub async fn route_timeout_middleware(
+    State(timeouts): State<Arc<RouteTimeouts>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let (limit, status) = match timeouts.route_timeout(&path) {
+        Some(limit) => (limit, StatusCode::GATEWAY_TIMEOUT),
+        None => (timeouts.default, StatusCode::REQUEST_TIMEOUT),
+    };
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(path, ?limit, "gateway request timed out");
+            status.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn timeouts(overrides: &[(&str, u64)]) -> RouteTimeouts {
+        let overrides = overrides
+            .iter()
+            .map(|(pattern, secs)| ((*pattern).to_string(), *secs))
+            .collect();
+        RouteTimeouts::new(Duration::from_secs(30), &overrides)
+    }
+
+    #[test]
+    fn route_timeout_prefers_exact_then_longest_prefix() {
+        let timeouts = timeouts(&[("/api/*", 5), ("/api/cron/*", 10), ("/api/doctor", 120)]);
+        assert_eq!(
+            timeouts.route_timeout("/api/doctor"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            timeouts.route_timeout("/api/cron/7"),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            timeouts.route_timeout("/api/status"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(timeouts.route_timeout("/health"), None);
+        assert_eq!(timeouts.route_timeout("/api/events"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_route_times_out_with_504_while_fast_route_succeeds() {
+        let app = Router::new()
+            .route(
+                "/api/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    "late"
+                }),
+            )
+            .route("/api/fast", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(timeouts(&[("/api/*", 1)])),
+                route_timeout_middleware,
+            ));
+        let request = |path: &str| axum::http::Request::get(path).body(Body::empty()).unwrap();
+
+        let slow = app.clone().oneshot(request("/api/slow")).await.unwrap();
+        assert_eq!(slow.status(), StatusCode::GATEWAY_TIMEOUT);
+        let fast = app.oneshot(request("/api/fast")).await.unwrap();
+        assert_eq!(fast.status(), StatusCode::OK);
+    }
+}
@@ -93,6 +93,9 @@ pub mod verifiable_intent;
 pub mod plugins;
 
 pub use config::Config;
+/// Re-exported for the benchmarks only; not part of the public API.
+#[doc(hidden)]
+pub use util::truncate_with_ellipsis_cow;
 
 /// Gateway management subcommands
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
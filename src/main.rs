@@ -41,6 +41,7 @@ use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::{EnvFilter, fmt};
 
 use crate::tools::Tool;
@@ -830,7 +831,9 @@ async fn main() -> Result<()> {
         .with_env_filter(
             EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
         )
-        .finish();
+        .finish()
+        .with(observability::quiet::quiet_filter());
+    observability::quiet::set_quiet(observability::quiet::quiet_requested_by_env());
 
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
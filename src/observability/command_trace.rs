@@ -0,0 +1,149 @@
+//! OpenTelemetry spans for shell command execution.
+//!
+//! When built with `observability-otel`, every command run by the agent's
+//! shell tool produces a `command.execute` span on the trace pipeline shared
+//! with [`OtelObserver`](super::OtelObserver). [`init`] starts that pipeline
+//! for [`OTLP_ENDPOINT_ENV`], or else `[observability] otel_endpoint`, when
+//! the observer has not. Without the feature, recording compiles to nothing;
+//! with the feature but no endpoint, it costs one initialized-flag check.
+
+use crate::config::ObservabilityConfig;
+use std::time::Duration;
+
+/// Environment variable holding the OTLP/HTTP base endpoint for command spans.
+pub const OTLP_ENDPOINT_ENV: &str = "ZEROCLAW_OTLP_ENDPOINT";
+
+/// Outcome of one command execution, as recorded on its span.
+#[derive(Debug, Clone)]
+pub struct CommandExecution<'a> {
+    pub runtime: &'a str,
+    pub duration: Duration,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+}
+
+/// Start exporting command spans if an OTLP endpoint is configured.
+pub fn init(config: &ObservabilityConfig) {
+    #[cfg(feature = "observability-otel")]
+    otlp::init(config);
+    #[cfg(not(feature = "observability-otel"))]
+    let _ = config;
+}
+
+/// Record a span for a finished command, if OTLP export is configured.
+#[inline]
+pub fn record_command_span(execution: &CommandExecution<'_>) {
+    #[cfg(feature = "observability-otel")]
+    otlp::record(execution);
+    #[cfg(not(feature = "observability-otel"))]
+    let _ = execution;
+}
+
+#[cfg(feature = "observability-otel")]
+mod otlp {
+    use super::{CommandExecution, OTLP_ENDPOINT_ENV};
+    use crate::config::ObservabilityConfig;
+    use crate::observability::otel;
+    use opentelemetry::KeyValue;
+    use opentelemetry::trace::{Span, SpanKind, Status, Tracer};
+    use std::time::SystemTime;
+
+    pub(super) fn init(config: &ObservabilityConfig) {
+        let endpoint = std::env::var(OTLP_ENDPOINT_ENV)
+            .ok()
+            .or_else(|| config.otel_endpoint.clone());
+        let Some(endpoint) = endpoint.filter(|endpoint| !endpoint.trim().is_empty()) else {
+            return;
+        };
+        if let Err(e) =
+            otel::shared_tracer_provider(Some(endpoint.trim()), config.otel_service_name.as_deref())
+        {
+            tracing::warn!("Failed to start command span export: {e}");
+        }
+    }
+
+    pub(super) fn record(execution: &CommandExecution<'_>) {
+        if let Some(tracer) = otel::shared_tracer() {
+            record_with(&tracer, execution);
+        }
+    }
+
+    pub(super) fn record_with<T: Tracer>(tracer: &T, execution: &CommandExecution<'_>) {
+        let start_time = SystemTime::now()
+            .checked_sub(execution.duration)
+            .unwrap_or(SystemTime::now());
+        let mut attributes = vec![
+            KeyValue::new("runtime.name", execution.runtime.to_string()),
+            KeyValue::new("duration_s", execution.duration.as_secs_f64()),
+            KeyValue::new("timed_out", execution.timed_out),
+        ];
+        if let Some(code) = execution.exit_code {
+            attributes.push(KeyValue::new("exit_code", i64::from(code)));
+        }
+
+        let mut span = tracer.build(
+            opentelemetry::trace::SpanBuilder::from_name("command.execute")
+                .with_kind(SpanKind::Internal)
+                .with_start_time(start_time)
+                .with_attributes(attributes),
+        );
+        if execution.timed_out || execution.exit_code != Some(0) {
+            span.set_status(Status::error(""));
+        } else {
+            span.set_status(Status::Ok);
+        }
+        span.end();
+    }
+}
+
+#[cfg(all(test, feature = "observability-otel"))]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::{Key, Value};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+    #[test]
+    fn init_falls_back_to_the_configured_endpoint() {
+        init(&ObservabilityConfig {
+            otel_endpoint: Some("http://127.0.0.1:19999".into()),
+            otel_service_name: Some("test".into()),
+            ..ObservabilityConfig::default()
+        });
+        assert!(crate::observability::otel::shared_tracer().is_some());
+    }
+
+    #[test]
+    fn command_span_records_expected_attributes() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        otlp::record_with(
+            &provider.tracer("test"),
+            &CommandExecution {
+                runtime: "native",
+                duration: Duration::from_millis(120),
+                exit_code: Some(2),
+                timed_out: false,
+            },
+        );
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name, "command.execute");
+
+        let attr = |key: &'static str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key == Key::from_static_str(key))
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(attr("runtime.name"), Some(Value::from("native")));
+        assert_eq!(attr("exit_code"), Some(Value::I64(2)));
+        assert_eq!(attr("timed_out"), Some(Value::Bool(false)));
+        assert_eq!(attr("duration_s"), Some(Value::F64(0.12)));
+    }
+}
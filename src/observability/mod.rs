@@ -1,3 +1,4 @@
+pub mod command_trace;
 pub mod log;
 pub mod multi;
 pub mod noop;
@@ -5,6 +6,8 @@ pub mod noop;
 pub mod otel;
 #[cfg(feature = "observability-prometheus")]
 pub mod prometheus;
+pub mod quiet;
+pub mod runtime_metrics;
 pub mod runtime_trace;
 pub mod traits;
 pub mod verbose;
@@ -26,6 +29,7 @@ use crate::config::ObservabilityConfig;
 
 /// Factory: create the right observer from config
 pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
+    command_trace::init(config);
     match config.backend.as_str() {
         "log" => Box::new(LogObserver::new()),
         "verbose" => Box::new(VerboseObserver::new()),
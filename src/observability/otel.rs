@@ -6,8 +6,63 @@ use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::metrics::SdkMeterProvider;
 use opentelemetry_sdk::trace::SdkTracerProvider;
 use std::any::Any;
+use std::sync::OnceLock;
 use std::time::SystemTime;
 
+/// Collector endpoint used when none is configured.
+const DEFAULT_ENDPOINT: &str = "http://localhost:4318";
+
+/// Service name reported when none is configured.
+const DEFAULT_SERVICE_NAME: &str = "R.A.I.N.";
+
+/// The process's one OTLP trace pipeline, shared by [`OtelObserver`] and
+/// [`command_trace`](super::command_trace) spans.
+pub(crate) struct SharedTracerProvider {
+    pub(crate) provider: SdkTracerProvider,
+    pub(crate) service_name: String,
+}
+
+static SHARED_TRACER_PROVIDER: OnceLock<SharedTracerProvider> = OnceLock::new();
+
+/// The shared trace pipeline, built for `endpoint` and `service_name` by the
+/// first caller; later callers get that pipeline whatever they pass.
+pub(crate) fn shared_tracer_provider(
+    endpoint: Option<&str>,
+    service_name: Option<&str>,
+) -> Result<&'static SharedTracerProvider, String> {
+    if let Some(shared) = SHARED_TRACER_PROVIDER.get() {
+        return Ok(shared);
+    }
+    let endpoint = endpoint.unwrap_or(DEFAULT_ENDPOINT);
+    let service_name = service_name.unwrap_or(DEFAULT_SERVICE_NAME);
+    let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .map_err(|e| format!("Failed to create OTLP span exporter: {e}"))?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(span_exporter)
+        .with_resource(
+            opentelemetry_sdk::Resource::builder()
+                .with_service_name(service_name.to_string())
+                .build(),
+        )
+        .build();
+    Ok(SHARED_TRACER_PROVIDER.get_or_init(|| SharedTracerProvider {
+        provider,
+        service_name: service_name.to_string(),
+    }))
+}
+
+/// A tracer from the shared pipeline, if anything has built it.
+pub(crate) fn shared_tracer() -> Option<opentelemetry_sdk::trace::SdkTracer> {
+    use opentelemetry::trace::TracerProvider as _;
+
+    SHARED_TRACER_PROVIDER
+        .get()
+        .map(|shared| shared.provider.tracer(shared.service_name.clone()))
+}
+
 /// OpenTelemetry-backed observer — exports traces and metrics via OTLP.
 pub struct OtelObserver {
     tracer_provider: SdkTracerProvider,
@@ -38,27 +93,14 @@ impl OtelObserver {
     /// Uses HTTP/protobuf transport (port 4318 by default).
     /// Falls back to `http://localhost:4318` if no endpoint is provided.
     pub fn new(endpoint: Option<&str>, service_name: Option<&str>) -> Result<Self, String> {
-        let base_endpoint = endpoint.unwrap_or("http://localhost:4318");
-        let traces_endpoint = format!("{}/v1/traces", base_endpoint.trim_end_matches('/'));
+        let base_endpoint = endpoint.unwrap_or(DEFAULT_ENDPOINT);
         let metrics_endpoint = format!("{}/v1/metrics", base_endpoint.trim_end_matches('/'));
-        let service_name = service_name.unwrap_or("R.A.I.N.");
+        let service_name = service_name.unwrap_or(DEFAULT_SERVICE_NAME);
 
         // ── Trace exporter ──────────────────────────────────────
-        let span_exporter = opentelemetry_otlp::SpanExporter::builder()
-            .with_http()
-            .with_endpoint(&traces_endpoint)
-            .build()
-            .map_err(|e| format!("Failed to create OTLP span exporter: {e}"))?;
-
-        let tracer_provider = SdkTracerProvider::builder()
-            .with_batch_exporter(span_exporter)
-            .with_resource(
-                opentelemetry_sdk::Resource::builder()
-                    .with_service_name(service_name.to_string())
-                    .build(),
-            )
-            .build();
-
+        let tracer_provider = shared_tracer_provider(endpoint, Some(service_name))?
+            .provider
+            .clone();
         global::set_tracer_provider(tracer_provider.clone());
 
         // ── Metric exporter ─────────────────────────────────────
@@ -0,0 +1,49 @@
+//! Quiet mode: suppress non-error logging from the gateway and runtime layers.
+//!
+//! Embedders that want silence enable it with `[gateway] quiet = true` or the
+//! [`QUIET_ENV`] variable. The subscriber must include [`quiet_filter`] for the
+//! switch to take effect; `main` installs it alongside the `RUST_LOG` filter.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Level;
+use tracing_subscriber::filter::FilterFn;
+
+/// Env var that enables quiet mode regardless of configuration.
+pub const QUIET_ENV: &str = "ZEROCLAW_QUIET";
+
+/// Modules (below the crate root) silenced in quiet mode.
+const QUIET_MODULES: &[&str] = &["gateway", "runtime"];
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Whether [`QUIET_ENV`] is set to a truthy value (`1`, `true`, `yes`, `on`).
+pub fn quiet_requested_by_env() -> bool {
+    std::env::var(QUIET_ENV).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Turn quiet mode on or off for every subscriber using [`quiet_filter`].
+pub fn set_quiet(enabled: bool) {
+    QUIET.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Filter rejecting below-error events from quiet modules while quiet mode is on.
+pub fn quiet_filter() -> FilterFn {
+    FilterFn::new(|metadata| {
+        !is_quiet()
+            || *metadata.level() == Level::ERROR
+            || !metadata
+                .target()
+                .split("::")
+                .nth(1)
+                .is_some_and(|module| QUIET_MODULES.contains(&module))
+    })
+}
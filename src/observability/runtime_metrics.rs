@@ -0,0 +1,199 @@
+//! Process-wide Prometheus metrics for gateway requests and command execution.
+//!
+//! Unlike [`PrometheusObserver`](super::PrometheusObserver), these metrics do not
+//! depend on the configured observer backend: the gateway request middleware and
+//! the command execution helpers update them directly, and `GET /metrics`
+//! appends them to its output. Without `observability-prometheus` every call is
+//! a no-op and [`encode`] returns an empty string.
+
+use std::time::Duration;
+
+/// Record one completed gateway request.
+///
+/// `route` should be the matched route template (e.g. `/api/cron/{id}`), not
+/// the raw path, to keep label cardinality bounded.
+pub fn record_http_request(route: &str, method: &str, status: u16, duration: Duration) {
+    #[cfg(feature = "observability-prometheus")]
+    prom::global().record_http_request(route, method, status, duration);
+    #[cfg(not(feature = "observability-prometheus"))]
+    let _ = (route, method, status, duration);
+}
+
+/// Record one gateway connection closed because the connection cap was reached.
+pub fn record_gateway_connection_rejected() {
+    #[cfg(feature = "observability-prometheus")]
+    prom::global().record_gateway_connection_rejected();
+}
+
+/// Record one finished command execution on `runtime`.
+pub fn record_command_execution(runtime: &str, success: bool, duration: Duration) {
+    #[cfg(feature = "observability-prometheus")]
+    prom::global().record_command_execution(runtime, success, duration);
+    #[cfg(not(feature = "observability-prometheus"))]
+    let _ = (runtime, success, duration);
+}
+
+/// Render all runtime metrics in Prometheus text exposition format.
+pub fn encode() -> String {
+    #[cfg(feature = "observability-prometheus")]
+    {
+        prom::global().encode()
+    }
+    #[cfg(not(feature = "observability-prometheus"))]
+    {
+        String::new()
+    }
+}
+
+#[cfg(feature = "observability-prometheus")]
+mod prom {
+    use prometheus::{
+        Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Registry, TextEncoder,
+    };
+    use std::sync::LazyLock;
+    use std::time::Duration;
+
+    static GLOBAL: LazyLock<RuntimeMetrics> = LazyLock::new(RuntimeMetrics::new);
+
+    pub(super) fn global() -> &'static RuntimeMetrics {
+        &GLOBAL
+    }
+
+    pub(super) struct RuntimeMetrics {
+        registry: Registry,
+        http_requests: IntCounterVec,
+        http_duration: HistogramVec,
+        rejected_connections: IntCounter,
+        command_executions: IntCounterVec,
+        command_duration: HistogramVec,
+    }
+
+    impl RuntimeMetrics {
+        fn new() -> Self {
+            let registry = Registry::new();
+
+            let http_requests = IntCounterVec::new(
+                prometheus::Opts::new("rain_gateway_requests_total", "Total gateway HTTP requests"),
+                &["route", "method", "status"],
+            )
+            .expect("valid metric");
+
+            let http_duration = HistogramVec::new(
+                HistogramOpts::new(
+                    "rain_gateway_request_duration_seconds",
+                    "Gateway HTTP request duration in seconds",
+                )
+                .buckets(vec![0.005, 0.025, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0, 30.0]),
+                &["route", "method"],
+            )
+            .expect("valid metric");
+
+            let rejected_connections = IntCounter::new(
+                "rain_gateway_connections_rejected_total",
+                "Gateway connections closed because max_connections was reached",
+            )
+            .expect("valid metric");
+
+            let command_executions = IntCounterVec::new(
+                prometheus::Opts::new(
+                    "rain_command_executions_total",
+                    "Total shell command executions",
+                ),
+                &["runtime", "success"],
+            )
+            .expect("valid metric");
+
+            let command_duration = HistogramVec::new(
+                HistogramOpts::new(
+                    "rain_command_duration_seconds",
+                    "Shell command execution duration in seconds",
+                )
+                .buckets(vec![0.01, 0.1, 0.5, 1.0, 5.0, 15.0, 30.0, 60.0, 300.0]),
+                &["runtime"],
+            )
+            .expect("valid metric");
+
+            registry
+                .register(Box::new(http_requests.clone()))
+                .expect("register metric");
+            registry
+                .register(Box::new(http_duration.clone()))
+                .expect("register metric");
+            registry
+                .register(Box::new(rejected_connections.clone()))
+                .expect("register metric");
+            registry
+                .register(Box::new(command_executions.clone()))
+                .expect("register metric");
+            registry
+                .register(Box::new(command_duration.clone()))
+                .expect("register metric");
+
+            Self {
+                registry,
+                http_requests,
+                http_duration,
+                rejected_connections,
+                command_executions,
+                command_duration,
+            }
+        }
+
+        pub(super) fn record_http_request(
+            &self,
+            route: &str,
+            method: &str,
+            status: u16,
+            duration: Duration,
+        ) {
+            let status = status.to_string();
+            self.http_requests
+                .with_label_values(&[route, method, status.as_str()])
+                .inc();
+            self.http_duration
+                .with_label_values(&[route, method])
+                .observe(duration.as_secs_f64());
+        }
+
+        pub(super) fn record_gateway_connection_rejected(&self) {
+            self.rejected_connections.inc();
+        }
+
+        pub(super) fn record_command_execution(
+            &self,
+            runtime: &str,
+            success: bool,
+            duration: Duration,
+        ) {
+            self.command_executions
+                .with_label_values(&[runtime, if success { "true" } else { "false" }])
+                .inc();
+            self.command_duration
+                .with_label_values(&[runtime])
+                .observe(duration.as_secs_f64());
+        }
+
+        pub(super) fn encode(&self) -> String {
+            let encoder = TextEncoder::new();
+            let families = self.registry.gather();
+            let mut buf = Vec::new();
+            encoder.encode(&families, &mut buf).unwrap_or_default();
+            String::from_utf8(buf).unwrap_or_default()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "observability-prometheus"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_executions_are_encoded() {
+        record_command_execution("metrics-test-runtime", true, Duration::from_millis(20));
+
+        let output = encode();
+        assert!(output.contains("rain_command_executions_total"));
+        assert!(output.contains("rain_command_duration_seconds"));
+        assert!(output.contains(r#"runtime="metrics-test-runtime""#));
+    }
+}
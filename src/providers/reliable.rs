@@ -2,6 +2,7 @@ use super::Provider;
 use super::traits::{
     ChatMessage, ChatRequest, ChatResponse, StreamChunk, StreamOptions, StreamResult,
 };
+use crate::util::Backoff;
 use async_trait::async_trait;
 use futures_util::{StreamExt, stream};
 use std::collections::HashMap;
@@ -276,6 +277,9 @@ fn push_failure(
 // Loop invariant: `failures` accumulates every failed attempt so the final
 // error message gives operators a complete diagnostic trail.
 
+/// Longest exponential backoff between retries, before any Retry-After.
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
 /// Provider wrapper with retry, fallback, auth rotation, and model failover.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
@@ -334,6 +338,19 @@ impl ReliableProvider {
         Some(&self.api_keys[idx])
     }
 
+    /// Retry delays for one (provider, model) pair: doubling from the base
+    /// backoff up to [`MAX_BACKOFF`].
+    fn backoff(&self) -> Backoff {
+        Backoff::new(Duration::from_millis(self.base_backoff_ms), MAX_BACKOFF)
+    }
+
+    /// Milliseconds to wait before the next retry: the next `backoff` delay,
+    /// stretched to honor a Retry-After in `err`.
+    fn next_backoff_ms(&self, backoff: &mut Backoff, err: &anyhow::Error) -> u64 {
+        let delay = u64::try_from(backoff.next_delay().as_millis()).unwrap_or(u64::MAX);
+        self.compute_backoff(delay, err)
+    }
+
     /// Compute backoff duration, respecting Retry-After if present.
     fn compute_backoff(&self, base: u64, err: &anyhow::Error) -> u64 {
         if let Some(retry_after) = parse_retry_after_ms(err) {
@@ -373,7 +390,7 @@ impl Provider for ReliableProvider {
         // retryable error, sleep with exponential backoff and retry.
         for current_model in &models {
             for (provider_name, provider) in &self.providers {
-                let mut backoff_ms = self.base_backoff_ms;
+                let mut backoff = self.backoff();
 
                 for attempt in 0..=self.max_retries {
                     match provider
@@ -454,7 +471,7 @@ impl Provider for ReliableProvider {
                             }
 
                             if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
+                                let wait = self.next_backoff_ms(&mut backoff, &e);
                                 tracing::warn!(
                                     provider = provider_name,
                                     model = *current_model,
@@ -465,7 +482,6 @@ impl Provider for ReliableProvider {
                                     "Provider call failed, retrying"
                                 );
                                 tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
                         }
                     }
@@ -506,7 +522,7 @@ impl Provider for ReliableProvider {
 
         for current_model in &models {
             for (provider_name, provider) in &self.providers {
-                let mut backoff_ms = self.base_backoff_ms;
+                let mut backoff = self.backoff();
 
                 for attempt in 0..=self.max_retries {
                     match provider
@@ -602,7 +618,7 @@ impl Provider for ReliableProvider {
                             }
 
                             if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
+                                let wait = self.next_backoff_ms(&mut backoff, &e);
                                 tracing::warn!(
                                     provider = provider_name,
                                     model = *current_model,
@@ -613,7 +629,6 @@ impl Provider for ReliableProvider {
                                     "Provider call failed, retrying"
                                 );
                                 tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
                         }
                     }
@@ -660,7 +675,7 @@ impl Provider for ReliableProvider {
 
         for current_model in &models {
             for (provider_name, provider) in &self.providers {
-                let mut backoff_ms = self.base_backoff_ms;
+                let mut backoff = self.backoff();
 
                 for attempt in 0..=self.max_retries {
                     match provider
@@ -756,7 +771,7 @@ impl Provider for ReliableProvider {
                             }
 
                             if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
+                                let wait = self.next_backoff_ms(&mut backoff, &e);
                                 tracing::warn!(
                                     provider = provider_name,
                                     model = *current_model,
@@ -767,7 +782,6 @@ impl Provider for ReliableProvider {
                                     "Provider call failed, retrying"
                                 );
                                 tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
                         }
                     }
@@ -800,7 +814,7 @@ impl Provider for ReliableProvider {
 
         for current_model in &models {
             for (provider_name, provider) in &self.providers {
-                let mut backoff_ms = self.base_backoff_ms;
+                let mut backoff = self.backoff();
 
                 for attempt in 0..=self.max_retries {
                     let req = ChatRequest {
@@ -897,7 +911,7 @@ impl Provider for ReliableProvider {
                             }
 
                             if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
+                                let wait = self.next_backoff_ms(&mut backoff, &e);
                                 tracing::warn!(
                                     provider = provider_name,
                                     model = *current_model,
@@ -908,7 +922,6 @@ impl Provider for ReliableProvider {
                                     "Provider call failed, retrying"
                                 );
                                 tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
                         }
                     }
@@ -1594,6 +1607,22 @@ mod tests {
         assert_eq!(provider.compute_backoff(500, &err), 3_000);
     }
 
+    #[test]
+    fn next_backoff_doubles_up_to_cap_unless_retry_after_is_longer() {
+        let provider = ReliableProvider::new(vec![], 0, 4_000);
+        let mut backoff = provider.backoff();
+        let err = anyhow::anyhow!("503 Service Unavailable");
+        assert_eq!(provider.next_backoff_ms(&mut backoff, &err), 4_000);
+        assert_eq!(provider.next_backoff_ms(&mut backoff, &err), 8_000);
+        assert_eq!(provider.next_backoff_ms(&mut backoff, &err), 10_000);
+
+        let rate_limited = anyhow::anyhow!("429 Retry-After: 20");
+        assert_eq!(
+            provider.next_backoff_ms(&mut backoff, &rate_limited),
+            20_000
+        );
+    }
+
     #[test]
     fn compute_backoff_caps_at_30s() {
         let provider = ReliableProvider::new(vec![], 0, 500);
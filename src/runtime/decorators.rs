@@ -0,0 +1,544 @@
+//! Composable [`RuntimeAdapter`] wrappers.
+//!
+//! Each decorator owns an inner adapter, delegates every method to it, and
+//! layers one concern on top, so behaviour can be added to any runtime
+//! without modifying it.
+
+use super::traits::RuntimeAdapter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Implement the listed [`RuntimeAdapter`] methods by forwarding them to
+/// `self.inner`.
+///
+/// Decorators write the methods they change by hand and list the rest here,
+/// so a new trait method gets one arm below instead of a copy per decorator.
+macro_rules! forward_to_inner {
+    ($($method:ident),* $(,)?) => {
+        $($crate::runtime::decorators::forward_to_inner!(@$method);)*
+    };
+    (@name) => {
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+    };
+    (@has_shell_access) => {
+        fn has_shell_access(&self) -> bool {
+            self.inner.has_shell_access()
+        }
+    };
+    (@has_filesystem_access) => {
+        fn has_filesystem_access(&self) -> bool {
+            self.inner.has_filesystem_access()
+        }
+    };
+    (@storage_path) => {
+        fn storage_path(&self) -> std::path::PathBuf {
+            self.inner.storage_path()
+        }
+    };
+    (@supports_long_running) => {
+        fn supports_long_running(&self) -> bool {
+            self.inner.supports_long_running()
+        }
+    };
+    (@has_network_access) => {
+        fn has_network_access(&self) -> bool {
+            self.inner.has_network_access()
+        }
+    };
+    (@has_writable_tmp) => {
+        fn has_writable_tmp(&self) -> bool {
+            self.inner.has_writable_tmp()
+        }
+    };
+    (@storage_available_bytes) => {
+        fn storage_available_bytes(&self) -> Option<u64> {
+            self.inner.storage_available_bytes()
+        }
+    };
+    (@memory_budget) => {
+        fn memory_budget(&self) -> u64 {
+            self.inner.memory_budget()
+        }
+    };
+    (@default_command_timeout) => {
+        fn default_command_timeout(&self) -> Option<std::time::Duration> {
+            self.inner.default_command_timeout()
+        }
+    };
+    (@max_concurrent_processes) => {
+        fn max_concurrent_processes(&self) -> usize {
+            self.inner.max_concurrent_processes()
+        }
+    };
+    (@graceful_signal) => {
+        fn graceful_signal(&self) -> Option<i32> {
+            self.inner.graceful_signal()
+        }
+    };
+    (@path_style) => {
+        fn path_style(&self) -> $crate::runtime::traits::PathStyle {
+            self.inner.path_style()
+        }
+    };
+    (@max_command_length) => {
+        fn max_command_length(&self) -> Option<usize> {
+            self.inner.max_command_length()
+        }
+    };
+    (@shell_program) => {
+        fn shell_program(&self) -> Option<String> {
+            self.inner.shell_program()
+        }
+    };
+    (@preprocessors) => {
+        fn preprocessors(
+            &self,
+        ) -> &[std::sync::Arc<dyn $crate::runtime::preprocess::CommandPreprocessor>] {
+            self.inner.preprocessors()
+        }
+    };
+    (@preprocess_command) => {
+        fn preprocess_command(&self, command: &str) -> String {
+            self.inner.preprocess_command(command)
+        }
+    };
+    (@health_probe) => {
+        fn health_probe(&self) -> Option<tokio::process::Command> {
+            self.inner.health_probe()
+        }
+    };
+    (@replayed_result) => {
+        fn replayed_result(&self, command: &str) -> Option<$crate::runtime::exec::CommandResult> {
+            self.inner.replayed_result(command)
+        }
+    };
+    (@observe_result) => {
+        fn observe_result(&self, result: &$crate::runtime::exec::CommandResult) {
+            self.inner.observe_result(result);
+        }
+    };
+    (@translate_path) => {
+        fn translate_path(
+            &self,
+            host_path: &std::path::Path,
+            workspace_dir: &std::path::Path,
+        ) -> std::path::PathBuf {
+            self.inner.translate_path(host_path, workspace_dir)
+        }
+    };
+    (@resolve_program) => {
+        fn resolve_program(
+            &self,
+            name: &str,
+            workspace_dir: &std::path::Path,
+        ) -> Option<std::path::PathBuf> {
+            self.inner.resolve_program(name, workspace_dir)
+        }
+    };
+    (@available_interpreters) => {
+        fn available_interpreters(&self, workspace_dir: &std::path::Path) -> Vec<String> {
+            self.inner.available_interpreters(workspace_dir)
+        }
+    };
+    (@effective_environment) => {
+        fn effective_environment(&self, workspace_dir: &std::path::Path) -> Vec<(String, String)> {
+            self.inner.effective_environment(workspace_dir)
+        }
+    };
+    (@default_env_additions) => {
+        fn default_env_additions(&self) -> Vec<(String, String)> {
+            self.inner.default_env_additions()
+        }
+    };
+    (@build_shell_command) => {
+        fn build_shell_command(
+            &self,
+            command: &str,
+            workspace_dir: &std::path::Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            self.inner.build_shell_command(command, workspace_dir)
+        }
+    };
+    (@build_exec_command) => {
+        fn build_exec_command(
+            &self,
+            argv: &[String],
+            workspace_dir: &std::path::Path,
+        ) -> Option<anyhow::Result<tokio::process::Command>> {
+            self.inner.build_exec_command(argv, workspace_dir)
+        }
+    };
+    (@build_from_argv) => {
+        fn build_from_argv(
+            &self,
+            argv: &[String],
+            workspace_dir: &std::path::Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            self.inner.build_from_argv(argv, workspace_dir)
+        }
+    };
+    (@build_interpreted_command) => {
+        fn build_interpreted_command(
+            &self,
+            interpreter: &str,
+            flag: &str,
+            code: &str,
+            workspace_dir: &std::path::Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            self.inner
+                .build_interpreted_command(interpreter, flag, code, workspace_dir)
+        }
+    };
+}
+
+pub(crate) use forward_to_inner;
+
+/// Render a command as a single shell-like line for logs and diagnostics.
+///
+/// Arguments containing whitespace or quotes are single-quoted; the result is
+/// meant for humans, not for re-execution.
+pub fn render_command(command: &tokio::process::Command) -> String {
+    let std_command = command.as_std();
+    std::iter::once(std_command.get_program())
+        .chain(std_command.get_args())
+        .map(|part| {
+            let part = part.to_string_lossy();
+            if part.is_empty()
+                || part.contains(|c: char| c.is_whitespace() || c == '\'' || c == '"')
+            {
+                format!("'{}'", part.replace('\'', r"'\''"))
+            } else {
+                part.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Runtime decorator that emits `tracing` events around every adapter call.
+#[derive(Debug, Clone)]
+pub struct LoggingRuntime<R: RuntimeAdapter> {
+    inner: R,
+    name: String,
+}
+
+impl<R: RuntimeAdapter> LoggingRuntime<R> {
+    pub fn new(inner: R) -> Self {
+        let name = format!("{} (logged)", inner.name());
+        Self { inner, name }
+    }
+
+    /// Borrow the wrapped adapter.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Log the outcome of building a process and pass it through.
+    fn log_built(
+        &self,
+        result: anyhow::Result<tokio::process::Command>,
+    ) -> anyhow::Result<tokio::process::Command> {
+        match result {
+            Ok(process) => {
+                tracing::info!(
+                    runtime = self.inner.name(),
+                    rendered = %render_command(&process),
+                    "built command"
+                );
+                Ok(process)
+            }
+            Err(err) => {
+                tracing::warn!(
+                    runtime = self.inner.name(),
+                    error = %err,
+                    "failed to build command"
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<R: RuntimeAdapter> RuntimeAdapter for LoggingRuntime<R> {
+    forward_to_inner!(
+        storage_available_bytes,
+        max_concurrent_processes,
+        graceful_signal,
+        path_style,
+        max_command_length,
+        shell_program,
+        preprocessors,
+        preprocess_command,
+        health_probe,
+        replayed_result,
+        observe_result,
+        translate_path,
+        resolve_program,
+        available_interpreters,
+        effective_environment,
+        default_env_additions,
+        build_exec_command,
+    );
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn has_shell_access(&self) -> bool {
+        let value = self.inner.has_shell_access();
+        tracing::debug!(runtime = self.inner.name(), value, "has_shell_access");
+        value
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        let value = self.inner.has_filesystem_access();
+        tracing::debug!(runtime = self.inner.name(), value, "has_filesystem_access");
+        value
+    }
+
+    fn storage_path(&self) -> PathBuf {
+        let value = self.inner.storage_path();
+        tracing::debug!(runtime = self.inner.name(), path = %value.display(), "storage_path");
+        value
+    }
+
+    fn supports_long_running(&self) -> bool {
+        let value = self.inner.supports_long_running();
+        tracing::debug!(runtime = self.inner.name(), value, "supports_long_running");
+        value
+    }
+
+    fn has_network_access(&self) -> bool {
+        let value = self.inner.has_network_access();
+        tracing::debug!(runtime = self.inner.name(), value, "has_network_access");
+        value
+    }
+
+    fn has_writable_tmp(&self) -> bool {
+        let value = self.inner.has_writable_tmp();
+        tracing::debug!(runtime = self.inner.name(), value, "has_writable_tmp");
+        value
+    }
+
+    fn memory_budget(&self) -> u64 {
+        let value = self.inner.memory_budget();
+        tracing::debug!(runtime = self.inner.name(), value, "memory_budget");
+        value
+    }
+
+    fn default_command_timeout(&self) -> Option<Duration> {
+        let value = self.inner.default_command_timeout();
+        tracing::debug!(runtime = self.inner.name(), value = ?value, "default_command_timeout");
+        value
+    }
+
+    fn build_shell_command(
+        &self,
+        command: &str,
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        tracing::debug!(
+            runtime = self.inner.name(),
+            command,
+            workspace = %workspace_dir.display(),
+            "building shell command"
+        );
+        self.log_built(self.inner.build_shell_command(command, workspace_dir))
+    }
+
+    fn build_from_argv(
+        &self,
+        argv: &[String],
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        tracing::debug!(
+            runtime = self.inner.name(),
+            argv = ?argv,
+            workspace = %workspace_dir.display(),
+            "building command from argv"
+        );
+        self.log_built(self.inner.build_from_argv(argv, workspace_dir))
+    }
+
+    fn build_interpreted_command(
+        &self,
+        interpreter: &str,
+        flag: &str,
+        code: &str,
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        tracing::debug!(
+            runtime = self.inner.name(),
+            interpreter,
+            workspace = %workspace_dir.display(),
+            "building interpreted command"
+        );
+        self.log_built(
+            self.inner
+                .build_interpreted_command(interpreter, flag, code, workspace_dir),
+        )
+    }
+}
+
+/// Runtime decorator that imposes a fixed command timeout on any adapter.
+///
+/// Execution helpers read [`RuntimeAdapter::default_command_timeout`], so
+/// wrapping a runtime is enough to bound every command it runs.
+#[derive(Debug, Clone)]
+pub struct TimeoutRuntime<R: RuntimeAdapter> {
+    inner: R,
+    timeout: Duration,
+}
+
+impl<R: RuntimeAdapter> TimeoutRuntime<R> {
+    pub fn new(inner: R, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    /// Borrow the wrapped adapter.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: RuntimeAdapter> RuntimeAdapter for TimeoutRuntime<R> {
+    forward_to_inner!(
+        name,
+        has_shell_access,
+        has_filesystem_access,
+        storage_path,
+        supports_long_running,
+        has_network_access,
+        has_writable_tmp,
+        storage_available_bytes,
+        memory_budget,
+        max_concurrent_processes,
+        graceful_signal,
+        path_style,
+        max_command_length,
+        shell_program,
+        preprocessors,
+        preprocess_command,
+        health_probe,
+        replayed_result,
+        observe_result,
+        translate_path,
+        resolve_program,
+        available_interpreters,
+        effective_environment,
+        default_env_additions,
+        build_shell_command,
+        build_exec_command,
+        build_from_argv,
+        build_interpreted_command,
+    );
+
+    fn default_command_timeout(&self) -> Option<Duration> {
+        Some(self.timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::NativeRuntime;
+
+    #[test]
+    fn logging_runtime_preserves_native_capabilities() {
+        let native = NativeRuntime::new();
+        let logged = LoggingRuntime::new(NativeRuntime::new());
+
+        assert_eq!(logged.has_shell_access(), native.has_shell_access());
+        assert_eq!(
+            logged.has_filesystem_access(),
+            native.has_filesystem_access()
+        );
+        assert_eq!(
+            logged.supports_long_running(),
+            native.supports_long_running()
+        );
+        assert_eq!(logged.memory_budget(), native.memory_budget());
+        assert_eq!(logged.storage_path(), native.storage_path());
+    }
+
+    #[test]
+    fn logging_runtime_suffixes_inner_name() {
+        let logged = LoggingRuntime::new(NativeRuntime::new());
+        assert_eq!(logged.name(), "native (logged)");
+        assert_eq!(logged.inner().name(), "native");
+    }
+
+    #[test]
+    fn logging_runtime_delegates_build_shell_command() {
+        let cwd = std::env::temp_dir();
+        let command = LoggingRuntime::new(NativeRuntime::new())
+            .build_shell_command("echo hello", &cwd)
+            .unwrap();
+        assert!(render_command(&command).contains("'echo hello'"));
+    }
+
+    #[test]
+    fn render_command_quotes_args_with_spaces() {
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg("echo it's");
+        assert_eq!(render_command(&command), r"sh -c 'echo it'\''s'");
+    }
+
+    #[test]
+    fn timeout_runtime_reports_configured_timeout() {
+        let runtime = TimeoutRuntime::new(NativeRuntime::new(), Duration::from_secs(5));
+        assert_eq!(runtime.inner().default_command_timeout(), None);
+        assert_eq!(
+            runtime.default_command_timeout(),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn timeout_runtime_delegates_capabilities() {
+        let native = NativeRuntime::new();
+        let runtime = TimeoutRuntime::new(NativeRuntime::new(), Duration::from_secs(5));
+
+        assert_eq!(runtime.name(), "native");
+        assert_eq!(runtime.has_shell_access(), native.has_shell_access());
+        assert_eq!(
+            runtime.has_filesystem_access(),
+            native.has_filesystem_access()
+        );
+        assert_eq!(
+            runtime.supports_long_running(),
+            native.supports_long_running()
+        );
+        assert_eq!(runtime.memory_budget(), native.memory_budget());
+        assert_eq!(runtime.storage_path(), native.storage_path());
+    }
+
+    #[test]
+    fn timeout_runtime_composes_with_logging() {
+        let runtime = LoggingRuntime::new(TimeoutRuntime::new(
+            NativeRuntime::new(),
+            Duration::from_millis(250),
+        ));
+        assert_eq!(runtime.name(), "native (logged)");
+        assert_eq!(
+            runtime.default_command_timeout(),
+            Some(Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn decorators_forward_preprocessing_and_interpreted_commands() {
+        let native = NativeRuntime::new()
+            .with_preprocessor(std::sync::Arc::new(crate::runtime::TimingPreprocessor));
+        let runtime = LoggingRuntime::new(TimeoutRuntime::new(native, Duration::from_secs(5)));
+
+        assert_eq!(runtime.preprocess_command("a | b"), "time (\na | b\n)");
+        let cwd = std::env::temp_dir();
+        let command = runtime
+            .build_interpreted_command("python3", "-c", "print(1)", &cwd)
+            .unwrap();
+        assert!(render_command(&command).contains("python3 -c print(1)"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Free-space queries for the volume behind a path.
+
+use std::path::Path;
+
+/// Bytes available to this process on the volume holding `path`.
+///
+/// `path` need not exist yet: the nearest existing ancestor is queried, so a
+/// storage directory that has not been created still reports its volume.
+/// Returns `None` if no ancestor exists or the platform query fails.
+pub fn available_bytes(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    query_available_bytes(existing)
+}
+
+#[cfg(unix)]
+fn query_available_bytes(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` points to
+    // writable memory sized for a `statvfs`, which the call fills on success.
+    let rc = unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) };
+    if rc != 0 {
+        return None;
+    }
+    // SAFETY: `statvfs` returned 0, so it initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+    // Field widths differ between platforms (u32 on macOS, u64 on Linux).
+    #[allow(clippy::useless_conversion)]
+    let (blocks, block_size) = (u64::from(stat.f_bavail), u64::from(stat.f_frsize));
+    Some(blocks.saturating_mul(block_size))
+}
+
+#[cfg(windows)]
+fn query_available_bytes(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_bytes_available_to_caller: *mut u64,
+            total_bytes: *mut u64,
+            total_free_bytes: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut available = 0_u64;
+    // SAFETY: `wide` is a NUL-terminated UTF-16 path, `available` is a valid
+    // out-pointer, and the API accepts null for the totals it should skip.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &raw mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn query_available_bytes(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn available_bytes_falls_back_to_existing_ancestor() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = available_bytes(dir.path()).unwrap();
+        assert!(existing > 0);
+        assert!(available_bytes(&dir.path().join("not/created/yet")).is_some());
+    }
+}
@@ -1,17 +1,197 @@
-use super::traits::RuntimeAdapter;
+use super::env::{EnvLayer, merge_env};
+use super::exec::{CommandResult, run_command};
+use super::native::{NativeRuntime, expand_workspace_placeholder, shell_quote};
+use super::traits::{PathStyle, RuntimeAdapter};
 use crate::config::DockerRuntimeConfig;
 use anyhow::{Context, Result};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Where the workspace is mounted inside the container.
+const CONTAINER_WORKSPACE: &str = "/workspace";
 
 /// Docker runtime with lightweight container isolation.
 #[derive(Debug, Clone)]
 pub struct DockerRuntime {
     config: DockerRuntimeConfig,
+    /// Extra bind mounts added with [`DockerRuntime::with_mount`].
+    mounts: Vec<BindMount>,
+    /// Network mode overriding `config.network`.
+    network: Option<DockerNetwork>,
+    /// Container user (`uid:gid`) overriding the host user.
+    user: Option<String>,
+    /// Variables set in every container with `--env`.
+    env: Vec<(String, String)>,
+}
+
+/// Container network mode, passed to `docker run --network`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DockerNetwork {
+    /// No networking at all; the container is fully isolated.
+    None,
+    /// Share the host's network stack.
+    Host,
+    /// Docker's default bridge network.
+    Bridge,
+    /// A user-defined network by name.
+    Custom(String),
+}
+
+impl DockerNetwork {
+    /// The value passed to `--network`.
+    pub fn as_arg(&self) -> &str {
+        match self {
+            Self::None => "none",
+            Self::Host => "host",
+            Self::Bridge => "bridge",
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+/// A host directory bind-mounted into the container.
+#[derive(Debug, Clone)]
+struct BindMount {
+    host: PathBuf,
+    container: PathBuf,
+    read_only: bool,
+}
+
+impl BindMount {
+    /// Render as a `--volume` argument, e.g. `/host:/container:ro`.
+    fn volume_arg(&self) -> String {
+        let mode = if self.read_only { "ro" } else { "rw" };
+        format!(
+            "{}:{}:{mode}",
+            self.host.display(),
+            self.container.display()
+        )
+    }
 }
 
 impl DockerRuntime {
     pub fn new(config: DockerRuntimeConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            mounts: Vec::new(),
+            network: None,
+            user: None,
+            env: Vec::new(),
+        }
+    }
+
+    /// Run commands as `user` (`uid:gid`, e.g. `"1000:1000"`) inside the container.
+    ///
+    /// Defaults to the invoking user's uid/gid on Unix so files created in the
+    /// bind-mounted workspace are not owned by root.
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Set `key=value` in every container, e.g. `CI=true` or `TERM=xterm`.
+    /// A later call for the same key replaces the earlier value.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        self.env.retain(|(existing, _)| *existing != key);
+        self.env.push((key, value.into()));
+        self
+    }
+
+    /// The effective `--user` value, if any.
+    fn user_arg(&self) -> Option<String> {
+        self.user.clone().or_else(host_user)
+    }
+
+    /// Mount a writable tmpfs at `/tmp`, overriding `runtime.docker.writable_tmp`.
+    pub fn with_writable_tmp(mut self, enabled: bool) -> Self {
+        self.config.writable_tmp = enabled;
+        self
+    }
+
+    /// Run containers on `mode`, overriding `runtime.docker.network`.
+    pub fn with_network(mut self, mode: DockerNetwork) -> Self {
+        self.network = Some(mode);
+        self
+    }
+
+    /// The effective `--network` value; empty means Docker's default.
+    fn network_arg(&self) -> &str {
+        self.network
+            .as_ref()
+            .map_or_else(|| self.config.network.trim(), DockerNetwork::as_arg)
+    }
+
+    /// Bind-mount `host` at `container` in addition to the workspace, e.g. a
+    /// shared read-only `~/.cargo` cache. Mounts accumulate across calls.
+    ///
+    /// Both paths must be absolute; this is checked when a command is built.
+    pub fn with_mount(
+        mut self,
+        host: impl Into<PathBuf>,
+        container: impl Into<PathBuf>,
+        read_only: bool,
+    ) -> Self {
+        self.mounts.push(BindMount {
+            host: host.into(),
+            container: container.into(),
+            read_only,
+        });
+        self
+    }
+
+    /// Pull the configured image if it is not already present locally, so the
+    /// first command does not pay for the download.
+    ///
+    /// Idempotent: once the image exists this only runs `docker image
+    /// inspect`. Returns whether a pull happened.
+    pub async fn prewarm(&self) -> Result<bool> {
+        let host = NativeRuntime::new();
+        let cwd = std::env::temp_dir();
+        self.prewarm_with(|command| {
+            let (host, cwd) = (&host, &cwd);
+            async move { run_command(host, &command, cwd).await }
+        })
+        .await
+    }
+
+    /// [`prewarm`](Self::prewarm) with `docker` invocations sent to `run`, a
+    /// host shell command runner.
+    pub async fn prewarm_with<F, Fut>(&self, mut run: F) -> Result<bool>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = CommandResult>,
+    {
+        let image = self.config.image.trim();
+        if image.is_empty() {
+            anyhow::bail!("runtime.docker.image is empty; nothing to prewarm");
+        }
+        let quoted = shell_quote(image);
+
+        let inspect = run(format!(
+            "docker image inspect --format '{{{{.Id}}}}' {quoted}"
+        ))
+        .await;
+        if inspect.success() {
+            tracing::debug!(image, "docker image already present; skipping pull");
+            return Ok(false);
+        }
+
+        tracing::info!(image, "pulling docker image");
+        let started = Instant::now();
+        let pull = run(format!("docker pull {quoted}")).await;
+        if !pull.success() {
+            anyhow::bail!(
+                "docker pull {image} failed: {}",
+                pull.stderr.trim().lines().last().unwrap_or("no output")
+            );
+        }
+        for line in pull.stdout.lines().filter(|line| !line.trim().is_empty()) {
+            tracing::debug!(image, "{line}");
+        }
+        tracing::info!(image, elapsed = ?started.elapsed(), "pulled docker image");
+        Ok(true)
     }
 
     fn workspace_mount_path(&self, workspace_dir: &Path) -> Result<PathBuf> {
@@ -52,6 +232,20 @@ impl DockerRuntime {
     }
 }
 
+/// The current process's `uid:gid`.
+#[cfg(unix)]
+fn host_user() -> Option<String> {
+    // SAFETY: `getuid()` and `getgid()` take no arguments, cannot fail, and
+    // only return the calling process's real user and group IDs.
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    Some(format!("{uid}:{gid}"))
+}
+
+#[cfg(not(unix))]
+fn host_user() -> Option<String> {
+    None
+}
+
 impl RuntimeAdapter for DockerRuntime {
     fn name(&self) -> &str {
         "docker"
@@ -77,12 +271,80 @@ impl RuntimeAdapter for DockerRuntime {
         false
     }
 
+    fn effective_environment(&self, _workspace_dir: &Path) -> Vec<(String, String)> {
+        // `docker run` forwards no host variables; commands only see the
+        // image's own environment, which is not known until it runs, plus
+        // the variables passed with `--env`.
+        super::env::masked_environment(self.default_env_additions())
+    }
+
+    fn default_env_additions(&self) -> Vec<(String, String)> {
+        self.env.clone()
+    }
+
+    fn has_network_access(&self) -> bool {
+        self.network_arg() != "none"
+    }
+
+    fn has_writable_tmp(&self) -> bool {
+        self.config.writable_tmp || !self.config.read_only_rootfs
+    }
+
+    fn health_probe(&self) -> Option<tokio::process::Command> {
+        // `docker version` contacts the daemon, so it fails (or hangs) when
+        // the daemon is down.
+        let mut probe = tokio::process::Command::new("docker");
+        probe
+            .arg("version")
+            .arg("--format")
+            .arg("{{.Server.Version}}");
+        Some(probe)
+    }
+
+    fn translate_path(&self, host_path: &Path, workspace_dir: &Path) -> PathBuf {
+        let workspace = self
+            .config
+            .mount_workspace
+            .then(|| (workspace_dir, Path::new(CONTAINER_WORKSPACE)));
+        let extra = self
+            .mounts
+            .iter()
+            .map(|mount| (mount.host.as_path(), mount.container.as_path()));
+        workspace
+            .into_iter()
+            .chain(extra)
+            .find_map(|(host, container)| {
+                let relative = host_path.strip_prefix(host).ok()?;
+                Some(if relative.as_os_str().is_empty() {
+                    container.to_path_buf()
+                } else {
+                    container.join(relative)
+                })
+            })
+            .unwrap_or_else(|| host_path.to_path_buf())
+    }
+
     fn memory_budget(&self) -> u64 {
         self.config
             .memory_limit_mb
             .map_or(0, |mb| mb.saturating_mul(1024 * 1024))
     }
 
+    fn storage_available_bytes(&self) -> Option<u64> {
+        // `storage_path` names a path inside the container, not on the host.
+        None
+    }
+
+    fn path_style(&self) -> PathStyle {
+        // Containers are Linux whatever the host is.
+        PathStyle::POSIX
+    }
+
+    fn shell_program(&self) -> Option<String> {
+        // Commands run via `sh -c` inside the container image.
+        Some("sh".to_string())
+    }
+
     fn build_shell_command(
         &self,
         command: &str,
@@ -95,7 +357,7 @@ impl RuntimeAdapter for DockerRuntime {
             .arg("--init")
             .arg("--interactive");
 
-        let network = self.config.network.trim();
+        let network = self.network_arg();
         if !network.is_empty() {
             process.arg("--network").arg(network);
         }
@@ -112,6 +374,23 @@ impl RuntimeAdapter for DockerRuntime {
             process.arg("--read-only");
         }
 
+        if self.config.writable_tmp {
+            // Docker's default tmpfs options include `noexec`, which breaks
+            // tools that build and run helpers from `/tmp`.
+            process.arg("--tmpfs").arg("/tmp:rw,exec,nosuid,nodev");
+        }
+
+        if let Some(user) = self.user_arg() {
+            process.arg("--user").arg(user);
+        }
+
+        for (key, value) in merge_env(&[EnvLayer::set(self.default_env_additions())]) {
+            let mut assignment = key;
+            assignment.push("=");
+            assignment.push(value);
+            process.arg("--env").arg(assignment);
+        }
+
         if self.config.mount_workspace {
             let host_workspace = self.workspace_mount_path(workspace_dir).with_context(|| {
                 format!(
@@ -122,16 +401,33 @@ impl RuntimeAdapter for DockerRuntime {
 
             process
                 .arg("--volume")
-                .arg(format!("{}:/workspace:rw", host_workspace.display()))
+                .arg(format!(
+                    "{}:{CONTAINER_WORKSPACE}:rw",
+                    host_workspace.display()
+                ))
                 .arg("--workdir")
-                .arg("/workspace");
+                .arg(CONTAINER_WORKSPACE);
         }
 
+        for mount in &self.mounts {
+            if !mount.host.is_absolute() || !mount.container.has_root() {
+                anyhow::bail!(
+                    "Docker bind mounts require absolute paths, got: {} -> {}",
+                    mount.host.display(),
+                    mount.container.display()
+                );
+            }
+            process.arg("--volume").arg(mount.volume_arg());
+        }
+
+        // Placeholders must name the workspace as the container sees it.
+        let workspace = self.translate_path(workspace_dir, workspace_dir);
+        let command = expand_workspace_placeholder(command, &workspace, Path::new("sh"));
         process
             .arg(self.config.image.trim())
             .arg("sh")
             .arg("-c")
-            .arg(command);
+            .arg(&*command);
 
         Ok(process)
     }
@@ -140,6 +436,7 @@ impl RuntimeAdapter for DockerRuntime {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runtime::{Capability, LoggingRuntime, MockRuntime, require_capability};
 
     #[test]
     fn docker_runtime_name() {
@@ -147,6 +444,99 @@ mod tests {
         assert_eq!(runtime.name(), "docker");
     }
 
+    #[test]
+    fn docker_runtime_shell_program_is_container_sh() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        assert_eq!(runtime.shell_program().as_deref(), Some("sh"));
+    }
+
+    #[test]
+    fn docker_translates_workspace_paths_to_container_mount() {
+        let cfg = DockerRuntimeConfig {
+            mount_workspace: true,
+            ..DockerRuntimeConfig::default()
+        };
+        let runtime = DockerRuntime::new(cfg).with_mount("/opt/cache", "/cache", true);
+        let workspace = Path::new("/srv/agent");
+
+        assert_eq!(
+            runtime.translate_path(Path::new("/srv/agent/src/main.rs"), workspace),
+            Path::new("/workspace/src/main.rs")
+        );
+        assert_eq!(
+            runtime.translate_path(workspace, workspace),
+            Path::new("/workspace")
+        );
+        assert_eq!(
+            runtime.translate_path(Path::new("/opt/cache/pkg"), workspace),
+            Path::new("/cache/pkg")
+        );
+        assert_eq!(
+            runtime.translate_path(Path::new("/etc/hosts"), workspace),
+            Path::new("/etc/hosts")
+        );
+    }
+
+    #[test]
+    fn docker_expands_workspace_placeholder_to_container_path() {
+        let cfg = DockerRuntimeConfig {
+            mount_workspace: true,
+            allowed_workspace_roots: Vec::new(),
+            ..DockerRuntimeConfig::default()
+        };
+        let workspace = std::env::temp_dir();
+        let command = DockerRuntime::new(cfg)
+            .build_shell_command("ls {{workspace}}", &workspace)
+            .unwrap();
+        let args = docker_args(&command);
+        assert_eq!(args.last().map(String::as_str), Some("ls '/workspace'"));
+    }
+
+    #[test]
+    fn docker_build_from_argv_quotes_args_for_container_shell() {
+        let cfg = DockerRuntimeConfig {
+            mount_workspace: false,
+            ..DockerRuntimeConfig::default()
+        };
+        let argv = [
+            "grep".to_string(),
+            "two words".to_string(),
+            "it's".to_string(),
+        ];
+        let command = DockerRuntime::new(cfg)
+            .build_from_argv(&argv, &std::env::temp_dir())
+            .unwrap();
+        let args = docker_args(&command);
+        assert_eq!(
+            args[args.len() - 2..],
+            ["-c", r"grep 'two words' 'it'\''s'"]
+        );
+    }
+
+    #[test]
+    fn docker_interpreted_command_runs_in_container_shell() {
+        let cfg = DockerRuntimeConfig {
+            mount_workspace: false,
+            ..DockerRuntimeConfig::default()
+        };
+        let command = DockerRuntime::new(cfg)
+            .build_interpreted_command("python", "-c", "print('hi there')", &std::env::temp_dir())
+            .unwrap();
+        let args = docker_args(&command);
+        assert_eq!(
+            args[args.len() - 2..],
+            ["-c", r"python -c 'print('\''hi there'\'')'"]
+        );
+    }
+
+    #[test]
+    fn docker_health_probe_pings_daemon() {
+        let probe = DockerRuntime::new(DockerRuntimeConfig::default())
+            .health_probe()
+            .unwrap();
+        assert!(crate::runtime::render_command(&probe).starts_with("docker version"));
+    }
+
     #[test]
     fn docker_runtime_memory_budget() {
         let mut cfg = DockerRuntimeConfig::default();
@@ -163,6 +553,7 @@ mod tests {
             memory_limit_mb: Some(128),
             cpu_limit: Some(1.5),
             read_only_rootfs: true,
+            writable_tmp: false,
             mount_workspace: true,
             allowed_workspace_roots: Vec::new(),
         };
@@ -235,6 +626,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn docker_writable_tmp_mounts_tmpfs_and_reports_capability() {
+        let workspace = std::env::temp_dir();
+        let read_only = DockerRuntime::new(DockerRuntimeConfig::default());
+        assert!(!read_only.has_writable_tmp());
+        let command = read_only.build_shell_command("true", &workspace).unwrap();
+        assert!(!docker_args(&command).iter().any(|arg| arg == "--tmpfs"));
+        let err = require_capability(&read_only, Capability::WritableTmp).unwrap_err();
+        assert!(err.to_string().contains("writable_tmp"), "{err}");
+
+        let scratch = DockerRuntime::new(DockerRuntimeConfig::default()).with_writable_tmp(true);
+        assert!(scratch.has_writable_tmp());
+        let command = scratch.build_shell_command("true", &workspace).unwrap();
+        assert!(docker_args(&command).contains(&"/tmp:rw,exec,nosuid,nodev".to_string()));
+
+        let writable_root = DockerRuntime::new(DockerRuntimeConfig {
+            read_only_rootfs: false,
+            ..DockerRuntimeConfig::default()
+        });
+        assert!(writable_root.has_writable_tmp());
+    }
+
     #[cfg(unix)]
     #[test]
     fn docker_refuses_root_mount() {
@@ -272,4 +685,181 @@ mod tests {
             "should not include --memory when not configured"
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn docker_extra_mounts_are_appended_with_modes() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default())
+            .with_mount("/home/user/.cargo", "/root/.cargo", true)
+            .with_mount("/srv/data", "/data", false);
+        let cmd = runtime
+            .build_shell_command("echo hello", &std::env::temp_dir())
+            .unwrap();
+        let args = docker_args(&cmd);
+
+        assert!(args.contains(&"/home/user/.cargo:/root/.cargo:ro".to_string()));
+        assert!(args.contains(&"/srv/data:/data:rw".to_string()));
+    }
+
+    #[test]
+    fn docker_reports_posix_path_style() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        assert_eq!(runtime.path_style(), PathStyle::POSIX);
+        assert_eq!(runtime.path_style().separator, '/');
+        assert!(runtime.path_style().case_sensitive);
+        assert_eq!(LoggingRuntime::new(runtime).path_style(), PathStyle::POSIX);
+    }
+
+    #[test]
+    fn docker_env_additions_are_passed_to_the_container() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default())
+            .with_env("CI", "true")
+            .with_env("TERM", "dumb")
+            .with_env("TERM", "xterm");
+        let cmd = runtime
+            .build_shell_command("echo hello", &std::env::temp_dir())
+            .unwrap();
+        let args = docker_args(&cmd);
+        let env_flags: Vec<&str> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "--env")
+            .map(|pair| pair[1].as_str())
+            .collect();
+
+        assert_eq!(env_flags, ["CI=true", "TERM=xterm"]);
+        assert_eq!(
+            LoggingRuntime::new(runtime.clone()).default_env_additions(),
+            runtime.default_env_additions()
+        );
+        assert_eq!(
+            runtime.effective_environment(&std::env::temp_dir()),
+            [
+                ("CI".to_string(), "true".to_string()),
+                ("TERM".to_string(), "xterm".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn docker_extra_mount_rejects_relative_host_path() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default()).with_mount(
+            "relative/cache",
+            "/cache",
+            true,
+        );
+        let result = runtime.build_shell_command("echo hello", &std::env::temp_dir());
+
+        let error = format!("{:#}", result.unwrap_err());
+        assert!(error.contains("absolute"), "unexpected error: {error}");
+    }
+
+    #[test]
+    fn docker_network_modes_set_flag_and_capability() {
+        let cases = [
+            (DockerNetwork::None, "none", false),
+            (DockerNetwork::Host, "host", true),
+            (DockerNetwork::Bridge, "bridge", true),
+            (DockerNetwork::Custom("agents".into()), "agents", true),
+        ];
+        for (mode, flag, networked) in cases {
+            let runtime = DockerRuntime::new(DockerRuntimeConfig::default()).with_network(mode);
+            let cmd = runtime
+                .build_shell_command("echo hello", &std::env::temp_dir())
+                .unwrap();
+            let args = docker_args(&cmd);
+            let position = args.iter().position(|arg| arg == "--network").unwrap();
+
+            assert_eq!(args[position + 1], flag);
+            assert_eq!(runtime.has_network_access(), networked, "mode {flag}");
+        }
+    }
+
+    #[test]
+    fn docker_network_falls_back_to_config() {
+        let cfg = DockerRuntimeConfig {
+            network: "none".into(),
+            ..DockerRuntimeConfig::default()
+        };
+        assert!(!DockerRuntime::new(cfg).has_network_access());
+    }
+
+    /// The arguments `cmd` passes to `docker`, as strings.
+    fn docker_args(cmd: &tokio::process::Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn user_flag(runtime: &DockerRuntime) -> Option<String> {
+        let cmd = runtime
+            .build_shell_command("echo hello", &std::env::temp_dir())
+            .unwrap();
+        let args = docker_args(&cmd);
+        let position = args.iter().position(|arg| arg == "--user")?;
+        args.get(position + 1).cloned()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn docker_user_defaults_to_host_uid_gid() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        // SAFETY: see `host_user`.
+        let expected = unsafe { format!("{}:{}", libc::getuid(), libc::getgid()) };
+        assert_eq!(user_flag(&runtime), Some(expected));
+    }
+
+    #[test]
+    fn docker_explicit_user_is_respected() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default()).with_user("1234:5678");
+        assert_eq!(user_flag(&runtime).as_deref(), Some("1234:5678"));
+    }
+
+    #[tokio::test]
+    async fn prewarm_skips_pull_when_image_present() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        let mock = MockRuntime::new().with_result(MockRuntime::success("sha256:abc\n"));
+
+        let pulled = runtime
+            .prewarm_with(|command| std::future::ready(mock.run(&command)))
+            .await
+            .unwrap();
+
+        assert!(!pulled);
+        assert_eq!(
+            mock.commands(),
+            ["docker image inspect --format '{{.Id}}' alpine:3.20"]
+        );
+    }
+
+    #[tokio::test]
+    async fn prewarm_pulls_when_image_absent() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        let mock = MockRuntime::new()
+            .with_result(MockRuntime::exit(1, "", "Error: No such image\n"))
+            .with_result(MockRuntime::success("Status: Downloaded newer image\n"));
+
+        let pulled = runtime
+            .prewarm_with(|command| std::future::ready(mock.run(&command)))
+            .await
+            .unwrap();
+
+        assert!(pulled);
+        assert_eq!(mock.commands().last().unwrap(), "docker pull alpine:3.20");
+    }
+
+    #[tokio::test]
+    async fn prewarm_reports_failed_pull() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        let mock = MockRuntime::new()
+            .with_result(MockRuntime::exit(1, "", ""))
+            .with_result(MockRuntime::exit(1, "", "manifest unknown\n"));
+
+        let err = runtime
+            .prewarm_with(|command| std::future::ready(mock.run(&command)))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("manifest unknown"));
+    }
 }
@@ -0,0 +1,211 @@
+//! Environment inspection helpers for diagnostics, and layered merging of
+//! the environment commands run with.
+
+use std::ffi::{OsStr, OsString};
+
+/// Placeholder shown in place of secret environment values.
+pub const MASKED_ENV_VALUE: &str = "***MASKED***";
+
+/// `_`-separated name segments that mark an environment variable as a
+/// secret. Whole segments only, so `GIT_AUTHOR_NAME` and `KEYBOARD_LAYOUT`
+/// are not mistaken for `AUTH` and `KEY`.
+const SECRET_KEY_SEGMENTS: &[&str] = &[
+    "KEY",
+    "KEYS",
+    "TOKENS",
+    "SECRETS",
+    "PASS",
+    "PASSPHRASE",
+    "CREDENTIAL",
+    "CREDENTIALS",
+    "CREDS",
+    "AUTH",
+    "AUTHORIZATION",
+    "SESSION",
+    "COOKIE",
+];
+
+/// Segment endings that mark a secret even when run together with a prefix,
+/// as in `PGPASSWORD` or `OPENAI_APIKEY`.
+const SECRET_KEY_SUFFIXES: &[&str] = &["PASSWORD", "PASSWD", "TOKEN", "SECRET", "APIKEY"];
+
+/// Whether `key` names a variable whose value must not be displayed.
+pub fn is_secret_env_key(key: &str) -> bool {
+    key.to_ascii_uppercase().split('_').any(|segment| {
+        SECRET_KEY_SEGMENTS.contains(&segment)
+            || SECRET_KEY_SUFFIXES
+                .iter()
+                .any(|suffix| segment.ends_with(suffix))
+    })
+}
+
+/// Sort `vars` by name and mask secret values for display.
+pub fn masked_environment(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = vars
+        .into_iter()
+        .map(|(key, value)| {
+            if is_secret_env_key(&key) && !value.is_empty() {
+                (key, MASKED_ENV_VALUE.to_string())
+            } else {
+                (key, value)
+            }
+        })
+        .collect();
+    vars.sort();
+    vars
+}
+
+/// One source of variables for [`merge_env`], applied over the layers
+/// before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvLayer {
+    /// Set these variables, replacing earlier values of the same name.
+    Set(Vec<(OsString, OsString)>),
+    /// Keep only these variables from the layers so far.
+    Allow(Vec<OsString>),
+    /// Drop these variables from the layers so far.
+    Deny(Vec<OsString>),
+}
+
+impl EnvLayer {
+    /// [`EnvLayer::Set`] from any string-like pairs.
+    pub fn set<K: Into<OsString>, V: Into<OsString>>(
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        Self::Set(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Whether two variable names refer to the same variable; Windows names
+/// are case-insensitive.
+fn same_name(a: &OsStr, b: &OsStr) -> bool {
+    if cfg!(windows) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Build an environment from `layers`, applied in order so later layers win.
+///
+/// The usual stack is the inherited server environment, then an
+/// allow/deny filter, then the runtime's default additions, then per-call
+/// overrides. A variable set again moves to the position of the layer that
+/// last set it, so the result is deterministic for a given stack.
+pub fn merge_env(layers: &[EnvLayer]) -> Vec<(OsString, OsString)> {
+    let mut vars: Vec<(OsString, OsString)> = Vec::new();
+    for layer in layers {
+        match layer {
+            EnvLayer::Set(set) => {
+                for (key, value) in set {
+                    vars.retain(|(existing, _)| !same_name(existing, key));
+                    vars.push((key.clone(), value.clone()));
+                }
+            }
+            EnvLayer::Allow(keys) => {
+                vars.retain(|(key, _)| keys.iter().any(|allowed| same_name(key, allowed)));
+            }
+            EnvLayer::Deny(keys) => {
+                vars.retain(|(key, _)| !keys.iter().any(|denied| same_name(key, denied)));
+            }
+        }
+    }
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_keys_are_detected() {
+        assert!(is_secret_env_key("OPENAI_API_KEY"));
+        assert!(is_secret_env_key("github_token"));
+        assert!(is_secret_env_key("DB_PASSWORD"));
+        assert!(!is_secret_env_key("PATH"));
+        assert!(!is_secret_env_key("LANG"));
+        assert!(is_secret_env_key("AUTH_TOKEN"));
+        assert!(is_secret_env_key("PGPASSWORD"));
+        assert!(!is_secret_env_key("GIT_AUTHOR_NAME"));
+        assert!(!is_secret_env_key("GIT_AUTHOR_EMAIL"));
+        assert!(!is_secret_env_key("KEYBOARD_LAYOUT"));
+    }
+
+    #[test]
+    fn masked_environment_sorts_and_masks() {
+        let vars = masked_environment([
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("API_TOKEN".to_string(), "hunter2".to_string()),
+            ("EMPTY_SECRET".to_string(), String::new()),
+        ]);
+        assert_eq!(
+            vars,
+            [
+                ("API_TOKEN".to_string(), MASKED_ENV_VALUE.to_string()),
+                ("EMPTY_SECRET".to_string(), String::new()),
+                ("PATH".to_string(), "/usr/bin".to_string()),
+            ]
+        );
+    }
+
+    fn os_pairs(pairs: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+            .collect()
+    }
+
+    #[test]
+    fn env_additions_override_inherited_values() {
+        let vars = merge_env(&[
+            EnvLayer::set([("TERM", "dumb"), ("PATH", "/usr/bin")]),
+            EnvLayer::set([("TERM", "xterm"), ("CI", "true")]),
+        ]);
+        assert_eq!(
+            vars,
+            os_pairs(&[("PATH", "/usr/bin"), ("TERM", "xterm"), ("CI", "true")])
+        );
+    }
+
+    #[test]
+    fn merge_env_later_layers_win() {
+        let layers = [
+            EnvLayer::set([("MODE", "inherited"), ("HOME", "/root"), ("TOKEN", "x")]),
+            EnvLayer::Deny(vec!["TOKEN".into()]),
+            EnvLayer::set([("MODE", "default"), ("CI", "true")]),
+            EnvLayer::set([("MODE", "override")]),
+        ];
+        let value = |vars: &[(OsString, OsString)], name: &str| {
+            vars.iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        let all = merge_env(&layers);
+        assert_eq!(value(&all, "MODE"), Some("override".into()));
+        assert_eq!(value(&all, "TOKEN"), None);
+        assert_eq!(merge_env(&layers[..3]).len(), 3);
+        assert_eq!(
+            value(&merge_env(&layers[..3]), "MODE"),
+            Some("default".into())
+        );
+        assert_eq!(
+            value(&merge_env(&layers[..1]), "MODE"),
+            Some("inherited".into())
+        );
+
+        // A filter only affects the layers before it.
+        let allowed = merge_env(&[
+            EnvLayer::set([("PATH", "/bin"), ("HOME", "/root")]),
+            EnvLayer::Allow(vec!["PATH".into()]),
+            EnvLayer::set([("CI", "true")]),
+        ]);
+        assert_eq!(allowed, os_pairs(&[("PATH", "/bin"), ("CI", "true")]));
+    }
+}
@@ -0,0 +1,1278 @@
+//! Execution helpers that run commands through a [`RuntimeAdapter`].
+//!
+//! These apply the adapter's preprocessors and default timeout so callers do
+//! not have to thread them through by hand.
+
+use super::output::{OutputChunk, OutputStream, decode_output_lossy};
+use super::registry::ProcessRegistry;
+use super::traits::{Capability, RuntimeAdapter, require_capability};
+use crate::util::is_spinner_frame;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::Child;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_util::sync::CancellationToken;
+
+/// Raw, undecoded output of a finished command.
+#[derive(Debug, Clone)]
+pub struct CapturedOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Outcome of a command run through [`run_command`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResult {
+    /// The command as submitted, before preprocessing.
+    pub command: String,
+    /// Exit code, or `None` when the process was killed or never started.
+    ///
+    /// A timed-out command that exits on its graceful signal keeps its code.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Why the command was killed for taking too long, if it was.
+    pub timed_out: Option<TimeoutReason>,
+    pub duration: Duration,
+}
+
+/// Which limit killed a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutReason {
+    /// The adapter's [`default_command_timeout`](RuntimeAdapter::default_command_timeout) elapsed.
+    Overall,
+    /// No output arrived within [`StreamOptions::idle_timeout`].
+    Idle,
+}
+
+impl TimeoutReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Overall => "overall",
+            Self::Idle => "idle",
+        }
+    }
+}
+
+/// Tuning for [`spawn_streaming`].
+#[derive(Debug, Clone, Default)]
+pub struct StreamOptions {
+    /// Kill the command if no output chunk arrives within this window.
+    ///
+    /// Independent of the adapter's overall timeout; a process that keeps
+    /// printing is never considered idle.
+    pub idle_timeout: Option<Duration>,
+    /// Split lines longer than this many bytes into several chunks, flagging
+    /// all but the last as [`continued`](OutputChunk::continued).
+    ///
+    /// Defaults to [`DEFAULT_MAX_LINE_BYTES`] when unset, so minified bundles
+    /// and base64 blobs never buffer unbounded.
+    pub max_line_bytes: Option<usize>,
+    /// Handle for pausing and resuming the command while it runs.
+    pub control: Option<StreamControl>,
+    /// Coalesce consecutive progress lines (`...: 42%`, including `\r`
+    /// redraws) into a single updated chunk to cut dashboard render churn.
+    ///
+    /// The held line is emitted when a different line arrives, when the
+    /// stream ends, or after [`COALESCE_FLUSH_INTERVAL`]. Only chunks passed
+    /// to the callback are affected; the result still has the full output.
+    pub coalesce_progress: bool,
+    /// Kill the command when this token is cancelled, e.g. because the
+    /// client consuming its output disconnected.
+    pub cancel: Option<CancellationToken>,
+    /// Tag each line passed to the callback with its source, for panes that
+    /// merge several commands' output. The result keeps the raw output.
+    pub line_prefix: Option<LinePrefix>,
+    /// Drop lines that are only a spinner frame (`|`, `⠋`, `◐`, ...; see
+    /// [`is_spinner_frame`]), including `\r` redraws, before they reach the
+    /// callback. The result keeps the raw output.
+    pub suppress_spinners: bool,
+}
+
+/// Source tag [`StreamOptions::line_prefix`] puts at the start of each line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinePrefix {
+    /// `[stdout] ` or `[stderr] `, by the pipe the line came from.
+    Stream,
+    /// `[<label>] ` on every line, e.g. the name of the command.
+    Label(String),
+}
+
+impl LinePrefix {
+    fn tag(&self, stream: OutputStream) -> String {
+        match self {
+            Self::Stream => format!("[{}] ", stream.as_str()),
+            Self::Label(label) => format!("[{label}] "),
+        }
+    }
+}
+
+/// Applies a [`LinePrefix`] to chunks on their way to the callback.
+///
+/// Only chunks that start a line are tagged; the rest of a line split for
+/// exceeding the length limit follows its first piece untouched.
+#[derive(Debug, Default)]
+struct LinePrefixer {
+    prefix: Option<LinePrefix>,
+    /// Whether the last stdout / stderr chunk ended mid-line.
+    mid_line: [bool; 2],
+}
+
+impl LinePrefixer {
+    fn emit(&mut self, chunk: &OutputChunk, on_chunk: &mut impl FnMut(&OutputChunk)) {
+        let Some(prefix) = &self.prefix else {
+            return on_chunk(chunk);
+        };
+        let mid_line = &mut self.mid_line[usize::from(chunk.stream == OutputStream::Stderr)];
+        if std::mem::replace(mid_line, chunk.continued) {
+            return on_chunk(chunk);
+        }
+        let mut tagged = chunk.clone();
+        tagged.text.insert_str(0, &prefix.tag(chunk.stream));
+        on_chunk(&tagged);
+    }
+}
+
+/// Longest a coalesced progress line is held before being emitted.
+pub const COALESCE_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Merges runs of progress lines that differ only in a trailing percentage.
+#[derive(Debug, Default)]
+struct ProgressCoalescer {
+    pending: Option<(OutputChunk, tokio::time::Instant)>,
+}
+
+impl ProgressCoalescer {
+    fn push(&mut self, chunk: OutputChunk, emit: &mut impl FnMut(&OutputChunk)) {
+        let key = progress_key(&chunk.text);
+        if let Some((pending, _)) = &mut self.pending {
+            if key.is_some() && pending.stream == chunk.stream && progress_key(&pending.text) == key
+            {
+                *pending = chunk;
+                return;
+            }
+        }
+        self.flush(emit);
+        if key.is_some() {
+            let deadline = tokio::time::Instant::now() + COALESCE_FLUSH_INTERVAL;
+            self.pending = Some((chunk, deadline));
+        } else {
+            emit(&chunk);
+        }
+    }
+
+    fn flush(&mut self, emit: &mut impl FnMut(&OutputChunk)) {
+        if let Some((chunk, _)) = self.pending.take() {
+            emit(&chunk);
+        }
+    }
+
+    fn flush_deadline(&self) -> Option<tokio::time::Instant> {
+        self.pending.as_ref().map(|(_, deadline)| *deadline)
+    }
+}
+
+/// The part of a progress line that stays fixed between updates, i.e. the
+/// text before a trailing percentage; `None` for other lines.
+fn progress_key(text: &str) -> Option<&str> {
+    let line = text.trim_end();
+    if !line.ends_with('%') {
+        return None;
+    }
+    let key = line.trim_end_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | '%' | ' '));
+    (key.len() < line.len()).then_some(key)
+}
+
+/// Cloneable pause/resume handle for a [`spawn_streaming`] command.
+///
+/// While paused, output stops being drained and the idle timeout is
+/// suspended; on Unix the process group is also stopped with `SIGSTOP` and
+/// continued with `SIGCONT`. The overall timeout keeps running.
+#[derive(Debug, Clone)]
+pub struct StreamControl {
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl Default for StreamControl {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl StreamControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+}
+
+/// How long a timed-out or cancelled command gets to exit after its
+/// [`graceful_signal`](RuntimeAdapter::graceful_signal) before `SIGKILL`.
+pub const TERMINATION_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// How long to wait for further output once a terminated command has exited.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Line-length limit used when [`StreamOptions::max_line_bytes`] is unset.
+pub const DEFAULT_MAX_LINE_BYTES: usize = 64 * 1024;
+
+impl CommandResult {
+    fn pending(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            timed_out: None,
+            duration: Duration::ZERO,
+        }
+    }
+
+    /// Whether the command ran to completion with exit code zero.
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Spawn `command` and wait for its output, or `None` if it timed out.
+async fn run_to_completion(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+) -> anyhow::Result<Option<Output>> {
+    require_capability(adapter, Capability::Shell)?;
+    let command = adapter.preprocess_command(command);
+    let mut process = adapter.build_shell_command(&command, workspace_dir)?;
+    process
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    match adapter.default_command_timeout() {
+        Some(timeout) => match tokio::time::timeout(timeout, process.output()).await {
+            Ok(output) => Ok(Some(output?)),
+            Err(_) => Ok(None),
+        },
+        None => Ok(Some(process.output().await?)),
+    }
+}
+
+/// Run `command` to completion and capture stdout/stderr as raw bytes.
+///
+/// Use this instead of decoding eagerly when output may be binary or in a
+/// legacy encoding; pair it with [`decode_output_lossy`] to detect replacement.
+///
+/// # Errors
+///
+/// Fails if the command cannot be built or spawned, or if it exceeds the
+/// adapter's [`default_command_timeout`](RuntimeAdapter::default_command_timeout).
+pub async fn capture_command_bytes(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+) -> anyhow::Result<CapturedOutput> {
+    let output = run_to_completion(adapter, command, workspace_dir)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Command timed out after {:?}",
+                adapter.default_command_timeout().unwrap_or_default()
+            )
+        })?;
+
+    Ok(CapturedOutput {
+        status: output.status,
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// Run `command` to completion, folding every failure into the result.
+///
+/// Build and spawn errors are reported through `stderr` with no exit code,
+/// so batch callers always get one result per command. Results go through
+/// the adapter's [`replayed_result`](RuntimeAdapter::replayed_result) and
+/// [`observe_result`](RuntimeAdapter::observe_result) hooks.
+pub async fn run_command(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+) -> CommandResult {
+    if let Some(result) = adapter.replayed_result(command) {
+        adapter.observe_result(&result);
+        return result;
+    }
+    let started = Instant::now();
+    let outcome = run_to_completion(adapter, command, workspace_dir).await;
+    let mut result = CommandResult::pending(command);
+    match outcome {
+        Ok(Some(output)) => {
+            result.exit_code = output.status.code();
+            result.stdout = decode_output_lossy(&output.stdout).0;
+            result.stderr = decode_output_lossy(&output.stderr).0;
+        }
+        Ok(None) => result.timed_out = Some(TimeoutReason::Overall),
+        Err(err) => result.stderr = err.to_string(),
+    }
+    result.duration = started.elapsed();
+    adapter.observe_result(&result);
+    result
+}
+
+/// Run `command` with stdout and stderr both redirected into `out_path`.
+///
+/// Suited to huge build logs that should not be held in memory. Parent
+/// directories are created and an existing file is truncated. The returned
+/// result carries no output; read the file instead. A replayed result's
+/// output is written to the file in place of running the command.
+pub async fn run_to_file(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+    out_path: &Path,
+) -> CommandResult {
+    if let Some(mut result) = adapter.replayed_result(command) {
+        adapter.observe_result(&result);
+        let output = std::mem::take(&mut result.stdout) + &std::mem::take(&mut result.stderr);
+        let written = match create_log(out_path).await {
+            Ok(mut file) => file.write_all(output.as_bytes()).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = written {
+            result.stderr = format!("failed to write {}: {err}", out_path.display());
+        }
+        return result;
+    }
+    let started = Instant::now();
+    let mut result = CommandResult::pending(command);
+    match run_redirected(adapter, command, workspace_dir, out_path).await {
+        Ok(Some(status)) => result.exit_code = status.code(),
+        Ok(None) => result.timed_out = Some(TimeoutReason::Overall),
+        Err(err) => result.stderr = err.to_string(),
+    }
+    result.duration = started.elapsed();
+    adapter.observe_result(&result);
+    result
+}
+
+/// Spawn `command` writing into `out_path`; `None` if it timed out.
+async fn run_redirected(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+    out_path: &Path,
+) -> anyhow::Result<Option<ExitStatus>> {
+    if let Some(parent) = out_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let stdout = tokio::fs::File::create(out_path).await?.into_std().await;
+    let stderr = stdout.try_clone()?;
+
+    require_capability(adapter, Capability::Shell)?;
+    let command = adapter.preprocess_command(command);
+    let mut process = adapter.build_shell_command(&command, workspace_dir)?;
+    process
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(stdout))
+        .stderr(Stdio::from(stderr))
+        .kill_on_drop(true);
+    let mut child = process.spawn()?;
+
+    match adapter.default_command_timeout() {
+        Some(timeout) => match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => Ok(Some(status?)),
+            Err(_) => {
+                let _ = child.kill().await;
+                Ok(None)
+            }
+        },
+        None => Ok(Some(child.wait().await?)),
+    }
+}
+
+/// Run `command`, handing each line of output to `on_chunk` as it arrives.
+///
+/// Output is also accumulated into the returned result. The process group is
+/// terminated when the adapter's overall timeout or `options.idle_timeout`
+/// elapses, or `options.cancel` fires: it first gets the adapter's
+/// [`graceful_signal`](RuntimeAdapter::graceful_signal), then `SIGKILL` after
+/// [`TERMINATION_GRACE_PERIOD`]. [`CommandResult::timed_out`] records which
+/// timeout fired. The command
+/// is listed in [`ProcessRegistry::global`] while it runs and is killed if an
+/// operator asks for it. A replayed result is handed to `on_chunk` a line at
+/// a time, stdout first, without running anything.
+pub async fn spawn_streaming(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+    options: &StreamOptions,
+    mut on_chunk: impl FnMut(&OutputChunk),
+) -> CommandResult {
+    let mut prefixer = LinePrefixer {
+        prefix: options.line_prefix.clone(),
+        ..LinePrefixer::default()
+    };
+    let mut on_chunk = |chunk: &OutputChunk| {
+        if !(options.suppress_spinners && is_spinner_frame(&chunk.text)) {
+            prefixer.emit(chunk, &mut on_chunk);
+        }
+    };
+    if let Some(result) = adapter.replayed_result(command) {
+        for (stream, text) in [
+            (OutputStream::Stdout, &result.stdout),
+            (OutputStream::Stderr, &result.stderr),
+        ] {
+            for line in text.split_inclusive('\n') {
+                on_chunk(&OutputChunk {
+                    stream,
+                    text: line.to_string(),
+                    continued: false,
+                });
+            }
+        }
+        adapter.observe_result(&result);
+        return result;
+    }
+    let started = Instant::now();
+    let mut result = CommandResult::pending(command);
+    let mut child = match spawn_piped(adapter, command, workspace_dir) {
+        Ok(child) => child,
+        Err(err) => {
+            result.stderr = err.to_string();
+            adapter.observe_result(&result);
+            return result;
+        }
+    };
+    let registration = ProcessRegistry::global().register(command, adapter.name(), child.id());
+
+    let splitter = LineSplitter {
+        max_line_bytes: options
+            .max_line_bytes
+            .unwrap_or(DEFAULT_MAX_LINE_BYTES)
+            .max(1),
+        // Progress and spinner redraws only become separate lines if `\r`
+        // ends a line.
+        split_on_cr: options.coalesce_progress || options.suppress_spinners,
+    };
+    let (tx, mut rx) = mpsc::channel(64);
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(forward_lines(
+            stdout,
+            OutputStream::Stdout,
+            splitter,
+            tx.clone(),
+        ));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(forward_lines(stderr, OutputStream::Stderr, splitter, tx));
+    }
+    let mut coalescer = options.coalesce_progress.then(ProgressCoalescer::default);
+
+    let deadline = adapter
+        .default_command_timeout()
+        .map(|timeout| tokio::time::Instant::now() + timeout);
+    let mut pause_rx = options
+        .control
+        .as_ref()
+        .map(|control| control.paused.subscribe());
+    // One idle window, restarted only by output; time spent paused is added
+    // back on resume so a pause never counts as silence.
+    let idle = tokio::time::sleep(options.idle_timeout.unwrap_or(Duration::MAX));
+    tokio::pin!(idle);
+    let mut paused_at: Option<tokio::time::Instant> = None;
+    let mut stopped = false;
+    let mut cancelled = false;
+    let mut output_closed = false;
+    let mut exited = false;
+    loop {
+        let paused = pause_rx.as_ref().is_some_and(|rx| *rx.borrow());
+        if paused != stopped {
+            signal_process_group(&child, paused);
+            stopped = paused;
+            match paused_at.take() {
+                Some(since) => {
+                    let deadline = idle.deadline() + since.elapsed();
+                    idle.as_mut().reset(deadline);
+                }
+                None => paused_at = Some(tokio::time::Instant::now()),
+            }
+        }
+        tokio::select! {
+            () = pause_changed(&mut pause_rx) => {}
+            () = registration.killed() => {
+                tracing::info!(id = registration.id(), command, "command killed by operator");
+                kill_process_group(&mut child).await;
+                break;
+            }
+            () = cancelled_opt(options.cancel.as_ref()) => {
+                tracing::debug!(id = registration.id(), command, "command cancelled");
+                cancelled = true;
+                break;
+            }
+            chunk = rx.recv(), if !paused && !output_closed => {
+                let Some(chunk) = chunk else {
+                    // Pipes closed; the command may still be running.
+                    output_closed = true;
+                    continue;
+                };
+                if let Some(timeout) = options.idle_timeout {
+                    idle.as_mut().reset(tokio::time::Instant::now() + timeout);
+                }
+                match chunk.stream {
+                    OutputStream::Stdout => result.stdout.push_str(&chunk.text),
+                    OutputStream::Stderr => result.stderr.push_str(&chunk.text),
+                }
+                match &mut coalescer {
+                    Some(coalescer) => coalescer.push(chunk, &mut on_chunk),
+                    None => on_chunk(&chunk),
+                }
+            }
+            () = sleep_until_opt(coalescer.as_ref().and_then(ProgressCoalescer::flush_deadline)) => {
+                if let Some(coalescer) = &mut coalescer {
+                    coalescer.flush(&mut on_chunk);
+                }
+            }
+            () = &mut idle, if options.idle_timeout.is_some() && !paused => {
+                result.timed_out = Some(TimeoutReason::Idle);
+                break;
+            }
+            () = sleep_until_opt(deadline) => {
+                result.timed_out = Some(TimeoutReason::Overall);
+                break;
+            }
+            status = child.wait(), if output_closed => {
+                result.exit_code = status.ok().and_then(|status| status.code());
+                exited = true;
+                break;
+            }
+        }
+    }
+
+    if result.timed_out.is_some() || cancelled {
+        if stopped {
+            signal_process_group(&child, false);
+        }
+        let status = terminate_process_group(&mut child, adapter.graceful_signal()).await;
+        result.exit_code = status.and_then(|status| status.code());
+        // Keep whatever a cleanly exiting command printed while shutting down.
+        if status.is_some() {
+            while let Ok(Some(chunk)) = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, rx.recv()).await
+            {
+                match chunk.stream {
+                    OutputStream::Stdout => result.stdout.push_str(&chunk.text),
+                    OutputStream::Stderr => result.stderr.push_str(&chunk.text),
+                }
+                match &mut coalescer {
+                    Some(coalescer) => coalescer.push(chunk, &mut on_chunk),
+                    None => on_chunk(&chunk),
+                }
+            }
+        }
+    } else if !exited {
+        result.exit_code = child.wait().await.ok().and_then(|status| status.code());
+    }
+    if let Some(coalescer) = &mut coalescer {
+        coalescer.flush(&mut on_chunk);
+    }
+    result.duration = started.elapsed();
+    adapter.observe_result(&result);
+    result
+}
+
+/// [`spawn_streaming`] on its own task, sending each chunk to the returned
+/// stream and appending its text to `log_path` as well.
+///
+/// The log holds exactly what the stream yields, line prefixes included. It
+/// is created (with parent directories) or truncated before the command
+/// starts; if that fails the command is not run and the result's `stderr`
+/// says why. Dropping the stream does not stop the command or the log: cancel
+/// `options.cancel` for that. The handle resolves once the log is flushed.
+pub fn spawn_streaming_tee(
+    adapter: Arc<dyn RuntimeAdapter>,
+    command: String,
+    workspace_dir: PathBuf,
+    log_path: PathBuf,
+    options: StreamOptions,
+) -> (
+    UnboundedReceiverStream<OutputChunk>,
+    JoinHandle<CommandResult>,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let handle = tokio::spawn(async move {
+        let log = match create_log(&log_path).await {
+            Ok(log) => log,
+            Err(err) => {
+                let mut result = CommandResult::pending(&command);
+                result.stderr = format!("failed to create {}: {err}", log_path.display());
+                return result;
+            }
+        };
+        let (log_tx, mut log_rx) = mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn(async move {
+            let mut log = tokio::io::BufWriter::new(log);
+            while let Some(text) = log_rx.recv().await {
+                log.write_all(text.as_bytes()).await?;
+            }
+            log.flush().await
+        });
+
+        let result = spawn_streaming(
+            adapter.as_ref(),
+            &command,
+            &workspace_dir,
+            &options,
+            |chunk| {
+                let _ = log_tx.send(chunk.text.clone());
+                let _ = tx.send(chunk.clone());
+            },
+        )
+        .await;
+        drop(log_tx);
+        match writer.await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => tracing::warn!("Failed to write {}: {err}", log_path.display()),
+            Err(err) => tracing::warn!("Log writer for {} failed: {err}", log_path.display()),
+        }
+        result
+    });
+    (UnboundedReceiverStream::new(rx), handle)
+}
+
+/// Create or truncate `path`, creating its parent directories first.
+async fn create_log(path: &Path) -> std::io::Result<tokio::fs::File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::File::create(path).await
+}
+
+/// Build and spawn `command` with piped output in its own process group.
+fn spawn_piped(
+    adapter: &dyn RuntimeAdapter,
+    command: &str,
+    workspace_dir: &Path,
+) -> anyhow::Result<Child> {
+    require_capability(adapter, Capability::Shell)?;
+    let command = adapter.preprocess_command(command);
+    let mut process = adapter.build_shell_command(&command, workspace_dir)?;
+    process
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    process.process_group(0);
+    Ok(process.spawn()?)
+}
+
+/// How [`forward_lines`] breaks output into chunks.
+#[derive(Debug, Clone, Copy)]
+struct LineSplitter {
+    max_line_bytes: usize,
+    /// Treat `\r` as a line terminator as well as `\n`.
+    split_on_cr: bool,
+}
+
+impl LineSplitter {
+    fn ends_line(self, byte: u8) -> bool {
+        byte == b'\n' || (self.split_on_cr && byte == b'\r')
+    }
+}
+
+/// Send each line read from `reader` as a chunk until EOF or the receiver closes.
+///
+/// Lines longer than `max_line_bytes` are sent in pieces so memory use stays
+/// bounded however long a line gets.
+async fn forward_lines(
+    reader: impl AsyncRead + Unpin,
+    stream: OutputStream,
+    splitter: LineSplitter,
+    tx: mpsc::Sender<OutputChunk>,
+) {
+    let max_line_bytes = splitter.max_line_bytes;
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    while let Ok(buffered) = reader.fill_buf().await {
+        if buffered.is_empty() {
+            if !line.is_empty() {
+                let _ = tx.send(line_chunk(stream, &line, false)).await;
+            }
+            break;
+        }
+
+        let room = max_line_bytes - line.len();
+        let (take, line_ended) = match buffered
+            .iter()
+            .take(room)
+            .position(|b| splitter.ends_line(*b))
+        {
+            Some(newline) => (newline + 1, true),
+            None => (buffered.len().min(room), false),
+        };
+        line.extend_from_slice(&buffered[..take]);
+        reader.consume(take);
+
+        if line_ended || line.len() >= max_line_bytes {
+            let split = split_at_char_boundary(&mut line, line_ended);
+            if tx
+                .send(line_chunk(stream, &line, !line_ended))
+                .await
+                .is_err()
+            {
+                break;
+            }
+            line = split;
+        }
+    }
+}
+
+/// Detach a trailing incomplete UTF-8 sequence from a full `line` so a
+/// multi-byte character is not torn across two chunks.
+fn split_at_char_boundary(line: &mut Vec<u8>, line_ended: bool) -> Vec<u8> {
+    if line_ended {
+        return Vec::new();
+    }
+    let valid_up_to = match std::str::from_utf8(line) {
+        Err(err) if err.error_len().is_none() && err.valid_up_to() > 0 => err.valid_up_to(),
+        Ok(_) | Err(_) => return Vec::new(),
+    };
+    line.split_off(valid_up_to)
+}
+
+fn line_chunk(stream: OutputStream, line: &[u8], continued: bool) -> OutputChunk {
+    OutputChunk {
+        stream,
+        text: decode_output_lossy(line).0,
+        continued,
+    }
+}
+
+/// Resolve when the pause state changes; never if there is no control.
+async fn pause_changed(rx: &mut Option<watch::Receiver<bool>>) {
+    let Some(rx) = rx else {
+        return std::future::pending().await;
+    };
+    if rx.changed().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Stop (`SIGSTOP`) or continue (`SIGCONT`) the child's process group.
+fn signal_process_group(child: &Child, stop: bool) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id().and_then(|pid| i32::try_from(pid).ok()) {
+        let signal = if stop { libc::SIGSTOP } else { libc::SIGCONT };
+        // SAFETY: `kill` has no memory-safety preconditions; a negative pid
+        // targets the group the child was spawned into (`process_group(0)`).
+        unsafe {
+            libc::kill(-pid, signal);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (child, stop);
+}
+
+async fn cancelled_opt(token: Option<&CancellationToken>) {
+    match token {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn sleep_until_opt(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Ask `child`'s process group to stop with `signal`, then
+/// [`kill_process_group`] if it is still running after
+/// [`TERMINATION_GRACE_PERIOD`].
+///
+/// Returns the exit status when the command exited within the grace period.
+pub(crate) async fn terminate_process_group(
+    child: &mut Child,
+    signal: Option<i32>,
+) -> Option<ExitStatus> {
+    #[cfg(unix)]
+    if let (Some(signal), Some(pid)) = (signal, child.id().and_then(|pid| i32::try_from(pid).ok()))
+    {
+        // SAFETY: `kill` has no memory-safety preconditions; a negative pid
+        // targets the group the child was spawned into (`process_group(0)`).
+        unsafe {
+            libc::kill(-pid, signal);
+        }
+        if let Ok(Ok(status)) = tokio::time::timeout(TERMINATION_GRACE_PERIOD, child.wait()).await {
+            return Some(status);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = signal;
+    kill_process_group(child).await;
+    None
+}
+
+/// Kill `child` and, on Unix, every process in its group.
+pub(crate) async fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id().and_then(|pid| i32::try_from(pid).ok()) {
+        // SAFETY: `kill` has no memory-safety preconditions; a negative pid
+        // targets the group the child was spawned into (`process_group(0)`).
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{NativeRuntime, TimeoutRuntime};
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn capture_preserves_invalid_utf8_bytes() {
+        let output = capture_command_bytes(
+            &NativeRuntime::new(),
+            r"printf 'ok \377\376 end' | cat",
+            &std::env::temp_dir(),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"ok \xff\xfe end");
+
+        let (text, replaced) = decode_output_lossy(&output.stdout);
+        assert!(replaced);
+        assert!(text.starts_with("ok "));
+        assert!(text.ends_with(" end"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_command_reports_exit_code_and_output() {
+        let result = run_command(
+            &NativeRuntime::new(),
+            "echo out; echo err >&2; exit 3",
+            &std::env::temp_dir(),
+        )
+        .await;
+
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stdout, "out\n");
+        assert_eq!(result.stderr, "err\n");
+        assert_eq!(result.timed_out, None);
+        assert!(!result.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_command_marks_timeouts() {
+        let runtime = TimeoutRuntime::new(NativeRuntime::new(), Duration::from_millis(100));
+        let result = run_command(&runtime, "sleep 5", &std::env::temp_dir()).await;
+
+        assert_eq!(result.timed_out, Some(TimeoutReason::Overall));
+        assert_eq!(result.exit_code, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_kills_silent_process_after_idle_window() {
+        let options = StreamOptions {
+            idle_timeout: Some(Duration::from_millis(300)),
+            ..StreamOptions::default()
+        };
+        let result = spawn_streaming(
+            &NativeRuntime::new(),
+            "echo start; sleep 5",
+            &std::env::temp_dir(),
+            &options,
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(result.timed_out, Some(TimeoutReason::Idle));
+        assert_eq!(result.stdout, "start\n");
+        assert!(result.duration < Duration::from_secs(3));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_sends_configured_graceful_signal_on_timeout() {
+        let runtime = TimeoutRuntime::new(
+            NativeRuntime::new().with_graceful_signal(libc::SIGINT),
+            Duration::from_millis(300),
+        );
+        assert_eq!(runtime.graceful_signal(), Some(libc::SIGINT));
+
+        let result = spawn_streaming(
+            &runtime,
+            "trap 'echo cleaned up; exit 0' INT; while :; do sleep 0.05; done",
+            &std::env::temp_dir(),
+            &StreamOptions::default(),
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(result.timed_out, Some(TimeoutReason::Overall));
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.stdout, "cleaned up\n");
+        assert!(result.duration < TERMINATION_GRACE_PERIOD);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_times_out_command_that_closes_its_output() {
+        let runtime = TimeoutRuntime::new(NativeRuntime::new(), Duration::from_millis(300));
+        let started = Instant::now();
+        let result = spawn_streaming(
+            &runtime,
+            "echo before; exec >/dev/null 2>&1; sleep 30",
+            &std::env::temp_dir(),
+            &StreamOptions::default(),
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(result.timed_out, Some(TimeoutReason::Overall));
+        assert_eq!(result.stdout, "before\n");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_keeps_chatty_process_alive() {
+        let options = StreamOptions {
+            idle_timeout: Some(Duration::from_millis(500)),
+            ..StreamOptions::default()
+        };
+        let mut chunks = Vec::new();
+        let result = spawn_streaming(
+            &NativeRuntime::new(),
+            "for i in 1 2 3 4 5 6; do echo $i; sleep 0.2; done",
+            &std::env::temp_dir(),
+            &options,
+            |chunk| chunks.push(chunk.text.clone()),
+        )
+        .await;
+
+        assert_eq!(result.timed_out, None);
+        assert!(result.success());
+        assert_eq!(chunks, ["1\n", "2\n", "3\n", "4\n", "5\n", "6\n"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_splits_oversized_lines() {
+        const LINE_BYTES: usize = 10 * 1024 * 1024;
+        const MAX_LINE_BYTES: usize = 64 * 1024;
+        let options = StreamOptions {
+            max_line_bytes: Some(MAX_LINE_BYTES),
+            ..StreamOptions::default()
+        };
+        let mut chunks = Vec::new();
+        let result = spawn_streaming(
+            &NativeRuntime::new(),
+            &format!("head -c {LINE_BYTES} /dev/zero | tr '\\0' a; echo; echo tail"),
+            &std::env::temp_dir(),
+            &options,
+            |chunk| chunks.push((chunk.text.len(), chunk.continued)),
+        )
+        .await;
+
+        assert!(result.success());
+        assert_eq!(result.stdout.len(), LINE_BYTES + "\ntail\n".len());
+        assert!(chunks.iter().all(|(len, _)| *len <= MAX_LINE_BYTES));
+        assert_eq!(chunks.len(), LINE_BYTES / MAX_LINE_BYTES + 2);
+        let (last, rest) = chunks.split_last().unwrap();
+        assert_eq!(*last, (5, false));
+        assert!(
+            rest[..rest.len() - 1]
+                .iter()
+                .all(|(_, continued)| *continued)
+        );
+        assert!(!rest[rest.len() - 1].1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_prefixes_lines_without_touching_result() {
+        let stream = |prefix: LinePrefix| async move {
+            let options = StreamOptions {
+                line_prefix: Some(prefix),
+                ..StreamOptions::default()
+            };
+            let mut chunks = Vec::new();
+            let result = spawn_streaming(
+                &NativeRuntime::new(),
+                "echo out; echo err >&2",
+                &std::env::temp_dir(),
+                &options,
+                |chunk| chunks.push(chunk.text.clone()),
+            )
+            .await;
+            chunks.sort();
+            (result, chunks)
+        };
+
+        let (result, chunks) = stream(LinePrefix::Stream).await;
+        assert_eq!(chunks, ["[stderr] err\n", "[stdout] out\n"]);
+        assert_eq!(result.stdout, "out\n");
+        assert_eq!(result.stderr, "err\n");
+
+        let (result, chunks) = stream(LinePrefix::Label("build".into())).await;
+        assert_eq!(chunks, ["[build] err\n", "[build] out\n"]);
+        assert_eq!(result.stdout, "out\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_tee_log_matches_streamed_chunks() {
+        use tokio_stream::StreamExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("logs").join("build.log");
+        let (stream, handle) = spawn_streaming_tee(
+            Arc::new(NativeRuntime::new()),
+            "for i in 1 2 3; do echo line $i; echo warn $i >&2; done".to_string(),
+            std::env::temp_dir(),
+            log_path.clone(),
+            StreamOptions::default(),
+        );
+
+        let streamed: String = stream
+            .map(|chunk| chunk.text)
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        let result = handle.await.unwrap();
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.stdout, "line 1\nline 2\nline 3\n");
+        assert_eq!(streamed.lines().count(), 6);
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), streamed);
+    }
+
+    #[test]
+    fn line_prefixer_tags_only_the_start_of_split_lines() {
+        let mut prefixer = LinePrefixer {
+            prefix: Some(LinePrefix::Stream),
+            ..LinePrefixer::default()
+        };
+        let mut emitted = Vec::new();
+        let mut collect = |chunk: &OutputChunk| emitted.push(chunk.text.clone());
+        let mut head = OutputChunk::stdout("aaaa");
+        head.continued = true;
+        prefixer.emit(&head, &mut collect);
+        prefixer.emit(&OutputChunk::stderr("e\n"), &mut collect);
+        prefixer.emit(&OutputChunk::stdout("aa\n"), &mut collect);
+        prefixer.emit(&OutputChunk::stdout("b\n"), &mut collect);
+        assert_eq!(
+            emitted,
+            ["[stdout] aaaa", "[stderr] e\n", "aa\n", "[stdout] b\n"]
+        );
+    }
+
+    #[test]
+    fn split_at_char_boundary_keeps_multibyte_chars_whole() {
+        let mut line = "ab\u{e9}".as_bytes()[..3].to_vec();
+        let rest = split_at_char_boundary(&mut line, false);
+        assert_eq!(line, b"ab");
+        assert_eq!(rest, [0xc3]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_pause_stops_and_resumes_output() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let control = StreamControl::new();
+        let options = StreamOptions {
+            control: Some(control.clone()),
+            ..StreamOptions::default()
+        };
+        let seen = Arc::new(AtomicUsize::new(0));
+        let observer = {
+            let seen = Arc::clone(&seen);
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                control.pause();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let before = seen.load(Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(400)).await;
+                let after = seen.load(Ordering::SeqCst);
+                control.resume();
+                (before, after)
+            })
+        };
+
+        let counter = Arc::clone(&seen);
+        let result = spawn_streaming(
+            &NativeRuntime::new(),
+            "for i in $(seq 1 20); do echo $i; sleep 0.05; done",
+            &std::env::temp_dir(),
+            &options,
+            |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        )
+        .await;
+
+        let (before, after) = observer.await.unwrap();
+        assert_eq!(before, after, "output advanced while paused");
+        assert!(before < 20);
+        assert!(result.success());
+        assert_eq!(seen.load(Ordering::SeqCst), 20);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_to_file_writes_output_to_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out_path = tmp.path().join("logs/build.log");
+        std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+        std::fs::write(&out_path, "stale contents that must be truncated").unwrap();
+
+        let result = run_to_file(
+            &NativeRuntime::new(),
+            "echo building; echo warning >&2",
+            tmp.path(),
+            &out_path,
+        )
+        .await;
+
+        assert!(result.success());
+        assert!(result.stdout.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&out_path).unwrap(),
+            "building\nwarning\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_to_file_creates_parent_directories() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out_path = tmp.path().join("nested/dir/out.log");
+
+        let result = run_to_file(&NativeRuntime::new(), "echo hi", tmp.path(), &out_path).await;
+
+        assert!(result.success());
+        assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "hi\n");
+    }
+
+    #[test]
+    fn progress_key_strips_trailing_percentage() {
+        assert_eq!(
+            progress_key("Downloading foo 42%\r"),
+            Some("Downloading foo")
+        );
+        assert_eq!(
+            progress_key("Downloading foo 42.5 %\n"),
+            Some("Downloading foo")
+        );
+        assert_eq!(progress_key("plain line\n"), None);
+        assert_eq!(progress_key("100%"), Some(""));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_coalesces_progress_updates() {
+        let command = "for p in 10 20 30 40 50 60 70 80 90 100; do printf 'Downloading %s%%\\r' $p; done; echo; echo done";
+        let run = |coalesce_progress: bool| async move {
+            let options = StreamOptions {
+                coalesce_progress,
+                ..StreamOptions::default()
+            };
+            let mut chunks = Vec::new();
+            let result = spawn_streaming(
+                &NativeRuntime::new(),
+                command,
+                &std::env::temp_dir(),
+                &options,
+                |chunk| chunks.push(chunk.text.clone()),
+            )
+            .await;
+            (result, chunks)
+        };
+
+        let (result, chunks) = run(true).await;
+        assert!(result.success());
+        assert_eq!(chunks, ["Downloading 100%\r", "\n", "done\n"]);
+        assert!(
+            result
+                .stdout
+                .starts_with("Downloading 10%\rDownloading 20%\r")
+        );
+
+        let (_, chunks) = run(false).await;
+        assert_eq!(
+            chunks.len(),
+            2,
+            "without coalescing, \\r does not split lines"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_coalesces_newline_progress_lines() {
+        let options = StreamOptions {
+            coalesce_progress: true,
+            ..StreamOptions::default()
+        };
+        let mut chunks = Vec::new();
+        spawn_streaming(
+            &NativeRuntime::new(),
+            "for p in 1 2 3 4 5 6 7 8; do echo \"step $p%\"; done; echo finished",
+            &std::env::temp_dir(),
+            &options,
+            |chunk| chunks.push(chunk.text.clone()),
+        )
+        .await;
+
+        assert_eq!(chunks, ["step 8%\n", "finished\n"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_streaming_suppresses_spinner_frames() {
+        let options = StreamOptions {
+            suppress_spinners: true,
+            ..StreamOptions::default()
+        };
+        let mut chunks = Vec::new();
+        let result = spawn_streaming(
+            &NativeRuntime::new(),
+            "printf '|\\r/\\r-\\r\\\\\\rfetching\\n⠋\\n- item\\n'",
+            &std::env::temp_dir(),
+            &options,
+            |chunk| chunks.push(chunk.text.clone()),
+        )
+        .await;
+
+        assert_eq!(chunks, ["fetching\n", "- item\n"]);
+        assert!(result.stdout.starts_with("|\r/\r-\r"));
+    }
+}
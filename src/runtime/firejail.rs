@@ -0,0 +1,204 @@
+use super::native::{NativeRuntime, expand_workspace_placeholder};
+use super::traits::RuntimeAdapter;
+use std::path::{Path, PathBuf};
+
+/// Firejail runtime — runs commands on the host inside a `firejail` sandbox.
+///
+/// Each command becomes `firejail --quiet --private=<workspace> --net=none
+/// sh -c <command>`: the workspace replaces the home directory, so the rest
+/// of the user's files are hidden, and networking is disabled. Both can be
+/// relaxed with [`with_network`](Self::with_network) and
+/// [`with_private_workspace`](Self::with_private_workspace).
+#[derive(Debug, Clone)]
+pub struct FirejailRuntime {
+    /// Give commands network access instead of `--net=none`.
+    network: bool,
+    /// Mount the workspace as a private home directory (`--private=<dir>`).
+    private_workspace: bool,
+}
+
+impl Default for FirejailRuntime {
+    fn default() -> Self {
+        Self {
+            network: false,
+            private_workspace: true,
+        }
+    }
+}
+
+impl FirejailRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow commands to reach the network. Off by default.
+    pub fn with_network(mut self, enabled: bool) -> Self {
+        self.network = enabled;
+        self
+    }
+
+    /// Mount the workspace as the sandbox's home directory, hiding the real
+    /// one. On by default; when off, commands see the host filesystem as
+    /// firejail's default profile allows.
+    pub fn with_private_workspace(mut self, enabled: bool) -> Self {
+        self.private_workspace = enabled;
+        self
+    }
+
+    /// The workspace as an absolute path firejail can mount.
+    fn private_dir(workspace_dir: &Path) -> anyhow::Result<PathBuf> {
+        let resolved = workspace_dir
+            .canonicalize()
+            .unwrap_or_else(|_| workspace_dir.to_path_buf());
+        if !resolved.is_absolute() {
+            anyhow::bail!(
+                "Firejail runtime requires an absolute workspace path, got: {}",
+                resolved.display()
+            );
+        }
+        if resolved == Path::new("/") {
+            anyhow::bail!("Refusing to use filesystem root (/) as the firejail private directory");
+        }
+        Ok(resolved)
+    }
+}
+
+impl RuntimeAdapter for FirejailRuntime {
+    fn name(&self) -> &str {
+        "firejail"
+    }
+
+    fn has_shell_access(&self) -> bool {
+        true
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        true
+    }
+
+    fn storage_path(&self) -> PathBuf {
+        // The agent itself runs outside the sandbox.
+        NativeRuntime::new().storage_path()
+    }
+
+    fn supports_long_running(&self) -> bool {
+        true
+    }
+
+    fn has_network_access(&self) -> bool {
+        self.network
+    }
+
+    fn shell_program(&self) -> Option<String> {
+        Some("sh".to_string())
+    }
+
+    fn health_probe(&self) -> Option<tokio::process::Command> {
+        let mut probe = tokio::process::Command::new("firejail");
+        probe.arg("--version");
+        Some(probe)
+    }
+
+    fn translate_path(&self, host_path: &Path, workspace_dir: &Path) -> PathBuf {
+        // `--private=<dir>` mounts the workspace over the user's home.
+        let home = directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        match (self.private_workspace, home) {
+            (true, Some(home)) => match host_path.strip_prefix(workspace_dir) {
+                Ok(relative) if relative.as_os_str().is_empty() => home,
+                Ok(relative) => home.join(relative),
+                Err(_) => host_path.to_path_buf(),
+            },
+            _ => host_path.to_path_buf(),
+        }
+    }
+
+    fn build_shell_command(
+        &self,
+        command: &str,
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        let mut process = tokio::process::Command::new("firejail");
+        process.arg("--quiet");
+
+        if self.private_workspace {
+            let private_dir = Self::private_dir(workspace_dir)?;
+            process.arg(format!("--private={}", private_dir.display()));
+        }
+
+        if !self.network {
+            process.arg("--net=none");
+        }
+
+        let workspace = self.translate_path(workspace_dir, workspace_dir);
+        let command = expand_workspace_placeholder(command, &workspace, Path::new("sh"));
+        process
+            .arg("sh")
+            .arg("-c")
+            .arg(&*command)
+            .current_dir(workspace_dir);
+
+        Ok(process)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::render_command;
+
+    fn args(command: &tokio::process::Command) -> Vec<String> {
+        command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn firejail_wraps_command_with_private_workspace_and_no_network() {
+        let workspace = tempfile::tempdir().unwrap();
+        let private = workspace.path().canonicalize().unwrap();
+        let command = FirejailRuntime::new()
+            .build_shell_command("echo hi", workspace.path())
+            .unwrap();
+
+        assert_eq!(command.as_std().get_program(), "firejail");
+        assert_eq!(
+            args(&command),
+            [
+                "--quiet".to_string(),
+                format!("--private={}", private.display()),
+                "--net=none".to_string(),
+                "sh".to_string(),
+                "-c".to_string(),
+                "echo hi".to_string(),
+            ]
+        );
+        assert!(render_command(&command).starts_with("firejail --quiet"));
+    }
+
+    #[test]
+    fn firejail_toggles_drop_flags_and_report_capabilities() {
+        let workspace = tempfile::tempdir().unwrap();
+        let sandboxed = FirejailRuntime::new();
+        assert!(!sandboxed.has_network_access());
+        assert!(sandboxed.has_shell_access());
+
+        let relaxed = FirejailRuntime::new()
+            .with_network(true)
+            .with_private_workspace(false);
+        assert!(relaxed.has_network_access());
+        let command = relaxed
+            .build_shell_command("echo hi", workspace.path())
+            .unwrap();
+        assert_eq!(args(&command), ["--quiet", "sh", "-c", "echo hi"]);
+    }
+
+    #[test]
+    fn firejail_refuses_root_as_private_dir() {
+        let err = FirejailRuntime::new()
+            .build_shell_command("true", Path::new("/"))
+            .unwrap_err();
+        assert!(err.to_string().contains("filesystem root"));
+    }
+}
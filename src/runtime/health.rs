@@ -0,0 +1,276 @@
+//! Readiness probing for runtimes that depend on an external service, and an
+//! end-to-end self test for validating a runtime configuration.
+
+use super::decorators::render_command;
+use super::exec::run_command;
+use super::traits::RuntimeAdapter;
+use std::future::Future;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+/// What [`self_test`] echoes and expects back.
+pub const SELF_TEST_MARKER: &str = "zeroclaw";
+
+/// Three-state health of a runtime, as reported by `/readyz`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The runtime can run commands.
+    Healthy,
+    /// The runtime did not answer in time; it may recover.
+    Degraded(String),
+    /// The runtime's probe failed outright.
+    Unhealthy(String),
+}
+
+impl HealthStatus {
+    /// Stable lowercase name, for JSON responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded(_) => "degraded",
+            Self::Unhealthy(_) => "unhealthy",
+        }
+    }
+
+    /// Why the runtime is not healthy, if it is not.
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Healthy => None,
+            Self::Degraded(reason) | Self::Unhealthy(reason) => Some(reason),
+        }
+    }
+}
+
+/// Run `adapter`'s [`health_probe`](RuntimeAdapter::health_probe), giving up
+/// after `timeout`.
+///
+/// A probe that outlives `timeout` is killed and reported as
+/// `Degraded("timeout")` so a hung daemon cannot block readiness checks.
+/// Runtimes without a probe are always healthy.
+pub async fn health_check(adapter: &dyn RuntimeAdapter, timeout: Duration) -> HealthStatus {
+    let Some(mut probe) = adapter.health_probe() else {
+        return HealthStatus::Healthy;
+    };
+    probe
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    with_timeout(timeout, async move {
+        match probe.output().await {
+            Ok(output) if output.status.success() => HealthStatus::Healthy,
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                HealthStatus::Unhealthy(format!(
+                    "probe exited with {}: {}",
+                    output.status,
+                    stderr.trim()
+                ))
+            }
+            Err(err) => HealthStatus::Unhealthy(format!("probe failed to start: {err}")),
+        }
+    })
+    .await
+}
+
+/// Resolve `check`, or `Degraded("timeout")` if it takes longer than `timeout`.
+async fn with_timeout(
+    timeout: Duration,
+    check: impl Future<Output = HealthStatus>,
+) -> HealthStatus {
+    tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| HealthStatus::Degraded("timeout".to_string()))
+}
+
+/// Outcome of [`self_test`], step by step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// The shell the runtime reports it runs commands with.
+    pub shell: Option<String>,
+    /// The built process, rendered for display, if building succeeded.
+    pub command: Option<String>,
+    /// Whether the command ran and printed [`SELF_TEST_MARKER`].
+    pub output_matched: bool,
+    /// How long building and running the command took.
+    pub duration: Duration,
+    /// The first step that failed and why.
+    pub error: Option<String>,
+}
+
+impl SelfTestReport {
+    /// Whether every step succeeded.
+    pub fn passed(&self) -> bool {
+        self.command.is_some() && self.output_matched && self.error.is_none()
+    }
+}
+
+/// Run `echo zeroclaw` through `adapter` the way real commands run, to catch
+/// misconfiguration (a missing shell, a stopped Docker daemon, a bad image)
+/// before any real work is attempted.
+///
+/// The command goes through [`build_shell_command`](RuntimeAdapter::build_shell_command)
+/// and then [`run_command`], so preprocessors, timeouts and environment policy
+/// all apply. Runtimes without shell access fail the test.
+pub async fn self_test(adapter: &dyn RuntimeAdapter, workspace_dir: &Path) -> SelfTestReport {
+    let started = Instant::now();
+    let mut report = SelfTestReport {
+        shell: adapter.shell_program(),
+        command: None,
+        output_matched: false,
+        duration: Duration::ZERO,
+        error: None,
+    };
+    let command = format!("echo {SELF_TEST_MARKER}");
+
+    if adapter.has_shell_access() {
+        match adapter.build_shell_command(&adapter.preprocess_command(&command), workspace_dir) {
+            Ok(process) => report.command = Some(render_command(&process)),
+            Err(err) => report.error = Some(format!("failed to build command: {err:#}")),
+        }
+    } else {
+        report.error = Some(format!("runtime '{}' has no shell access", adapter.name()));
+    }
+
+    if report.error.is_none() {
+        let result = run_command(adapter, &command, workspace_dir).await;
+        report.output_matched = result.success()
+            && result
+                .stdout
+                .lines()
+                .any(|line| line.trim() == SELF_TEST_MARKER);
+        if !report.output_matched {
+            report.error = Some(if let Some(reason) = result.timed_out {
+                format!("command timed out ({})", reason.as_str())
+            } else {
+                format!(
+                    "command exited with {:?}: {}",
+                    result.exit_code,
+                    result.stderr.trim()
+                )
+            });
+        }
+    }
+
+    report.duration = started.elapsed();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::NativeRuntime;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn health_check_degrades_when_probe_outlives_timeout() {
+        let started = Instant::now();
+        let status = with_timeout(Duration::from_millis(50), async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            HealthStatus::Healthy
+        })
+        .await;
+
+        assert_eq!(status, HealthStatus::Degraded("timeout".to_string()));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    /// A runtime whose daemon never answers its health probe.
+    #[cfg(target_os = "linux")]
+    struct HangingProbeRuntime;
+
+    #[cfg(target_os = "linux")]
+    impl RuntimeAdapter for HangingProbeRuntime {
+        fn name(&self) -> &str {
+            "hanging-probe"
+        }
+
+        fn has_shell_access(&self) -> bool {
+            false
+        }
+
+        fn has_filesystem_access(&self) -> bool {
+            false
+        }
+
+        fn storage_path(&self) -> std::path::PathBuf {
+            std::env::temp_dir()
+        }
+
+        fn supports_long_running(&self) -> bool {
+            false
+        }
+
+        fn health_probe(&self) -> Option<tokio::process::Command> {
+            let mut probe = tokio::process::Command::new("sleep");
+            probe.arg("10.2718");
+            Some(probe)
+        }
+
+        fn build_shell_command(
+            &self,
+            _command: &str,
+            _workspace_dir: &Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            anyhow::bail!("no shell")
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn health_check_kills_probe_that_outlives_timeout() {
+        let started = Instant::now();
+        let status = health_check(&HangingProbeRuntime, Duration::from_millis(200)).await;
+
+        assert_eq!(status, HealthStatus::Degraded("timeout".to_string()));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let probe_running = || {
+            std::fs::read_dir("/proc")
+                .unwrap()
+                .filter_map(|entry| std::fs::read(entry.ok()?.path().join("cmdline")).ok())
+                .any(|cmdline| cmdline == b"sleep\010.2718\0")
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while probe_running() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!probe_running(), "timed-out probe must be killed");
+    }
+
+    #[tokio::test]
+    async fn health_check_without_probe_is_healthy() {
+        let status = health_check(&NativeRuntime::new(), Duration::from_secs(1)).await;
+        assert_eq!(status, HealthStatus::Healthy);
+        assert_eq!(status.as_str(), "healthy");
+        assert_eq!(status.reason(), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn native_self_test_passes_and_echoes_marker() {
+        let report = self_test(&NativeRuntime::new(), &std::env::temp_dir()).await;
+
+        assert!(report.passed(), "{report:?}");
+        assert_eq!(report.error, None);
+        assert!(
+            report
+                .command
+                .as_deref()
+                .is_some_and(|command| command.contains(SELF_TEST_MARKER))
+        );
+        assert!(report.shell.is_some());
+        assert!(report.duration > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn self_test_reports_runtime_that_cannot_run_commands() {
+        let report = self_test(&crate::runtime::MockRuntime::new(), &std::env::temp_dir()).await;
+
+        assert!(!report.passed());
+        assert!(report.command.is_some());
+        assert!(report.error.is_some());
+    }
+}
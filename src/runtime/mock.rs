@@ -0,0 +1,169 @@
+//! In-memory [`RuntimeAdapter`] for tests that must not spawn processes.
+
+use super::exec::CommandResult;
+use super::traits::RuntimeAdapter;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Runtime that records requested commands and replays scripted results.
+///
+/// [`run`](Self::run) returns queued results in order, falling back to an
+/// empty successful result once the queue is drained. It also answers
+/// [`replayed_result`](RuntimeAdapter::replayed_result), so
+/// [`run_command`](super::run_command) and the other execution helpers get
+/// scripted results without spawning anything.
+/// [`build_shell_command`](RuntimeAdapter::build_shell_command) only records
+/// the command; the returned process names a nonexistent `mock-runtime`
+/// program, so code that spawns it fails loudly instead of running anything.
+#[derive(Debug, Default)]
+pub struct MockRuntime {
+    results: Mutex<VecDeque<CommandResult>>,
+    commands: Mutex<Vec<String>>,
+}
+
+impl MockRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `result` to be returned by a later [`run`](Self::run).
+    pub fn with_result(self, result: CommandResult) -> Self {
+        self.push_result(result);
+        self
+    }
+
+    /// Queue `result` to be returned by a later [`run`](Self::run).
+    pub fn push_result(&self, result: CommandResult) {
+        self.results.lock().push_back(result);
+    }
+
+    /// Record `command` and return the next scripted result.
+    pub fn run(&self, command: &str) -> CommandResult {
+        self.commands.lock().push(command.to_string());
+        let mut result = self
+            .results
+            .lock()
+            .pop_front()
+            .unwrap_or_else(|| Self::success(""));
+        result.command = command.to_string();
+        result
+    }
+
+    /// Every command requested so far, in order.
+    pub fn commands(&self) -> Vec<String> {
+        self.commands.lock().clone()
+    }
+
+    /// A successful result with the given stdout, for scripting.
+    pub fn success(stdout: &str) -> CommandResult {
+        Self::exit(0, stdout, "")
+    }
+
+    /// A completed result with the given exit code and output, for scripting.
+    pub fn exit(code: i32, stdout: &str, stderr: &str) -> CommandResult {
+        CommandResult {
+            command: String::new(),
+            exit_code: Some(code),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            timed_out: None,
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+impl RuntimeAdapter for MockRuntime {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn has_shell_access(&self) -> bool {
+        true
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        true
+    }
+
+    fn storage_path(&self) -> PathBuf {
+        std::env::temp_dir().join("rain-mock-runtime")
+    }
+
+    fn supports_long_running(&self) -> bool {
+        false
+    }
+
+    fn shell_program(&self) -> Option<String> {
+        Some("mock".to_string())
+    }
+
+    fn effective_environment(&self, _workspace_dir: &Path) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn replayed_result(&self, command: &str) -> Option<CommandResult> {
+        Some(self.run(command))
+    }
+
+    fn build_shell_command(
+        &self,
+        command: &str,
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        self.commands.lock().push(command.to_string());
+        let mut process = tokio::process::Command::new("mock-runtime");
+        process.arg(command).current_dir(workspace_dir);
+        Ok(process)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_runtime_replays_results_in_order() {
+        let runtime = MockRuntime::new()
+            .with_result(MockRuntime::success("first\n"))
+            .with_result(MockRuntime::exit(2, "", "boom\n"));
+
+        let first = runtime.run("echo first");
+        assert!(first.success());
+        assert_eq!(first.stdout, "first\n");
+        assert_eq!(first.command, "echo first");
+
+        let second = runtime.run("false");
+        assert_eq!(second.exit_code, Some(2));
+        assert_eq!(second.stderr, "boom\n");
+
+        let drained = runtime.run("true");
+        assert!(drained.success());
+        assert!(drained.stdout.is_empty());
+    }
+
+    #[test]
+    fn mock_runtime_records_commands() {
+        let runtime = MockRuntime::new();
+        runtime.run("cargo build");
+        runtime
+            .build_shell_command("cargo test", Path::new("."))
+            .unwrap();
+
+        assert_eq!(runtime.commands(), ["cargo build", "cargo test"]);
+        assert_eq!(runtime.name(), "mock");
+        assert!(runtime.has_shell_access());
+    }
+
+    #[tokio::test]
+    async fn run_command_returns_scripted_results_without_spawning() {
+        let runtime = MockRuntime::new().with_result(MockRuntime::exit(1, "", "nope\n"));
+
+        let result = crate::runtime::run_command(&runtime, "make", Path::new(".")).await;
+        assert_eq!(result.exit_code, Some(1));
+        assert_eq!(result.stderr, "nope\n");
+        assert_eq!(result.command, "make");
+        assert_eq!(runtime.commands(), ["make"]);
+    }
+}
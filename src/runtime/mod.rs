@@ -1,28 +1,109 @@
+pub mod decorators;
+pub mod disk;
 pub mod docker;
+pub mod env;
+pub mod exec;
+pub mod firejail;
+pub mod health;
+pub mod mock;
 pub mod native;
+pub mod output;
+pub mod overrides;
+pub mod preprocess;
+pub mod registry;
+pub mod session;
 pub mod traits;
 
-pub use docker::DockerRuntime;
-pub use native::NativeRuntime;
-pub use traits::RuntimeAdapter;
+#[allow(unused_imports)]
+pub use decorators::{LoggingRuntime, TimeoutRuntime, render_command};
+#[allow(unused_imports)]
+pub use docker::{DockerNetwork, DockerRuntime};
+#[allow(unused_imports)]
+pub use env::{EnvLayer, MASKED_ENV_VALUE, masked_environment, merge_env};
+#[allow(unused_imports)]
+pub use exec::{
+    CapturedOutput, CommandResult, LinePrefix, StreamControl, StreamOptions, TimeoutReason,
+    capture_command_bytes, run_command, run_to_file, spawn_streaming, spawn_streaming_tee,
+};
+pub use firejail::FirejailRuntime;
+#[allow(unused_imports)]
+pub use health::{HealthStatus, SELF_TEST_MARKER, SelfTestReport, health_check, self_test};
+#[allow(unused_imports)]
+pub use mock::MockRuntime;
+#[allow(unused_imports)]
+pub use native::{EnvPolicy, NativeRuntime, shell_quote};
+#[allow(unused_imports)]
+pub use output::{OutputChunk, OutputStream, decode_output_lossy};
+#[allow(unused_imports)]
+pub use overrides::{RUNTIME_CONFIG_FILE, load_runtime_config};
+#[allow(unused_imports)]
+pub use preprocess::{CommandPreprocessor, PipefailPreprocessor, TimingPreprocessor};
+#[allow(unused_imports)]
+pub use registry::{ProcessGuard, ProcessInfo, ProcessRegistry};
+#[allow(unused_imports)]
+pub use session::{RecordingRuntime, ReplayRuntime};
+#[allow(unused_imports)]
+pub use traits::{
+    Capability, KNOWN_INTERPRETERS, PathStyle, RuntimeAdapter, RuntimeError, require_capability,
+};
 
 use crate::config::RuntimeConfig;
+use futures_util::future::join_all;
+use std::path::Path;
+use tokio::sync::Semaphore;
 
 /// Factory: create the right runtime from config
 pub fn create_runtime(config: &RuntimeConfig) -> anyhow::Result<Box<dyn RuntimeAdapter>> {
     match config.kind.as_str() {
         "native" => Ok(Box::new(NativeRuntime::new())),
         "docker" => Ok(Box::new(DockerRuntime::new(config.docker.clone()))),
+        "firejail" => Ok(Box::new(FirejailRuntime::new())),
         "cloudflare" => anyhow::bail!(
             "runtime.kind='cloudflare' is not implemented yet. Use runtime.kind='native' for now."
         ),
         other if other.trim().is_empty() => {
-            anyhow::bail!("runtime.kind cannot be empty. Supported values: native, docker")
+            anyhow::bail!(
+                "runtime.kind cannot be empty. Supported values: native, docker, firejail"
+            )
         }
-        other => anyhow::bail!("Unknown runtime kind '{other}'. Supported values: native, docker"),
+        other => anyhow::bail!(
+            "Unknown runtime kind '{other}'. Supported values: native, docker, firejail"
+        ),
     }
 }
 
+/// Factory: create the runtime for `config` after applying host defaults.
+///
+/// `runtime.toml` in the host storage directory and `rain_RUNTIME_*`
+/// environment variables are layered over `config`; see [`overrides`].
+pub fn detect_runtime(config: &RuntimeConfig) -> anyhow::Result<Box<dyn RuntimeAdapter>> {
+    create_runtime(&resolve_runtime_config(config)?)
+}
+
+/// The config [`detect_runtime`] builds from: `config` with host defaults applied.
+pub fn resolve_runtime_config(config: &RuntimeConfig) -> anyhow::Result<RuntimeConfig> {
+    let storage_dir = NativeRuntime::new().storage_path();
+    load_runtime_config(config, &storage_dir, |key| std::env::var(key).ok())
+}
+
+/// Run independent `commands` concurrently, bounded by
+/// [`RuntimeAdapter::max_concurrent_processes`].
+///
+/// Results are returned in input order regardless of completion order.
+pub async fn run_batch(
+    adapter: &dyn RuntimeAdapter,
+    commands: &[String],
+    workspace_dir: &Path,
+) -> Vec<CommandResult> {
+    let permits = Semaphore::new(adapter.max_concurrent_processes().max(1));
+    join_all(commands.iter().map(|command| async {
+        // The semaphore is never closed, so acquiring cannot fail.
+        let _permit = permits.acquire().await.expect("batch semaphore closed");
+        run_command(adapter, command, workspace_dir).await
+    }))
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,6 +130,17 @@ mod tests {
         assert!(rt.has_shell_access());
     }
 
+    #[test]
+    fn factory_firejail() {
+        let cfg = RuntimeConfig {
+            kind: "firejail".into(),
+            ..RuntimeConfig::default()
+        };
+        let rt = create_runtime(&cfg).unwrap();
+        assert_eq!(rt.name(), "firejail");
+        assert!(!rt.has_network_access());
+    }
+
     #[test]
     fn factory_cloudflare_errors() {
         let cfg = RuntimeConfig {
@@ -84,4 +176,40 @@ mod tests {
             Ok(_) => panic!("empty runtime should error"),
         }
     }
+
+    #[test]
+    fn runtime_toml_image_reaches_docker_runtime() {
+        let storage = tempfile::tempdir().unwrap();
+        std::fs::write(
+            storage.path().join(RUNTIME_CONFIG_FILE),
+            "kind = \"docker\"\n\n[docker]\nimage = \"python:3.12-slim\"\nmount_workspace = false\n",
+        )
+        .unwrap();
+
+        let resolved =
+            load_runtime_config(&RuntimeConfig::default(), storage.path(), |_| None).unwrap();
+        let rt = create_runtime(&resolved).unwrap();
+        assert_eq!(rt.name(), "docker");
+
+        let command = rt.build_shell_command("true", storage.path()).unwrap();
+        assert!(render_command(&command).contains(" python:3.12-slim "));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_batch_respects_limit_and_preserves_order() {
+        let workspace = tempfile::tempdir().unwrap();
+        let runtime = NativeRuntime::new().with_max_concurrent_processes(1);
+        // `mkdir` is atomic, so the lock directory acts as a shared counter:
+        // a second concurrent command fails to create it.
+        let commands: Vec<String> = (1..=3)
+            .map(|n| format!("mkdir running && sleep 0.2 && rmdir running && echo {n}"))
+            .collect();
+
+        let results = run_batch(&runtime, &commands, workspace.path()).await;
+
+        let stdout: Vec<&str> = results.iter().map(|r| r.stdout.trim()).collect();
+        assert_eq!(stdout, ["1", "2", "3"]);
+        assert!(results.iter().all(CommandResult::success));
+    }
 }
@@ -1,12 +1,358 @@
-use super::traits::RuntimeAdapter;
+use super::env::{EnvLayer, merge_env};
+use super::preprocess::{CommandPreprocessor, PipefailPreprocessor};
+use super::traits::{RuntimeAdapter, RuntimeError};
+use std::borrow::Cow;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Placeholder in commands that expands to the quoted workspace directory.
+pub const WORKSPACE_PLACEHOLDER: &str = "{{workspace}}";
+
+/// Command-length ceiling on Windows, kept below `cmd.exe`'s 8191-character
+/// limit to leave room for the shell invocation itself.
+const WINDOWS_MAX_COMMAND_LENGTH: usize = 8000;
+
+/// Platform default shell used when none is configured.
+const DEFAULT_SHELL: &str = if cfg!(target_os = "windows") {
+    "powershell.exe"
+} else {
+    "sh"
+};
+
+/// Errors raised while preparing commands on the native runtime.
+#[derive(Debug, thiserror::Error)]
+pub enum NativeRuntimeError {
+    /// The shell configured via [`NativeRuntime::with_shell`] does not exist.
+    #[error("configured shell '{}' was not found", shell.display())]
+    ShellNotFound { shell: PathBuf },
+}
+
+/// Which server environment variables commands inherit.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EnvPolicy {
+    /// Pass the whole server environment through.
+    #[default]
+    InheritAll,
+    /// Start commands with an empty environment.
+    InheritNone,
+    /// Pass only these variables through.
+    Allowlist(Vec<String>),
+    /// Pass everything except these variables through.
+    Denylist(Vec<String>),
+}
+
+impl EnvPolicy {
+    /// The policy as a filter over the inherited environment.
+    fn layer(&self) -> EnvLayer {
+        let names = |keys: &[String]| keys.iter().map(OsString::from).collect();
+        match self {
+            Self::InheritAll => EnvLayer::Deny(Vec::new()),
+            Self::InheritNone => EnvLayer::Allow(Vec::new()),
+            Self::Allowlist(keys) => EnvLayer::Allow(names(keys)),
+            Self::Denylist(keys) => EnvLayer::Deny(names(keys)),
+        }
+    }
+}
 
 /// Native runtime — full access, runs on Mac/Linux/Windows/Docker/Raspberry Pi
-pub struct NativeRuntime;
+#[derive(Debug, Clone, Default)]
+pub struct NativeRuntime {
+    /// File-creation mask applied to spawned commands (Unix only).
+    umask: Option<u32>,
+    /// Shell overriding the platform default (`sh` / `powershell.exe`).
+    shell: Option<PathBuf>,
+    /// Start POSIX shells as login shells so profile scripts are sourced.
+    login_shell: bool,
+    /// Command transformations applied before building, in order.
+    preprocessors: Vec<Arc<dyn CommandPreprocessor>>,
+    /// Cap on concurrently running commands; host parallelism when unset.
+    max_concurrent_processes: Option<usize>,
+    /// Which server variables commands inherit.
+    env_policy: EnvPolicy,
+    /// Switch the Windows console to UTF-8 before running commands.
+    utf8_codepage: bool,
+    /// `(uid, gid)` commands switch to before exec (Unix only).
+    drop_privileges: Option<(u32, u32)>,
+    /// Signal overriding the default graceful-termination signal.
+    graceful_signal: Option<i32>,
+    /// Program and arguments every command is run under (e.g. `nice -n 10`).
+    command_prefix: Vec<String>,
+    /// Fail pipelines when any stage fails, not just the last.
+    pipefail: bool,
+}
 
 impl NativeRuntime {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Apply `mode` as the umask of spawned commands so files they create get
+    /// the intended permissions.
+    ///
+    /// By default commands inherit the server's umask. Ignored on non-Unix platforms.
+    pub fn with_umask(mut self, mode: u32) -> Self {
+        self.umask = Some(mode & 0o777);
+        self
+    }
+
+    /// Run commands with `shell` (e.g. `bash`, `/bin/zsh`, `cmd.exe`) instead of
+    /// the platform default.
+    ///
+    /// Bare names are looked up on `PATH`. The shell is validated when a command
+    /// is built, failing with [`NativeRuntimeError::ShellNotFound`].
+    pub fn with_shell(mut self, shell: impl Into<PathBuf>) -> Self {
+        self.shell = Some(shell.into());
+        self
+    }
+
+    /// Start the shell as a login shell (`-l`) so `~/.profile`-style scripts run.
+    ///
+    /// Needed for tools installed through profile hooks (nvm, pyenv, rbenv).
+    /// Sourcing profiles adds noticeable startup latency to every command and
+    /// makes behaviour depend on the user's dotfiles, so it is off by default.
+    /// Has no effect on `cmd.exe` or PowerShell.
+    pub fn with_login_shell(mut self, enabled: bool) -> Self {
+        self.login_shell = enabled;
+        self
+    }
+
+    /// Append a command preprocessor; preprocessors run in the order added.
+    pub fn with_preprocessor(mut self, preprocessor: Arc<dyn CommandPreprocessor>) -> Self {
+        self.preprocessors.push(preprocessor);
+        self
+    }
+
+    /// Limit batch helpers to `limit` concurrently running commands (at least one).
+    pub fn with_max_concurrent_processes(mut self, limit: usize) -> Self {
+        self.max_concurrent_processes = Some(limit.max(1));
+        self
+    }
+
+    /// Choose which server environment variables commands inherit.
+    pub fn with_env_policy(mut self, policy: EnvPolicy) -> Self {
+        self.env_policy = policy;
+        self
+    }
+
+    /// Clear the environment of spawned commands except for `vars`.
+    ///
+    /// Shorthand for [`EnvPolicy::Allowlist`].
+    pub fn with_env_allowlist<I, S>(self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.with_env_policy(EnvPolicy::Allowlist(
+            vars.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// Run commands as `uid`/`gid` with no supplementary groups, for privilege
+    /// separation when the server itself runs as root.
+    ///
+    /// The child calls `setgroups([])`, `setgid`, then `setuid` before exec —
+    /// groups first, since they can no longer be changed once the uid is
+    /// dropped. Spawning fails if any call fails. Ignored on non-Unix platforms.
+    pub fn with_drop_privileges(mut self, uid: u32, gid: u32) -> Self {
+        self.drop_privileges = Some((uid, gid));
+        self
+    }
+
+    /// Ask timed-out or cancelled commands to stop with `signal` (e.g.
+    /// `libc::SIGINT`) instead of `SIGTERM` before they are killed.
+    ///
+    /// Ignored on non-Unix platforms, where commands are always killed.
+    pub fn with_graceful_signal(mut self, signal: i32) -> Self {
+        self.graceful_signal = Some(signal);
+        self
+    }
+
+    /// Run every command under `prefix`, e.g. `["nice", "-n", "10"]` or
+    /// `["timeout", "60"]`, without writing a custom adapter.
+    ///
+    /// The prefix goes in front of the shell invocation (after shell selection
+    /// and `-l`, so `nice -n 10 bash -l -c '<command>'`) and in front of
+    /// programs run directly from an argv. An empty prefix disables it.
+    pub fn with_command_prefix(mut self, prefix: Vec<String>) -> Self {
+        self.command_prefix = prefix;
+        self
+    }
+
+    /// A process for `program`, run under the configured command prefix.
+    fn new_process(&self, program: impl AsRef<std::ffi::OsStr>) -> tokio::process::Command {
+        match self.command_prefix.split_first() {
+            Some((wrapper, wrapper_args)) => {
+                let mut process = tokio::process::Command::new(wrapper);
+                process.args(wrapper_args).arg(program);
+                process
+            }
+            None => tokio::process::Command::new(program),
+        }
+    }
+
+    /// On Windows, switch the console to UTF-8 before each command so output
+    /// is not emitted in the OEM codepage (437, 1252, ...) and garbled.
+    ///
+    /// `cmd.exe` gets a `chcp 65001 >nul &&` prefix; PowerShell sets
+    /// `[Console]::OutputEncoding`. Ignored on other platforms.
+    pub fn with_utf8_codepage(mut self, enabled: bool) -> Self {
+        self.utf8_codepage = enabled;
+        self
+    }
+
+    /// Make a pipeline fail when any stage fails, not only the last one, so
+    /// `failing-step | tee log` reports the failure in its exit code.
+    ///
+    /// Commands go through a [`PipefailPreprocessor`] for the runtime's shell:
+    /// shells without the option (`cmd.exe`, PowerShell, older dash as `sh`)
+    /// log a warning and run commands unchanged. Off by default.
+    pub fn with_pipefail(mut self, enabled: bool) -> Self {
+        self.pipefail = enabled;
+        self
+    }
+
+    /// Expand placeholders, apply Windows codepage switching and pipefail for
+    /// `shell`, and enforce [`RuntimeAdapter::max_command_length`].
+    fn prepare_command<'a>(
+        &self,
+        command: &'a str,
+        workspace_dir: &Path,
+        shell: &Path,
+    ) -> Result<Cow<'a, str>, RuntimeError> {
+        let workspace = self.translate_path(workspace_dir, workspace_dir);
+        let mut command = expand_workspace_placeholder(command, &workspace, shell);
+        if cfg!(target_os = "windows") && self.utf8_codepage {
+            command = Cow::Owned(utf8_codepage_prefix(&command, shell));
+        }
+        if self.pipefail {
+            command = Cow::Owned(PipefailPreprocessor::for_shell(shell).process(&command));
+        }
+        if let Some(limit) = self.max_command_length() {
+            let length = command.chars().count();
+            if length > limit {
+                return Err(RuntimeError::CommandTooLong { length, limit });
+            }
+        }
+        Ok(command)
+    }
+
+    /// The server environment filtered through the env policy, plus
+    /// [`RuntimeAdapter::default_env_additions`], unmasked.
+    fn passed_environment(&self) -> Vec<(OsString, OsString)> {
+        self.filter_environment(std::env::vars_os().collect())
+    }
+
+    /// [`passed_environment`](Self::passed_environment) for `server_env`
+    /// instead of the live process environment.
+    fn filter_environment(
+        &self,
+        server_env: Vec<(OsString, OsString)>,
+    ) -> Vec<(OsString, OsString)> {
+        merge_env(&[
+            EnvLayer::Set(server_env),
+            self.env_policy.layer(),
+            EnvLayer::set(self.default_env_additions()),
+        ])
+    }
+
+    /// What commands would see given `server_env`, with secrets masked.
+    fn masked_environment_from(
+        &self,
+        server_env: Vec<(OsString, OsString)>,
+    ) -> Vec<(String, String)> {
+        let lossy = |value: OsString| value.to_string_lossy().into_owned();
+        super::env::masked_environment(
+            self.filter_environment(server_env)
+                .into_iter()
+                .map(|(key, value)| (lossy(key), lossy(value))),
+        )
+    }
+
+    /// Resolve the configured shell to an executable path.
+    fn resolve_shell(shell: &Path) -> Result<PathBuf, NativeRuntimeError> {
+        let not_found = || NativeRuntimeError::ShellNotFound {
+            shell: shell.to_path_buf(),
+        };
+        if shell.components().count() > 1 || shell.is_absolute() {
+            return if shell.is_file() {
+                Ok(shell.to_path_buf())
+            } else {
+                Err(not_found())
+            };
+        }
+        which::which(shell).map_err(|_| not_found())
+    }
+}
+
+/// Whether `shell` is a POSIX-style shell that understands `-l` and `-c`.
+fn is_posix_shell(shell: &Path) -> bool {
+    shell_command_flag(shell) == "-c"
+}
+
+/// Quote `arg` as a single POSIX shell word.
+///
+/// Words made only of characters no shell treats specially are returned
+/// as-is; anything else is single-quoted.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Quote `value` as a single word for `shell`.
+///
+/// `cmd.exe` gets double quotes (it has no single-quote syntax and `"` cannot
+/// occur in Windows paths); PowerShell and POSIX shells get single quotes.
+fn quote_for_shell(value: &str, shell: &Path) -> String {
+    match shell_command_flag(shell) {
+        "/C" => format!("\"{}\"", value.replace('"', "\"\"")),
+        "-Command" => format!("'{}'", value.replace('\'', "''")),
+        _ => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
+
+/// Expand [`WORKSPACE_PLACEHOLDER`] using the quoting rules of `shell`.
+pub(super) fn expand_workspace_placeholder<'a>(
+    command: &'a str,
+    workspace_dir: &Path,
+    shell: &Path,
+) -> Cow<'a, str> {
+    if command.contains(WORKSPACE_PLACEHOLDER) {
+        let quoted = quote_for_shell(&workspace_dir.to_string_lossy(), shell);
+        Cow::Owned(command.replace(WORKSPACE_PLACEHOLDER, &quoted))
+    } else {
+        Cow::Borrowed(command)
+    }
+}
+
+/// Prefix `command` so `shell` emits UTF-8 instead of the console codepage.
+fn utf8_codepage_prefix(command: &str, shell: &Path) -> String {
+    match shell_command_flag(shell) {
+        "/C" => format!("chcp 65001 >nul && {command}"),
+        "-Command" => {
+            format!("[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; {command}")
+        }
+        _ => command.to_string(),
+    }
+}
+
+/// Flag that makes `shell` execute its next argument as a command string.
+fn shell_command_flag(shell: &Path) -> &'static str {
+    let stem = shell
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match stem.as_str() {
+        "cmd" => "/C",
+        "powershell" | "pwsh" => "-Command",
+        _ => "-c",
     }
 }
 
@@ -34,30 +380,165 @@ impl RuntimeAdapter for NativeRuntime {
         true
     }
 
+    fn preprocessors(&self) -> &[Arc<dyn CommandPreprocessor>] {
+        &self.preprocessors
+    }
+
+    fn effective_environment(&self, _workspace_dir: &Path) -> Vec<(String, String)> {
+        self.masked_environment_from(std::env::vars_os().collect())
+    }
+
+    fn max_command_length(&self) -> Option<usize> {
+        cfg!(target_os = "windows").then_some(WINDOWS_MAX_COMMAND_LENGTH)
+    }
+
+    fn max_concurrent_processes(&self) -> usize {
+        self.max_concurrent_processes.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        })
+    }
+
+    #[cfg(unix)]
+    fn graceful_signal(&self) -> Option<i32> {
+        Some(self.graceful_signal.unwrap_or(libc::SIGTERM))
+    }
+
+    fn shell_program(&self) -> Option<String> {
+        Some(self.shell.as_ref().map_or_else(
+            || DEFAULT_SHELL.to_string(),
+            |shell| shell.display().to_string(),
+        ))
+    }
+
     fn build_shell_command(
         &self,
         command: &str,
         workspace_dir: &Path,
     ) -> anyhow::Result<tokio::process::Command> {
-        #[cfg(not(target_os = "windows"))]
-        {
-            let mut process = tokio::process::Command::new("sh");
-            process.arg("-c").arg(command).current_dir(workspace_dir);
-            Ok(process)
+        // Quote placeholders for the same shell that will run the command.
+        let mut process = if let Some(shell) = &self.shell {
+            let program = Self::resolve_shell(shell)?;
+            let command = self.prepare_command(command, workspace_dir, &program)?;
+            let mut process = self.new_process(&program);
+            if self.login_shell && is_posix_shell(&program) {
+                process.arg("-l");
+            }
+            let flag = shell_command_flag(&program);
+            process.arg(flag);
+            push_command_arg(&mut process, flag, &command);
+            process
+        } else {
+            let command = self.prepare_command(command, workspace_dir, Path::new(DEFAULT_SHELL))?;
+            default_shell_command(self.new_process(DEFAULT_SHELL), &command, self.login_shell)
+        };
+        self.configure_process(&mut process, workspace_dir);
+        Ok(process)
+    }
+
+    fn resolve_program(&self, name: &str, _workspace_dir: &Path) -> Option<PathBuf> {
+        // `which` searches PATH and, on Windows, tries each PATHEXT extension.
+        which::which(name).ok()
+    }
+
+    fn build_exec_command(
+        &self,
+        argv: &[String],
+        workspace_dir: &Path,
+    ) -> Option<anyhow::Result<tokio::process::Command>> {
+        let (program, args) = argv.split_first()?;
+        let mut process = self.new_process(program);
+        process.args(args);
+        self.configure_process(&mut process, workspace_dir);
+        Some(Ok(process))
+    }
+}
+
+impl NativeRuntime {
+    /// Apply the working directory, environment and Unix process settings
+    /// shared by shell and exec commands.
+    fn configure_process(&self, process: &mut tokio::process::Command, workspace_dir: &Path) {
+        process.current_dir(workspace_dir);
+
+        if self.env_policy == EnvPolicy::InheritAll {
+            // The child inherits everything already; only layer additions on top.
+            process.envs(merge_env(&[EnvLayer::set(self.default_env_additions())]));
+        } else {
+            process.env_clear().envs(self.passed_environment());
         }
 
-        #[cfg(target_os = "windows")]
-        {
-            let mut process = tokio::process::Command::new("powershell.exe");
-            process
-                .arg("-NoLogo")
-                .arg("-NoProfile")
-                .arg("-NonInteractive")
-                .arg("-Command")
-                .arg(command)
-                .current_dir(workspace_dir);
-            Ok(process)
+        #[cfg(unix)]
+        if let Some(mask) = self.umask {
+            let mask = mask as libc::mode_t;
+            // SAFETY: `umask` is async-signal-safe and only affects the forked child.
+            unsafe {
+                process.pre_exec(move || {
+                    libc::umask(mask);
+                    Ok(())
+                });
+            }
+        }
+
+        #[cfg(unix)]
+        if let Some((uid, gid)) = self.drop_privileges {
+            // SAFETY: `setgroups`, `setgid` and `setuid` are async-signal-safe
+            // and only affect the forked child.
+            unsafe {
+                process.pre_exec(move || {
+                    if libc::setgroups(0, std::ptr::null()) != 0
+                        || libc::setgid(gid) != 0
+                        || libc::setuid(uid) != 0
+                    {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+}
+
+/// Append the command string for a shell invoked with `flag`.
+///
+/// `cmd.exe` does not parse its command line with MSVC argument rules, so on
+/// Windows the string is passed verbatim rather than re-escaped; otherwise the
+/// quotes produced by [`quote_for_shell`] would arrive backslash-escaped.
+fn push_command_arg(process: &mut tokio::process::Command, flag: &str, command: &str) {
+    #[cfg(target_os = "windows")]
+    if flag == "/C" {
+        use std::os::windows::process::CommandExt;
+        process.as_std_mut().raw_arg(command);
+        return;
+    }
+    let _ = flag;
+    process.arg(command);
+}
+
+/// Platform default shell invocation for `command`, appended to `process`,
+/// which already names [`DEFAULT_SHELL`] (possibly behind a command prefix).
+fn default_shell_command(
+    mut process: tokio::process::Command,
+    command: &str,
+    login_shell: bool,
+) -> tokio::process::Command {
+    #[cfg(not(target_os = "windows"))]
+    {
+        if login_shell {
+            process.arg("-l");
         }
+        process.arg("-c").arg(command);
+        process
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = login_shell;
+        process
+            .arg("-NoLogo")
+            .arg("-NoProfile")
+            .arg("-NonInteractive")
+            .arg("-Command")
+            .arg(command);
+        process
     }
 }
 
@@ -65,6 +546,268 @@ impl RuntimeAdapter for NativeRuntime {
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[tokio::test]
+    #[ignore = "requires root: run with -- --ignored as root"]
+    async fn native_drop_privileges_runs_command_as_target_uid() {
+        const NOBODY: u32 = 65534;
+        let output = NativeRuntime::new()
+            .with_drop_privileges(NOBODY, NOBODY)
+            .build_shell_command("id -u; id -g; id -G", Path::new("/"))
+            .unwrap()
+            .output()
+            .await
+            .unwrap();
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines, ["65534", "65534", "65534"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn native_drop_privileges_to_own_ids_runs_or_fails_spawn() {
+        // SAFETY: `geteuid` and `getegid` have no preconditions.
+        let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+        let spawned = NativeRuntime::new()
+            .with_drop_privileges(uid, gid)
+            .build_shell_command("id -u", Path::new("/"))
+            .unwrap()
+            .output()
+            .await;
+
+        if uid == 0 {
+            let output = spawned.unwrap();
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "0");
+        } else {
+            // Only root may clear supplementary groups, so the spawn itself
+            // must fail rather than run with the server's privileges.
+            let err = spawned.unwrap_err();
+            assert_eq!(err.raw_os_error(), Some(libc::EPERM), "{err}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn native_command_prefix_wraps_shell_invocation() {
+        let prefix = ["nice", "-n", "10"].map(String::from).to_vec();
+        let runtime = NativeRuntime::new().with_command_prefix(prefix);
+        let command = runtime
+            .build_shell_command("echo hi", &std::env::temp_dir())
+            .unwrap();
+
+        assert_eq!(command.as_std().get_program(), "nice");
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(args, ["-n", "10", "sh", "-c", "echo hi"]);
+
+        let argv = ["ls", "-la"].map(String::from);
+        let exec = runtime
+            .build_from_argv(&argv, &std::env::temp_dir())
+            .unwrap();
+        assert_eq!(crate::runtime::render_command(&exec), "nice -n 10 ls -la");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn native_command_prefix_composes_with_configured_shell() {
+        let command = NativeRuntime::new()
+            .with_shell("/bin/sh")
+            .with_login_shell(true)
+            .with_command_prefix(vec!["nice".into(), "-n".into(), "10".into()])
+            .build_shell_command("echo hi", &std::env::temp_dir())
+            .unwrap();
+
+        assert_eq!(
+            crate::runtime::render_command(&command),
+            "nice -n 10 /bin/sh -l -c 'echo hi'"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn native_command_prefix_runs_command() {
+        let output = NativeRuntime::new()
+            .with_command_prefix(vec!["nice".into(), "-n".into(), "10".into()])
+            .build_shell_command("echo prefixed", &std::env::temp_dir())
+            .unwrap()
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "prefixed\n");
+    }
+
+    #[test]
+    fn native_interpreted_command_passes_code_as_one_argument() {
+        let code = "import sys; print('hi there', sys.argv)";
+        let command = NativeRuntime::new()
+            .build_interpreted_command("python", "-c", code, &std::env::temp_dir())
+            .unwrap();
+
+        assert_eq!(command.as_std().get_program(), "python");
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(args, ["-c", code]);
+        assert_eq!(
+            crate::runtime::render_command(&command),
+            r"python -c 'import sys; print('\''hi there'\'', sys.argv)'"
+        );
+        assert!(
+            NativeRuntime::new()
+                .build_interpreted_command(" ", "-c", code, &std::env::temp_dir())
+                .is_err()
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn native_rejects_commands_over_windows_limit() {
+        let runtime = NativeRuntime::new();
+        assert_eq!(
+            runtime.max_command_length(),
+            Some(WINDOWS_MAX_COMMAND_LENGTH)
+        );
+
+        let command = format!("echo {}", "x".repeat(WINDOWS_MAX_COMMAND_LENGTH));
+        let err = runtime
+            .build_shell_command(&command, &std::env::temp_dir())
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::CommandTooLong { limit, .. }) if *limit == WINDOWS_MAX_COMMAND_LENGTH
+        ));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn native_has_no_command_length_limit_on_unix() {
+        assert_eq!(NativeRuntime::new().max_command_length(), None);
+    }
+
+    #[test]
+    fn utf8_codepage_prefix_matches_shell() {
+        assert_eq!(
+            utf8_codepage_prefix("dir", Path::new("cmd.exe")),
+            "chcp 65001 >nul && dir"
+        );
+        assert!(
+            utf8_codepage_prefix("ls", Path::new("pwsh"))
+                .starts_with("[Console]::OutputEncoding = [System.Text.Encoding]::UTF8;")
+        );
+        assert_eq!(utf8_codepage_prefix("ls", Path::new("sh")), "ls");
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn native_utf8_codepage_prefixes_cmd_commands() {
+        let cwd = std::env::temp_dir();
+        let enabled = NativeRuntime::new()
+            .with_shell("cmd.exe")
+            .with_utf8_codepage(true)
+            .build_shell_command("echo hi", &cwd)
+            .unwrap();
+        let disabled = NativeRuntime::new()
+            .with_shell("cmd.exe")
+            .build_shell_command("echo hi", &cwd)
+            .unwrap();
+
+        assert!(format!("{enabled:?}").contains("chcp 65001 >nul && echo hi"));
+        assert!(!format!("{disabled:?}").contains("chcp"));
+    }
+
+    #[test]
+    fn native_effective_environment_reports_only_allowlisted_masked_vars() {
+        const SECRET_VAR: &str = "RAIN_TEST_EFFECTIVE_ENV_TOKEN";
+        let server_env = [
+            ("PATH", "/usr/bin"),
+            (SECRET_VAR, "hunter2"),
+            ("HOME", "/root"),
+        ]
+        .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+        .to_vec();
+
+        let runtime =
+            NativeRuntime::new().with_env_allowlist(["PATH", SECRET_VAR, "RAIN_TEST_UNSET_VAR"]);
+        let env = runtime.masked_environment_from(server_env);
+
+        let names: Vec<&str> = env.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["PATH", SECRET_VAR]);
+        assert_eq!(env[1].1, crate::runtime::MASKED_ENV_VALUE);
+    }
+
+    #[test]
+    fn env_policy_variants_filter_server_environment() {
+        let vars = || {
+            [("PATH", "/bin"), ("HOME", "/root"), ("TOKEN", "x")]
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+        };
+        let names = |policy: EnvPolicy| -> Vec<String> {
+            merge_env(&[EnvLayer::set(vars()), policy.layer()])
+                .into_iter()
+                .map(|(k, _)| k.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(names(EnvPolicy::InheritAll), ["PATH", "HOME", "TOKEN"]);
+        assert!(names(EnvPolicy::InheritNone).is_empty());
+        assert_eq!(
+            names(EnvPolicy::Allowlist(vec!["PATH".into(), "MISSING".into()])),
+            ["PATH"]
+        );
+        assert_eq!(
+            names(EnvPolicy::Denylist(vec!["TOKEN".into()])),
+            ["PATH", "HOME"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn env_policy_shapes_built_command_environment() {
+        let workspace = std::env::temp_dir();
+        let build = |policy: EnvPolicy| {
+            NativeRuntime::new()
+                .with_env_policy(policy)
+                .build_shell_command("true", &workspace)
+                .unwrap()
+        };
+
+        let inherit_all = build(EnvPolicy::InheritAll);
+        assert_eq!(inherit_all.as_std().get_envs().count(), 0);
+        assert!(!format!("{inherit_all:?}").contains("env -i"));
+
+        let inherit_none = build(EnvPolicy::InheritNone);
+        assert_eq!(inherit_none.as_std().get_envs().count(), 0);
+        assert!(format!("{inherit_none:?}").contains("env -i"));
+
+        let allow = build(EnvPolicy::Allowlist(vec!["PATH".into()]));
+        let keys: Vec<_> = allow.as_std().get_envs().map(|(k, _)| k).collect();
+        assert_eq!(keys, [std::ffi::OsStr::new("PATH")]);
+
+        let deny = build(EnvPolicy::Denylist(vec!["PATH".into()]));
+        assert!(deny.as_std().get_envs().all(|(k, _)| k != "PATH"));
+        assert!(format!("{deny:?}").contains("env -i"));
+    }
+
+    #[test]
+    fn native_has_no_default_env_additions() {
+        let runtime = NativeRuntime::new();
+        assert!(runtime.default_env_additions().is_empty());
+        let command = runtime
+            .build_shell_command("true", &std::env::temp_dir())
+            .unwrap();
+        assert_eq!(command.as_std().get_envs().count(), 0);
+    }
+
+    #[test]
+    fn native_max_concurrent_processes_is_configurable() {
+        assert!(NativeRuntime::new().max_concurrent_processes() >= 1);
+        let runtime = NativeRuntime::new().with_max_concurrent_processes(2);
+        assert_eq!(runtime.max_concurrent_processes(), 2);
+        let runtime = NativeRuntime::new().with_max_concurrent_processes(0);
+        assert_eq!(runtime.max_concurrent_processes(), 1);
+    }
+
     #[test]
     fn native_name() {
         assert_eq!(NativeRuntime::new().name(), "native");
@@ -96,6 +839,19 @@ mod tests {
         assert!(path.to_string_lossy().contains("R.A.I.N."));
     }
 
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn native_shell_program_is_sh() {
+        assert_eq!(NativeRuntime::new().shell_program().as_deref(), Some("sh"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn native_shell_program_is_powershell() {
+        let shell = NativeRuntime::new().shell_program().unwrap();
+        assert!(shell.contains("powershell"));
+    }
+
     #[test]
     fn native_builds_shell_command() {
         let cwd = std::env::temp_dir();
@@ -105,4 +861,211 @@ mod tests {
         let debug = format!("{command:?}");
         assert!(debug.contains("echo hello"));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn native_umask_applies_to_created_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let status = NativeRuntime::new()
+            .with_umask(0o077)
+            .build_shell_command("touch created.txt", tmp.path())
+            .unwrap()
+            .status()
+            .await
+            .unwrap();
+        assert!(status.success());
+
+        let mode = std::fs::metadata(tmp.path().join("created.txt"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn native_custom_shell_uses_bash() {
+        let runtime = NativeRuntime::new().with_shell("bash");
+        let command = runtime
+            .build_shell_command("echo ${BASH_VERSION}", &std::env::temp_dir())
+            .unwrap();
+        let program = command.as_std().get_program().to_string_lossy().to_string();
+        assert!(program.ends_with("bash"), "unexpected program: {program}");
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(args, ["-c", "echo ${BASH_VERSION}"]);
+        assert_eq!(runtime.shell_program().as_deref(), Some("bash"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn native_pipefail_reports_failing_first_stage() {
+        let cwd = std::env::temp_dir();
+        let run = |runtime: NativeRuntime| {
+            let cwd = cwd.clone();
+            async move { crate::runtime::run_command(&runtime, "false | true", &cwd).await }
+        };
+
+        let strict = run(NativeRuntime::new().with_shell("bash").with_pipefail(true)).await;
+        assert_eq!(strict.exit_code, Some(1));
+        let lenient = run(NativeRuntime::new().with_shell("bash")).await;
+        assert_eq!(lenient.exit_code, Some(0));
+    }
+
+    #[test]
+    fn native_invalid_shell_is_structured_error() {
+        let missing = std::env::temp_dir().join("definitely-missing-shell-xyz");
+        let err = NativeRuntime::new()
+            .with_shell(&missing)
+            .build_shell_command("echo hi", &std::env::temp_dir())
+            .unwrap_err();
+        match err.downcast_ref::<NativeRuntimeError>() {
+            Some(NativeRuntimeError::ShellNotFound { shell }) => assert_eq!(shell, &missing),
+            None => panic!("expected ShellNotFound, got: {err}"),
+        }
+    }
+
+    #[test]
+    fn shell_command_flag_matches_shell_family() {
+        assert_eq!(shell_command_flag(Path::new("/bin/zsh")), "-c");
+        assert_eq!(shell_command_flag(Path::new("cmd.exe")), "/C");
+        assert_eq!(shell_command_flag(Path::new("pwsh")), "-Command");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn native_login_shell_adds_l_flag_only_when_enabled() {
+        let cwd = std::env::temp_dir();
+        let args = |runtime: NativeRuntime| -> Vec<String> {
+            runtime
+                .build_shell_command("echo hi", &cwd)
+                .unwrap()
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            args(NativeRuntime::new().with_login_shell(true)),
+            ["-l", "-c", "echo hi"]
+        );
+        assert_eq!(
+            args(NativeRuntime::new().with_login_shell(false)),
+            ["-c", "echo hi"]
+        );
+        assert_eq!(
+            args(NativeRuntime::new().with_shell("sh").with_login_shell(true)),
+            ["-l", "-c", "echo hi"]
+        );
+    }
+
+    #[test]
+    fn shell_quote_leaves_plain_words_and_quotes_the_rest() {
+        assert_eq!(shell_quote("src/main.rs"), "src/main.rs");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+    }
+
+    #[test]
+    fn native_build_from_argv_execs_without_shell() {
+        let argv = ["echo".to_string(), "a b".to_string()];
+        let command = NativeRuntime::new()
+            .build_from_argv(&argv, &std::env::temp_dir())
+            .unwrap();
+        assert_eq!(command.as_std().get_program(), "echo");
+        assert_eq!(
+            command.as_std().get_args().collect::<Vec<_>>(),
+            [std::ffi::OsStr::new("a b")]
+        );
+    }
+
+    #[test]
+    fn native_resolve_program_searches_path() {
+        let runtime = NativeRuntime::new();
+        let workspace = std::env::temp_dir();
+        let shell = if cfg!(target_os = "windows") {
+            "cmd"
+        } else {
+            "sh"
+        };
+        let resolved = runtime.resolve_program(shell, &workspace).unwrap();
+        assert!(resolved.is_absolute());
+        assert_eq!(
+            runtime.resolve_program("definitely-not-a-real-program-xyz", &workspace),
+            None
+        );
+    }
+
+    #[test]
+    fn native_available_interpreters_match_path() {
+        let runtime = NativeRuntime::new();
+        let available = runtime.available_interpreters(&std::env::temp_dir());
+        for name in crate::runtime::KNOWN_INTERPRETERS {
+            assert_eq!(
+                available.iter().any(|found| found == name),
+                which::which(name).is_ok(),
+                "{name}"
+            );
+        }
+        assert!(
+            !available
+                .iter()
+                .any(|found| found == "definitely-not-a-real-program-xyz")
+        );
+    }
+
+    #[test]
+    fn native_path_style_matches_host() {
+        let style = NativeRuntime::new().path_style();
+        assert_eq!(style, crate::runtime::PathStyle::HOST);
+        assert_eq!(style.separator, std::path::MAIN_SEPARATOR);
+        assert_eq!(
+            style.case_sensitive,
+            cfg!(not(any(windows, target_os = "macos")))
+        );
+    }
+
+    #[test]
+    fn native_translate_path_is_identity() {
+        let workspace = Path::new("/srv/agent");
+        let path = Path::new("/srv/agent/src/main.rs");
+        assert_eq!(NativeRuntime::new().translate_path(path, workspace), path);
+    }
+
+    #[test]
+    fn workspace_placeholder_is_quoted_per_shell() {
+        let workspace = Path::new("/tmp/my work's");
+        assert_eq!(
+            expand_workspace_placeholder("ls {{workspace}}", workspace, Path::new("sh")),
+            r"ls '/tmp/my work'\''s'"
+        );
+        assert_eq!(
+            expand_workspace_placeholder("dir {{workspace}}", workspace, Path::new("pwsh")),
+            "dir '/tmp/my work''s'"
+        );
+        assert_eq!(
+            expand_workspace_placeholder("echo hi", workspace, Path::new("sh")),
+            "echo hi"
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn workspace_placeholder_with_spaces_is_cmd_quoted() {
+        let workspace = PathBuf::from(r"C:\Users\Jane Doe\agent workspace");
+        let command = NativeRuntime::new()
+            .with_shell("cmd.exe")
+            .build_shell_command("dir {{workspace}}", &workspace)
+            .unwrap();
+        let args: Vec<String> = command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(args, ["/C", r#"dir "C:\Users\Jane Doe\agent workspace""#]);
+    }
 }
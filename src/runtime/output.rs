@@ -0,0 +1,74 @@
+//! Streamed command output types shared by execution helpers and consumers.
+
+/// Which pipe a chunk of command output was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+        }
+    }
+}
+
+/// A line (or partial line) of output from a running command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputChunk {
+    pub stream: OutputStream,
+    pub text: String,
+    /// The line continues in the next chunk because it exceeded the
+    /// streaming line-length limit.
+    pub continued: bool,
+}
+
+impl OutputChunk {
+    pub fn stdout(text: impl Into<String>) -> Self {
+        Self {
+            stream: OutputStream::Stdout,
+            text: text.into(),
+            continued: false,
+        }
+    }
+
+    pub fn stderr(text: impl Into<String>) -> Self {
+        Self {
+            stream: OutputStream::Stderr,
+            text: text.into(),
+            continued: false,
+        }
+    }
+}
+
+/// Decode command output as UTF-8, replacing invalid sequences.
+///
+/// The flag is `true` when any replacement happened, which usually means the
+/// output is binary or in a legacy encoding and should be flagged to the user
+/// rather than shown as-is.
+pub fn decode_output_lossy(bytes: &[u8]) -> (String, bool) {
+    match String::from_utf8_lossy(bytes) {
+        std::borrow::Cow::Borrowed(text) => (text.to_string(), false),
+        std::borrow::Cow::Owned(text) => (text, true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_output_lossy_flags_replacement() {
+        assert_eq!(
+            decode_output_lossy(b"plain text"),
+            ("plain text".into(), false)
+        );
+
+        let (text, replaced) = decode_output_lossy(b"abc\xff\xfedef");
+        assert!(replaced);
+        assert_eq!(text, "abc\u{fffd}\u{fffd}def");
+    }
+}
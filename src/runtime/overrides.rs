@@ -0,0 +1,162 @@
+//! Per-host runtime defaults layered over the `[runtime]` config section.
+//!
+//! Container runtimes need settings such as a base image that differ between
+//! machines more than between projects. They can be kept in
+//! `<storage>/runtime.toml`, which uses the same shape as `[runtime]`, and
+//! individual values can be forced with `rain_RUNTIME_*` environment
+//! variables. Precedence is environment, then `runtime.toml`, then the main
+//! config; anything left unset keeps its usual default.
+
+use crate::config::RuntimeConfig;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// File name of the runtime defaults inside the host storage directory.
+pub const RUNTIME_CONFIG_FILE: &str = "runtime.toml";
+
+/// Resolve the effective runtime config for `base`.
+///
+/// `env` looks up environment variables; pass `|key| std::env::var(key).ok()`
+/// outside tests. A missing `runtime.toml` is not an error.
+pub fn load_runtime_config(
+    base: &RuntimeConfig,
+    storage_dir: &Path,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<RuntimeConfig> {
+    let path = storage_dir.join(RUNTIME_CONFIG_FILE);
+    let mut config = match std::fs::read_to_string(&path) {
+        Ok(raw) => merge_file(base, &raw)
+            .with_context(|| format!("Failed to parse runtime defaults {}", path.display()))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => base.clone(),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to read runtime defaults {}", path.display()));
+        }
+    };
+    apply_env_overrides(&mut config, env)?;
+    Ok(config)
+}
+
+/// Overlay the TOML document `raw` onto `base`, key by key.
+fn merge_file(base: &RuntimeConfig, raw: &str) -> Result<RuntimeConfig> {
+    let overlay: toml::Table = toml::from_str(raw)?;
+    let mut merged = toml::Table::try_from(base)?;
+    merge_tables(&mut merged, overlay);
+    Ok(merged.try_into()?)
+}
+
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(nested)) => {
+                merge_tables(existing, nested);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn apply_env_overrides(
+    config: &mut RuntimeConfig,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<()> {
+    let var = |key: &str| env(key).filter(|value| !value.trim().is_empty());
+
+    if let Some(kind) = var("rain_RUNTIME_KIND") {
+        config.kind = kind.trim().to_string();
+    }
+    if let Some(image) = var("rain_RUNTIME_DOCKER_IMAGE") {
+        config.docker.image = image.trim().to_string();
+    }
+    if let Some(network) = var("rain_RUNTIME_DOCKER_NETWORK") {
+        config.docker.network = network.trim().to_string();
+    }
+    if let Some(memory) = var("rain_RUNTIME_DOCKER_MEMORY_LIMIT_MB") {
+        let mb = memory
+            .trim()
+            .parse::<u64>()
+            .with_context(|| format!("Invalid rain_RUNTIME_DOCKER_MEMORY_LIMIT_MB: {memory}"))?;
+        config.docker.memory_limit_mb = Some(mb);
+    }
+    if let Some(cpus) = var("rain_RUNTIME_DOCKER_CPU_LIMIT") {
+        let limit = cpus
+            .trim()
+            .parse::<f64>()
+            .with_context(|| format!("Invalid rain_RUNTIME_DOCKER_CPU_LIMIT: {cpus}"))?;
+        config.docker.cpu_limit = Some(limit);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn missing_file_keeps_base_config() {
+        let storage = tempfile::tempdir().unwrap();
+        let config =
+            load_runtime_config(&RuntimeConfig::default(), storage.path(), no_env).unwrap();
+        assert_eq!(config.kind, "native");
+        assert_eq!(config.docker.image, "alpine:3.20");
+    }
+
+    #[test]
+    fn file_overrides_only_the_keys_it_sets() {
+        let storage = tempfile::tempdir().unwrap();
+        std::fs::write(
+            storage.path().join(RUNTIME_CONFIG_FILE),
+            "kind = \"docker\"\n\n[docker]\nimage = \"python:3.12-slim\"\n",
+        )
+        .unwrap();
+
+        let config =
+            load_runtime_config(&RuntimeConfig::default(), storage.path(), no_env).unwrap();
+        assert_eq!(config.kind, "docker");
+        assert_eq!(config.docker.image, "python:3.12-slim");
+        assert_eq!(config.docker.network, "none");
+        assert_eq!(config.docker.memory_limit_mb, Some(512));
+    }
+
+    #[test]
+    fn env_wins_over_file() {
+        let storage = tempfile::tempdir().unwrap();
+        std::fs::write(
+            storage.path().join(RUNTIME_CONFIG_FILE),
+            "[docker]\nimage = \"python:3.12-slim\"\n",
+        )
+        .unwrap();
+        let env = |key: &str| match key {
+            "rain_RUNTIME_DOCKER_IMAGE" => Some("node:22".to_string()),
+            "rain_RUNTIME_DOCKER_MEMORY_LIMIT_MB" => Some("1024".to_string()),
+            _ => None,
+        };
+
+        let config = load_runtime_config(&RuntimeConfig::default(), storage.path(), env).unwrap();
+        assert_eq!(config.docker.image, "node:22");
+        assert_eq!(config.docker.memory_limit_mb, Some(1024));
+    }
+
+    #[test]
+    fn invalid_file_or_env_is_reported() {
+        let storage = tempfile::tempdir().unwrap();
+        std::fs::write(storage.path().join(RUNTIME_CONFIG_FILE), "kind = [").unwrap();
+        let err = load_runtime_config(&RuntimeConfig::default(), storage.path(), no_env)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Failed to parse runtime defaults"));
+
+        let empty = tempfile::tempdir().unwrap();
+        let env = |key: &str| (key == "rain_RUNTIME_DOCKER_CPU_LIMIT").then(|| "lots".to_string());
+        let err = load_runtime_config(&RuntimeConfig::default(), empty.path(), env)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("rain_RUNTIME_DOCKER_CPU_LIMIT"));
+    }
+}
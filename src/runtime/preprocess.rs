@@ -0,0 +1,165 @@
+//! Command preprocessors applied before a runtime builds a shell command.
+//!
+//! Deployments differ in how commands should be wrapped (`nice`, strict shell
+//! options, timing). A runtime exposes an ordered list through
+//! [`RuntimeAdapter::preprocessors`](super::RuntimeAdapter::preprocessors) and
+//! [`RuntimeAdapter::preprocess_command`](super::RuntimeAdapter::preprocess_command)
+//! folds the command through it, first to last.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+/// A transformation applied to a command string before execution.
+pub trait CommandPreprocessor: Send + Sync + Debug {
+    /// Short identifier for logs and diagnostics.
+    fn name(&self) -> &str;
+
+    /// Return the transformed command.
+    fn process(&self, command: &str) -> String;
+}
+
+/// Makes a pipeline fail when any stage fails, not just the last one.
+///
+/// Built [`for_shell`](Self::for_shell), the prelude suits that shell: bash,
+/// zsh and ksh get `set -o pipefail`, other POSIX shells (plain `sh`
+/// included) are probed once for the option, and shells without it (`cmd.exe`,
+/// PowerShell, older dash) log a warning and run commands unchanged. Without a
+/// shell the prelude is guarded so shells lacking the option still run the
+/// command.
+#[derive(Debug, Clone, Default)]
+pub struct PipefailPreprocessor {
+    shell: Option<PathBuf>,
+}
+
+impl PipefailPreprocessor {
+    /// A preprocessor for whatever shell ends up running the command.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A preprocessor for commands run by `shell`.
+    pub fn for_shell(shell: impl Into<PathBuf>) -> Self {
+        Self {
+            shell: Some(shell.into()),
+        }
+    }
+}
+
+impl CommandPreprocessor for PipefailPreprocessor {
+    fn name(&self) -> &str {
+        "pipefail"
+    }
+
+    fn process(&self, command: &str) -> String {
+        let Some(shell) = &self.shell else {
+            // POSIX only added pipefail in 2024; probe in a subshell first so
+            // older shells run the command instead of aborting on `set`.
+            return format!("(set -o pipefail) 2>/dev/null && set -o pipefail; {command}");
+        };
+        if shell_supports_pipefail(shell) {
+            format!("set -o pipefail; {command}")
+        } else {
+            command.to_string()
+        }
+    }
+}
+
+/// Whether `shell` accepts `set -o pipefail`, warning once per shell when it
+/// does not.
+fn shell_supports_pipefail(shell: &Path) -> bool {
+    static SUPPORT: OnceLock<Mutex<HashMap<PathBuf, bool>>> = OnceLock::new();
+
+    let mut support = SUPPORT.get_or_init(Mutex::default).lock();
+    *support.entry(shell.to_path_buf()).or_insert_with(|| {
+        let stem = shell
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let supported = match stem.as_str() {
+            "bash" | "zsh" | "ksh" | "mksh" => true,
+            "cmd" | "powershell" | "pwsh" => false,
+            _ => std::process::Command::new(shell)
+                .args(["-c", "set -o pipefail"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success()),
+        };
+        if !supported {
+            tracing::warn!(
+                shell = %shell.display(),
+                "shell has no pipefail; pipelines report the last stage's status"
+            );
+        }
+        supported
+    })
+}
+
+/// Wraps the command in the shell's `time` keyword so its duration is
+/// reported on stderr.
+///
+/// Requires a shell where `time` is a keyword (bash, zsh, ksh).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimingPreprocessor;
+
+impl CommandPreprocessor for TimingPreprocessor {
+    fn name(&self) -> &str {
+        "timing"
+    }
+
+    fn process(&self, command: &str) -> String {
+        format!("time (\n{command}\n)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{NativeRuntime, RuntimeAdapter};
+    use std::sync::Arc;
+
+    #[test]
+    fn pipefail_prelude_suits_the_shell() {
+        assert_eq!(
+            PipefailPreprocessor::new().process("false | true"),
+            "(set -o pipefail) 2>/dev/null && set -o pipefail; false | true"
+        );
+        assert_eq!(
+            PipefailPreprocessor::for_shell("/bin/bash").process("a | b"),
+            "set -o pipefail; a | b"
+        );
+        assert_eq!(
+            PipefailPreprocessor::for_shell("cmd.exe").process("a | b"),
+            "a | b"
+        );
+        // A plain `sh` that cannot be run is treated as lacking the option.
+        let missing = std::env::temp_dir().join("definitely-missing-sh-xyz/sh");
+        assert_eq!(
+            PipefailPreprocessor::for_shell(missing).process("a | b"),
+            "a | b"
+        );
+    }
+
+    #[test]
+    fn preprocessors_compose_in_order() {
+        let runtime = NativeRuntime::new()
+            .with_preprocessor(Arc::new(PipefailPreprocessor::for_shell("bash")))
+            .with_preprocessor(Arc::new(TimingPreprocessor));
+        let names: Vec<_> = runtime.preprocessors().iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["pipefail", "timing"]);
+        assert_eq!(
+            runtime.preprocess_command("make"),
+            "time (\nset -o pipefail; make\n)"
+        );
+    }
+
+    #[test]
+    fn no_preprocessors_leaves_command_untouched() {
+        assert_eq!(NativeRuntime::new().preprocess_command("ls -la"), "ls -la");
+    }
+}
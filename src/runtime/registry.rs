@@ -0,0 +1,157 @@
+//! Registry of running commands, so operators can list and kill them.
+//!
+//! Execution helpers register each process they spawn and hold the returned
+//! [`ProcessGuard`] until it exits; dropping the guard removes the entry.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use tokio::sync::Notify;
+
+/// A running command as shown to operators.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub id: u64,
+    pub command: String,
+    pub runtime: String,
+    pub started_at: DateTime<Utc>,
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    info: ProcessInfo,
+    kill: Arc<Notify>,
+}
+
+/// Tracks running commands by id.
+#[derive(Debug, Default)]
+pub struct ProcessRegistry {
+    next_id: AtomicU64,
+    entries: Mutex<HashMap<u64, Entry>>,
+}
+
+static GLOBAL: LazyLock<ProcessRegistry> = LazyLock::new(ProcessRegistry::default);
+
+impl ProcessRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry used by the execution helpers and gateway.
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// Record a spawned command; it stays listed until the guard is dropped.
+    pub fn register(&self, command: &str, runtime: &str, pid: Option<u32>) -> ProcessGuard<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let kill = Arc::new(Notify::new());
+        let info = ProcessInfo {
+            id,
+            command: command.to_string(),
+            runtime: runtime.to_string(),
+            started_at: Utc::now(),
+            pid,
+        };
+        self.entries.lock().insert(
+            id,
+            Entry {
+                info,
+                kill: Arc::clone(&kill),
+            },
+        );
+        ProcessGuard {
+            registry: self,
+            id,
+            kill,
+        }
+    }
+
+    /// Running commands, oldest first.
+    pub fn list(&self) -> Vec<ProcessInfo> {
+        let mut processes: Vec<ProcessInfo> = self
+            .entries
+            .lock()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        processes.sort_by_key(|info| info.id);
+        processes
+    }
+
+    /// Ask the owner of command `id` to kill it. Returns `false` if no such
+    /// command is running.
+    ///
+    /// The entry is removed once the owner has reaped the process.
+    pub fn kill(&self, id: u64) -> bool {
+        match self.entries.lock().get(&id) {
+            Some(entry) => {
+                entry.kill.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Keeps a command listed in its [`ProcessRegistry`] while it runs.
+#[derive(Debug)]
+pub struct ProcessGuard<'a> {
+    registry: &'a ProcessRegistry,
+    id: u64,
+    kill: Arc<Notify>,
+}
+
+impl ProcessGuard<'_> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Resolve once an operator asks for this command to be killed.
+    pub async fn killed(&self) {
+        self.kill.notified().await;
+    }
+}
+
+impl Drop for ProcessGuard<'_> {
+    fn drop(&mut self) {
+        self.registry.entries.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn registry_lists_until_guard_dropped() {
+        let registry = ProcessRegistry::new();
+        let guard = registry.register("sleep 10", "native", Some(42));
+
+        let listed = registry.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, guard.id());
+        assert_eq!(listed[0].command, "sleep 10");
+        assert_eq!(listed[0].pid, Some(42));
+
+        drop(guard);
+        assert!(registry.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn registry_kill_notifies_owner() {
+        let registry = ProcessRegistry::new();
+        let guard = registry.register("sleep 10", "native", None);
+
+        assert!(registry.kill(guard.id()));
+        tokio::time::timeout(Duration::from_secs(1), guard.killed())
+            .await
+            .expect("kill request should wake the owner");
+        assert!(!registry.kill(guard.id() + 1));
+    }
+}
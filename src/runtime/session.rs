@@ -0,0 +1,352 @@
+//! Record command sessions and replay them without executing anything.
+//!
+//! [`RecordingRuntime`] appends every command result to a JSON Lines session
+//! file, whether it ran through [`run_command`](super::run_command),
+//! [`run_to_file`](super::run_to_file), [`spawn_streaming`](super::spawn_streaming)
+//! or the shell tool; [`ReplayRuntime`] loads that file
+//! and answers the same commands from it, so an agent run can be reproduced
+//! deterministically on another machine.
+
+use super::decorators::forward_to_inner;
+use super::exec::{CommandResult, TimeoutReason};
+use super::traits::RuntimeAdapter;
+use anyhow::Context;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// One line of a session file.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionEntry {
+    command: String,
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    /// [`TimeoutReason::as_str`], if the command timed out.
+    timed_out: Option<String>,
+    duration: Duration,
+}
+
+impl From<&CommandResult> for SessionEntry {
+    fn from(result: &CommandResult) -> Self {
+        Self {
+            command: result.command.clone(),
+            exit_code: result.exit_code,
+            stdout: result.stdout.clone(),
+            stderr: result.stderr.clone(),
+            timed_out: result.timed_out.map(|reason| reason.as_str().to_string()),
+            duration: result.duration,
+        }
+    }
+}
+
+impl From<SessionEntry> for CommandResult {
+    fn from(entry: SessionEntry) -> Self {
+        let timed_out = match entry.timed_out.as_deref() {
+            Some("idle") => Some(TimeoutReason::Idle),
+            Some(_) => Some(TimeoutReason::Overall),
+            None => None,
+        };
+        Self {
+            command: entry.command,
+            exit_code: entry.exit_code,
+            stdout: entry.stdout,
+            stderr: entry.stderr,
+            timed_out,
+            duration: entry.duration,
+        }
+    }
+}
+
+/// Runtime decorator that writes each command result to a session file.
+///
+/// Only results that pass through [`run_command`](super::run_command) (and
+/// so [`run_batch`](super::run_batch)) are recorded.
+#[derive(Debug)]
+pub struct RecordingRuntime<R: RuntimeAdapter> {
+    inner: R,
+    path: PathBuf,
+    file: Mutex<std::fs::File>,
+}
+
+impl<R: RuntimeAdapter> RecordingRuntime<R> {
+    /// Record `inner`'s commands to `path`, replacing any existing session.
+    pub fn new(inner: R, path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create session file {}", path.display()))?;
+        Ok(Self {
+            inner,
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Borrow the wrapped adapter.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Where the session is being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<R: RuntimeAdapter> RuntimeAdapter for RecordingRuntime<R> {
+    forward_to_inner!(
+        name,
+        has_shell_access,
+        has_filesystem_access,
+        storage_path,
+        supports_long_running,
+        has_network_access,
+        has_writable_tmp,
+        storage_available_bytes,
+        memory_budget,
+        default_command_timeout,
+        max_concurrent_processes,
+        graceful_signal,
+        path_style,
+        max_command_length,
+        shell_program,
+        preprocessors,
+        preprocess_command,
+        health_probe,
+        replayed_result,
+        translate_path,
+        resolve_program,
+        available_interpreters,
+        effective_environment,
+        default_env_additions,
+        build_shell_command,
+        build_exec_command,
+        build_from_argv,
+        build_interpreted_command,
+    );
+
+    fn observe_result(&self, result: &CommandResult) {
+        self.inner.observe_result(result);
+        let line = match serde_json::to_string(&SessionEntry::from(result)) {
+            Ok(line) => line,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to serialize session entry");
+                return;
+            }
+        };
+        if let Err(err) = writeln!(self.file.lock(), "{line}") {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %err,
+                "failed to record command result"
+            );
+        }
+    }
+}
+
+/// Runtime that answers commands from a recorded session instead of running them.
+///
+/// Repeated commands replay their recorded results in order; once those run
+/// out, the last one is repeated. Commands missing from the session fail to
+/// build, so nothing is ever executed.
+#[derive(Debug, Default)]
+pub struct ReplayRuntime {
+    results: Mutex<HashMap<String, VecDeque<CommandResult>>>,
+}
+
+impl ReplayRuntime {
+    /// Load a session file written by [`RecordingRuntime`].
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read session file {}", path.display()))?;
+        let mut results: HashMap<String, VecDeque<CommandResult>> = HashMap::new();
+        for (index, line) in raw.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: SessionEntry = serde_json::from_str(line).with_context(|| {
+                format!(
+                    "Invalid session entry on line {} of {}",
+                    index + 1,
+                    path.display()
+                )
+            })?;
+            results
+                .entry(entry.command.clone())
+                .or_default()
+                .push_back(entry.into());
+        }
+        Ok(Self {
+            results: Mutex::new(results),
+        })
+    }
+
+    /// Number of distinct commands in the session.
+    pub fn len(&self) -> usize {
+        self.results.lock().len()
+    }
+
+    /// Whether the session recorded no commands.
+    pub fn is_empty(&self) -> bool {
+        self.results.lock().is_empty()
+    }
+}
+
+impl RuntimeAdapter for ReplayRuntime {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn has_shell_access(&self) -> bool {
+        true
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        false
+    }
+
+    fn storage_path(&self) -> PathBuf {
+        std::env::temp_dir().join("rain-replay-runtime")
+    }
+
+    fn supports_long_running(&self) -> bool {
+        false
+    }
+
+    fn effective_environment(&self, _workspace_dir: &Path) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn replayed_result(&self, command: &str) -> Option<CommandResult> {
+        let mut results = self.results.lock();
+        let queue = results.get_mut(command)?;
+        if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        }
+    }
+
+    fn build_shell_command(
+        &self,
+        command: &str,
+        _workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        anyhow::bail!("no recorded result for command `{command}` in this replay session")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{NativeRuntime, run_command};
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn replay_returns_recorded_results_without_running() {
+        let workspace = tempfile::tempdir().unwrap();
+        let session = workspace.path().join("session.jsonl");
+        let commands = [
+            "echo run >> runs.log && echo hello",
+            "echo oops >&2; exit 3",
+        ];
+
+        let recorder = RecordingRuntime::new(NativeRuntime::new(), &session).unwrap();
+        let mut recorded = Vec::new();
+        for command in commands {
+            recorded.push(run_command(&recorder, command, workspace.path()).await);
+        }
+        drop(recorder);
+
+        let replay = ReplayRuntime::load(&session).unwrap();
+        assert_eq!(replay.len(), 2);
+        for (command, expected) in commands.iter().zip(&recorded) {
+            let replayed = run_command(&replay, command, workspace.path()).await;
+            assert_eq!(&replayed, expected);
+        }
+        assert_eq!(recorded[1].exit_code, Some(3));
+
+        // The side effect happened once, while recording.
+        let runs = std::fs::read_to_string(workspace.path().join("runs.log")).unwrap();
+        assert_eq!(runs.lines().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn streaming_and_file_runs_are_recorded_and_replayed() {
+        use crate::runtime::{StreamOptions, run_to_file, spawn_streaming};
+
+        let workspace = tempfile::tempdir().unwrap();
+        let session = workspace.path().join("session.jsonl");
+        let streamed = "echo run >> runs.log && printf 'a\\nb\\n'";
+        let to_file = "echo run >> runs.log && exit 4";
+        let options = StreamOptions::default();
+
+        let recorder = RecordingRuntime::new(NativeRuntime::new(), &session).unwrap();
+        let recorded =
+            spawn_streaming(&recorder, streamed, workspace.path(), &options, |_| {}).await;
+        let out_path = workspace.path().join("out.log");
+        let filed = run_to_file(&recorder, to_file, workspace.path(), &out_path).await;
+        drop(recorder);
+        assert_eq!(filed.exit_code, Some(4));
+
+        let replay = ReplayRuntime::load(&session).unwrap();
+        let mut lines = Vec::new();
+        let replayed = spawn_streaming(&replay, streamed, workspace.path(), &options, |chunk| {
+            lines.push(chunk.text.clone());
+        })
+        .await;
+        assert_eq!(replayed, recorded);
+        assert_eq!(lines, ["a\n", "b\n"]);
+        let replayed = run_to_file(&replay, to_file, workspace.path(), &out_path).await;
+        assert_eq!(replayed, filed);
+
+        // Both side effects happened once, while recording.
+        let runs = std::fs::read_to_string(workspace.path().join("runs.log")).unwrap();
+        assert_eq!(runs.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn replay_refuses_unrecorded_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("session.jsonl");
+        std::fs::write(&session, "").unwrap();
+
+        let replay = ReplayRuntime::load(&session).unwrap();
+        assert!(replay.is_empty());
+
+        let result = run_command(&replay, "rm -rf /", dir.path()).await;
+        assert_eq!(result.exit_code, None);
+        assert!(result.stderr.contains("no recorded result"));
+    }
+
+    #[test]
+    fn replay_repeats_the_last_result_for_a_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("session.jsonl");
+        let lines: Vec<String> = ["first", "second"]
+            .iter()
+            .map(|stdout| {
+                serde_json::to_string(&SessionEntry {
+                    command: "date".into(),
+                    exit_code: Some(0),
+                    stdout: (*stdout).into(),
+                    stderr: String::new(),
+                    timed_out: None,
+                    duration: Duration::from_millis(5),
+                })
+                .unwrap()
+            })
+            .collect();
+        std::fs::write(&session, lines.join("\n")).unwrap();
+
+        let replay = ReplayRuntime::load(&session).unwrap();
+        let stdout = |replay: &ReplayRuntime| replay.replayed_result("date").unwrap().stdout;
+        assert_eq!(stdout(&replay), "first");
+        assert_eq!(stdout(&replay), "second");
+        assert_eq!(stdout(&replay), "second");
+    }
+}
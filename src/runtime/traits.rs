@@ -1,4 +1,129 @@
+use super::exec::CommandResult;
+use super::preprocess::CommandPreprocessor;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Errors any runtime may raise while preparing a command.
+#[derive(Debug, thiserror::Error)]
+pub enum RuntimeError {
+    /// The command exceeds [`RuntimeAdapter::max_command_length`].
+    #[error("command is {length} characters long, exceeding this runtime's limit of {limit}")]
+    CommandTooLong { length: usize, limit: usize },
+    /// The runtime lacks a capability the operation needs.
+    #[error("this runtime ({runtime}) does not support {capability}; {}", capability.remedy())]
+    Unsupported {
+        runtime: String,
+        capability: Capability,
+    },
+}
+
+/// [`RuntimeAdapter::graceful_signal`] unless a runtime overrides it.
+#[cfg(unix)]
+const DEFAULT_GRACEFUL_SIGNAL: Option<i32> = Some(libc::SIGTERM);
+#[cfg(not(unix))]
+const DEFAULT_GRACEFUL_SIGNAL: Option<i32> = None;
+
+/// One-liner interpreters [`RuntimeAdapter::available_interpreters`] probes for.
+pub const KNOWN_INTERPRETERS: &[&str] = &[
+    "python3", "python", "node", "deno", "bun", "ruby", "perl", "php", "lua",
+];
+
+/// Path conventions commands see in a runtime; see
+/// [`RuntimeAdapter::path_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathStyle {
+    /// Separator between path components.
+    pub separator: char,
+    /// Whether names differing only in case refer to different files.
+    pub case_sensitive: bool,
+}
+
+impl PathStyle {
+    /// Linux and other Unix filesystems: `/`, case-sensitive.
+    pub const POSIX: Self = Self {
+        separator: '/',
+        case_sensitive: true,
+    };
+
+    /// Windows: `\`, case-insensitive.
+    pub const WINDOWS: Self = Self {
+        separator: '\\',
+        case_sensitive: false,
+    };
+
+    /// The conventions of the machine this binary runs on. macOS volumes are
+    /// case-insensitive by default.
+    pub const HOST: Self = if cfg!(windows) {
+        Self::WINDOWS
+    } else if cfg!(target_os = "macos") {
+        Self {
+            separator: '/',
+            case_sensitive: false,
+        }
+    } else {
+        Self::POSIX
+    };
+}
+
+/// A capability a runtime may lack; checked with [`require_capability`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Shell,
+    Filesystem,
+    LongRunning,
+    Network,
+    WritableTmp,
+}
+
+impl Capability {
+    /// How the user can get a runtime that has this capability.
+    fn remedy(self) -> &'static str {
+        match self {
+            Self::Network => "switch to native, or give docker a network other than none",
+            Self::WritableTmp => "set runtime.docker.writable_tmp, or disable read_only_rootfs",
+            Self::Shell | Self::Filesystem | Self::LongRunning => "switch to native or docker",
+        }
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Shell => "shell execution",
+            Self::Filesystem => "filesystem access",
+            Self::LongRunning => "long-running processes",
+            Self::Network => "network access",
+            Self::WritableTmp => "a writable /tmp",
+        })
+    }
+}
+
+/// Fail with an actionable [`RuntimeError::Unsupported`] unless `adapter`
+/// has `capability`.
+///
+/// # Errors
+///
+/// Returns [`RuntimeError::Unsupported`] naming the runtime and capability.
+pub fn require_capability(
+    adapter: &dyn RuntimeAdapter,
+    capability: Capability,
+) -> Result<(), RuntimeError> {
+    let supported = match capability {
+        Capability::Shell => adapter.has_shell_access(),
+        Capability::Filesystem => adapter.has_filesystem_access(),
+        Capability::LongRunning => adapter.supports_long_running(),
+        Capability::Network => adapter.has_network_access(),
+        Capability::WritableTmp => adapter.has_writable_tmp(),
+    };
+    if supported {
+        return Ok(());
+    }
+    Err(RuntimeError::Unsupported {
+        runtime: adapter.name().to_string(),
+        capability,
+    })
+}
 
 /// Runtime adapter that abstracts platform differences for the agent.
 ///
@@ -43,6 +168,35 @@ pub trait RuntimeAdapter: Send + Sync {
     /// limits should return `false`.
     fn supports_long_running(&self) -> bool;
 
+    /// Report whether commands run by this runtime can reach the network.
+    ///
+    /// Defaults to `true`. Sandboxed runtimes should return `false` when
+    /// isolated so network-dependent tools can be disabled up front.
+    fn has_network_access(&self) -> bool {
+        true
+    }
+
+    /// Report whether commands can write scratch files under `/tmp` (the
+    /// platform temp directory, on hosts without one).
+    ///
+    /// Defaults to `true`. Sandboxes that mount a read-only root filesystem
+    /// should return `false` unless they provide a writable `/tmp`, so tools
+    /// needing scratch space can warn or be disabled up front.
+    fn has_writable_tmp(&self) -> bool {
+        true
+    }
+
+    /// Return the free bytes on the volume holding
+    /// [`storage_path`](Self::storage_path).
+    ///
+    /// Lets the agent refuse work before the storage volume fills up. The
+    /// default queries the host filesystem (`statvfs` / `GetDiskFreeSpaceExW`),
+    /// using the nearest existing ancestor if the path has not been created.
+    /// Runtimes whose storage path is not a host path return `None`.
+    fn storage_available_bytes(&self) -> Option<u64> {
+        super::disk::available_bytes(&self.storage_path())
+    }
+
     /// Return the maximum memory budget in bytes for this runtime.
     ///
     /// A value of `0` (the default) indicates no limit. Constrained
@@ -52,6 +206,189 @@ pub trait RuntimeAdapter: Send + Sync {
         0
     }
 
+    /// Return the timeout execution helpers should enforce on commands.
+    ///
+    /// `None` (the default) lets each caller apply its own limit. Runtimes
+    /// with a hard execution ceiling should return it so commands are killed
+    /// before the platform does it less gracefully.
+    fn default_command_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Return how many commands execution helpers may run at once.
+    ///
+    /// Defaults to the host's available parallelism. Batch helpers such as
+    /// [`run_batch`](super::run_batch) size their semaphore from this value.
+    fn max_concurrent_processes(&self) -> usize {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    }
+
+    /// Signal sent to a command's process group when it times out or is
+    /// cancelled, before escalating to `SIGKILL` after
+    /// [`TERMINATION_GRACE_PERIOD`](super::exec::TERMINATION_GRACE_PERIOD).
+    ///
+    /// Defaults to `SIGTERM` on Unix. Programs that only shut down cleanly on
+    /// `SIGINT` or `SIGHUP` can be given those instead. `None` (the default
+    /// elsewhere) kills immediately.
+    fn graceful_signal(&self) -> Option<i32> {
+        DEFAULT_GRACEFUL_SIGNAL
+    }
+
+    /// Return the path conventions of the filesystem commands run against.
+    ///
+    /// Lets callers build paths for the runtime rather than the host, e.g.
+    /// `/`-separated, case-sensitive paths for a Linux container driven from
+    /// Windows. Defaults to [`PathStyle::HOST`].
+    fn path_style(&self) -> PathStyle {
+        PathStyle::HOST
+    }
+
+    /// Return the longest command string this runtime can run, in characters.
+    ///
+    /// `None` (the default) means no practical limit. Runtimes with a hard
+    /// command-line ceiling (e.g. `cmd.exe`'s 8191 characters) report it so
+    /// [`build_shell_command`](Self::build_shell_command) can fail with
+    /// [`RuntimeError::CommandTooLong`] instead of silently truncating.
+    fn max_command_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return the shell program used to run commands, for display.
+    ///
+    /// Lets the dashboard show e.g. "running commands via sh" without
+    /// inspecting the opaque [`tokio::process::Command`]. Runtimes without
+    /// shell access return `None` (the default).
+    fn shell_program(&self) -> Option<String> {
+        None
+    }
+
+    /// Return the preprocessors applied to commands, in application order.
+    ///
+    /// Empty by default.
+    fn preprocessors(&self) -> &[Arc<dyn CommandPreprocessor>] {
+        &[]
+    }
+
+    /// Fold `command` through [`preprocessors`](Self::preprocessors), first to last.
+    ///
+    /// Execution helpers call this before
+    /// [`build_shell_command`](Self::build_shell_command).
+    fn preprocess_command(&self, command: &str) -> String {
+        self.preprocessors()
+            .iter()
+            .fold(command.to_string(), |command, preprocessor| {
+                preprocessor.process(&command)
+            })
+    }
+
+    /// Return a command whose success shows the runtime can run commands.
+    ///
+    /// Runtimes backed by a daemon (e.g. Docker) ping it here;
+    /// [`health_check`](super::health_check) runs the probe with a timeout.
+    /// `None` (the default) means the runtime is always ready.
+    fn health_probe(&self) -> Option<tokio::process::Command> {
+        None
+    }
+
+    /// A stored result to return for `command` instead of running it.
+    ///
+    /// [`run_command`](super::run_command), [`run_to_file`](super::run_to_file),
+    /// [`spawn_streaming`](super::spawn_streaming) and the shell tool consult
+    /// this before building a process; [`ReplayRuntime`](super::ReplayRuntime)
+    /// serves recorded sessions through it. `None` (the default) means run the
+    /// command.
+    fn replayed_result(&self, _command: &str) -> Option<CommandResult> {
+        None
+    }
+
+    /// Observe a result one of the execution paths that consult
+    /// [`replayed_result`](Self::replayed_result) produced.
+    ///
+    /// Called once per command, including replayed ones. The default does
+    /// nothing; [`RecordingRuntime`](super::RecordingRuntime) writes it to a
+    /// session file.
+    fn observe_result(&self, _result: &CommandResult) {}
+
+    /// Map `host_path` to the path commands in this runtime see it at.
+    ///
+    /// `workspace_dir` is the host workspace the command will run in, so
+    /// runtimes that mount it elsewhere (e.g. Docker at `/workspace`) can
+    /// rebase paths beneath it. Defaults to the identity mapping; paths the
+    /// runtime cannot see are returned unchanged.
+    fn translate_path(&self, host_path: &Path, _workspace_dir: &Path) -> PathBuf {
+        host_path.to_path_buf()
+    }
+
+    /// Locate `name` the way a command run by this runtime would find it.
+    ///
+    /// The default runs `command -v` through
+    /// [`build_shell_command`](Self::build_shell_command), so it sees the
+    /// runtime's own `PATH` (e.g. inside a container). It blocks until the
+    /// probe exits; call it from `spawn_blocking` in async code. Returns
+    /// `None` when the program is missing or the runtime has no shell.
+    fn resolve_program(&self, name: &str, workspace_dir: &Path) -> Option<PathBuf> {
+        if name.is_empty() || !self.has_shell_access() {
+            return None;
+        }
+        let command = format!("command -v {}", super::native::shell_quote(name));
+        let probe = self.build_shell_command(&command, workspace_dir).ok()?;
+        let probe = probe.as_std();
+        let mut process = std::process::Command::new(probe.get_program());
+        process
+            .args(probe.get_args())
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null());
+        if let Some(dir) = probe.get_current_dir() {
+            process.current_dir(dir);
+        }
+        for (key, value) in probe.get_envs() {
+            match value {
+                Some(value) => process.env(key, value),
+                None => process.env_remove(key),
+            };
+        }
+
+        let output = process
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let path = stdout.lines().next()?.trim();
+        (!path.is_empty()).then(|| PathBuf::from(path))
+    }
+
+    /// Return the [`KNOWN_INTERPRETERS`] that
+    /// [`resolve_program`](Self::resolve_program) finds, in list order.
+    ///
+    /// Probes where commands run, so container runtimes report what the
+    /// container has. Blocks for one probe per interpreter; call it from
+    /// `spawn_blocking` in async code.
+    fn available_interpreters(&self, workspace_dir: &Path) -> Vec<String> {
+        KNOWN_INTERPRETERS
+            .iter()
+            .filter(|name| self.resolve_program(name, workspace_dir).is_some())
+            .map(|name| (*name).to_string())
+            .collect()
+    }
+
+    /// Return the environment [`build_shell_command`](Self::build_shell_command)
+    /// gives commands, sorted by name with secret values masked.
+    ///
+    /// Meant for debugging why a command behaves differently than expected.
+    /// The default reports the server's own environment, which spawned
+    /// commands inherit unless the runtime filters it.
+    fn effective_environment(&self, _workspace_dir: &Path) -> Vec<(String, String)> {
+        super::env::masked_environment(std::env::vars())
+    }
+
+    /// Variables this runtime sets on every command, e.g. `CI=true`.
+    ///
+    /// Applied after the inherited environment has been filtered, so they
+    /// override inherited values of the same name. Empty by default.
+    fn default_env_additions(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
     /// Build a shell command process configured for this runtime.
     ///
     /// Constructs a [`tokio::process::Command`] that will execute `command`
@@ -68,6 +405,71 @@ pub trait RuntimeAdapter: Send + Sync {
         command: &str,
         workspace_dir: &Path,
     ) -> anyhow::Result<tokio::process::Command>;
+
+    /// Build a process that runs `argv` directly, without a shell.
+    ///
+    /// Returns `None` (the default) when the runtime can only run shell
+    /// strings, in which case [`build_from_argv`](Self::build_from_argv)
+    /// falls back to a quoted shell command.
+    fn build_exec_command(
+        &self,
+        _argv: &[String],
+        _workspace_dir: &Path,
+    ) -> Option<anyhow::Result<tokio::process::Command>> {
+        None
+    }
+
+    /// Build a process for a parsed `argv`, whichever way this runtime runs it.
+    ///
+    /// Uses [`build_exec_command`](Self::build_exec_command) when available;
+    /// otherwise joins `argv` with [`shell_quote`](super::native::shell_quote)
+    /// and passes the result to [`build_shell_command`](Self::build_shell_command).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `argv` is empty or the command cannot be built.
+    fn build_from_argv(
+        &self,
+        argv: &[String],
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        if argv.is_empty() {
+            anyhow::bail!("cannot build a command from an empty argv");
+        }
+        if let Some(process) = self.build_exec_command(argv, workspace_dir) {
+            return process;
+        }
+        let command = argv
+            .iter()
+            .map(|arg| super::native::shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.build_shell_command(&command, workspace_dir)
+    }
+
+    /// Build a process that runs `code` with `interpreter`, e.g.
+    /// `python -c '<code>'` or `node -e '<code>'`, instead of the default shell.
+    ///
+    /// Goes through [`build_from_argv`](Self::build_from_argv), so `code` is
+    /// passed as a single argument on runtimes that exec directly and quoted
+    /// for the runtime's own shell (e.g. inside a container) otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `interpreter` is empty or the command cannot be built.
+    fn build_interpreted_command(
+        &self,
+        interpreter: &str,
+        flag: &str,
+        code: &str,
+        workspace_dir: &Path,
+    ) -> anyhow::Result<tokio::process::Command> {
+        if interpreter.trim().is_empty() {
+            anyhow::bail!("cannot build an interpreted command without an interpreter");
+        }
+        let argv = [interpreter, flag, code].map(str::to_string);
+        self.build_from_argv(&argv, workspace_dir)
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +533,53 @@ mod tests {
         assert_eq!(runtime.memory_budget(), 0);
     }
 
+    #[test]
+    fn default_has_network_access() {
+        assert!(DummyRuntime.has_network_access());
+    }
+
+    #[test]
+    fn default_max_command_length_is_unlimited() {
+        assert_eq!(DummyRuntime.max_command_length(), None);
+    }
+
+    #[test]
+    fn default_translate_path_is_identity() {
+        let path = Path::new("/srv/workspace/src");
+        assert_eq!(
+            DummyRuntime.translate_path(path, Path::new("/srv/workspace")),
+            path
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn default_resolve_program_runs_command_v_in_shell() {
+        // DummyRuntime echoes the command it is given, so the probe's first
+        // output line is the `command -v` invocation itself.
+        let workspace = std::env::temp_dir();
+        assert_eq!(
+            DummyRuntime.resolve_program("sh", &workspace),
+            Some(PathBuf::from("command -v sh"))
+        );
+        assert_eq!(DummyRuntime.resolve_program("", &workspace), None);
+    }
+
+    #[test]
+    fn default_shell_program_is_none() {
+        assert_eq!(DummyRuntime.shell_program(), None);
+    }
+
+    #[test]
+    fn default_max_concurrent_processes_is_positive() {
+        assert!(DummyRuntime.max_concurrent_processes() >= 1);
+    }
+
+    #[test]
+    fn default_command_timeout_is_none() {
+        assert_eq!(DummyRuntime.default_command_timeout(), None);
+    }
+
     #[test]
     fn runtime_reports_capabilities() {
         let runtime = DummyRuntime;
@@ -155,4 +604,115 @@ mod tests {
         assert!(output.status.success());
         assert!(stdout.contains("hello-runtime"));
     }
+
+    struct WasiRuntime;
+
+    impl RuntimeAdapter for WasiRuntime {
+        fn name(&self) -> &str {
+            "wasi"
+        }
+
+        fn has_shell_access(&self) -> bool {
+            false
+        }
+
+        fn has_filesystem_access(&self) -> bool {
+            false
+        }
+
+        fn storage_path(&self) -> PathBuf {
+            PathBuf::new()
+        }
+
+        fn supports_long_running(&self) -> bool {
+            false
+        }
+
+        fn has_network_access(&self) -> bool {
+            false
+        }
+
+        fn build_shell_command(
+            &self,
+            _command: &str,
+            _workspace_dir: &Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            anyhow::bail!("no shell")
+        }
+    }
+
+    #[test]
+    fn require_capability_refusals_name_runtime_and_remedy() {
+        let cases = [
+            (
+                Capability::Shell,
+                "this runtime (wasi) does not support shell execution; switch to native or docker",
+            ),
+            (
+                Capability::Filesystem,
+                "this runtime (wasi) does not support filesystem access; switch to native or docker",
+            ),
+            (
+                Capability::LongRunning,
+                "this runtime (wasi) does not support long-running processes; switch to native or docker",
+            ),
+            (
+                Capability::Network,
+                "this runtime (wasi) does not support network access; switch to native, or give docker a network other than none",
+            ),
+        ];
+        for (capability, message) in cases {
+            let err = require_capability(&WasiRuntime, capability).unwrap_err();
+            assert_eq!(err.to_string(), message);
+            assert!(require_capability(&DummyRuntime, capability).is_ok());
+        }
+    }
+
+    /// Native-like runtime whose storage lives under a given home directory.
+    struct HomeRuntime {
+        home: PathBuf,
+    }
+
+    impl RuntimeAdapter for HomeRuntime {
+        fn name(&self) -> &str {
+            "home-runtime"
+        }
+
+        fn has_shell_access(&self) -> bool {
+            false
+        }
+
+        fn has_filesystem_access(&self) -> bool {
+            true
+        }
+
+        fn storage_path(&self) -> PathBuf {
+            self.home.join(".R.A.I.N.")
+        }
+
+        fn supports_long_running(&self) -> bool {
+            false
+        }
+
+        fn build_shell_command(
+            &self,
+            _command: &str,
+            _workspace_dir: &Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            anyhow::bail!("no shell")
+        }
+    }
+
+    #[test]
+    fn storage_available_bytes_reports_free_space_under_home() {
+        let home = tempfile::tempdir().unwrap();
+        let runtime = HomeRuntime {
+            home: home.path().to_path_buf(),
+        };
+        // The storage directory does not exist yet; its volume is still known.
+        assert!(!runtime.storage_path().exists());
+        let available = runtime.storage_available_bytes().unwrap();
+        assert!(available > 0);
+        assert!(available < u64::MAX);
+    }
 }
@@ -304,6 +304,11 @@ impl RuntimeAdapter for WasmRuntime {
         self.config.memory_limit_mb.saturating_mul(1024 * 1024)
     }
 
+    fn shell_program(&self) -> Option<String> {
+        // No shell inside the WASI sandbox.
+        None
+    }
+
     fn build_shell_command(
         &self,
         _command: &str,
@@ -340,6 +345,12 @@ mod tests {
         assert!(!rt.has_shell_access());
     }
 
+    #[test]
+    fn wasm_has_no_shell_program() {
+        let rt = WasmRuntime::new(default_config());
+        assert_eq!(rt.shell_program(), None);
+    }
+
     #[test]
     fn wasm_no_filesystem_by_default() {
         let rt = WasmRuntime::new(default_config());
@@ -12,6 +12,10 @@ use std::sync::OnceLock;
 
 /// Minimum token length considered for high-entropy detection.
 const ENTROPY_TOKEN_MIN_LEN: usize = 24;
+/// Sensitivity used by [`LeakDetector::new`] and [`looks_like_secret`].
+const DEFAULT_SENSITIVITY: f64 = 0.7;
+/// Fraction of a token's maximum possible entropy it must reach to be flagged.
+const ENTROPY_CEILING_FRACTION: f64 = 0.9;
 
 /// Result of leak detection.
 #[derive(Debug, Clone)]
@@ -43,7 +47,9 @@ impl Default for LeakDetector {
 impl LeakDetector {
     /// Create a new leak detector with default sensitivity.
     pub fn new() -> Self {
-        Self { sensitivity: 0.7 }
+        Self {
+            sensitivity: DEFAULT_SENSITIVITY,
+        }
     }
 
     /// Create a detector with custom sensitivity.
@@ -305,9 +311,6 @@ impl LeakDetector {
         patterns: &mut Vec<String>,
         redacted: &mut String,
     ) {
-        // Entropy threshold scales with sensitivity: at 0.7 this is ~4.37.
-        let entropy_threshold = 3.5 + self.sensitivity * 1.25;
-
         // Strip URLs before extracting tokens so that path segments like
         // "org/documents/2024-report-a1b2c3d4e5f6g7h8i9j0" are not mistaken
         // for high-entropy credentials.
@@ -318,17 +321,57 @@ impl LeakDetector {
         let tokens = extract_candidate_tokens(&content_without_urls);
 
         for token in tokens {
-            if token.len() >= ENTROPY_TOKEN_MIN_LEN {
-                let entropy = shannon_entropy(token);
-                if entropy >= entropy_threshold && has_mixed_alpha_digit(token) {
-                    patterns.push("High-entropy token".to_string());
-                    *redacted = redacted.replace(token, "[REDACTED_HIGH_ENTROPY_TOKEN]");
-                }
+            if token_looks_like_secret(token, self.sensitivity) {
+                patterns.push("High-entropy token".to_string());
+                *redacted = redacted.replace(token, "[REDACTED_HIGH_ENTROPY_TOKEN]");
             }
         }
     }
 }
 
+/// Whether a bare `token` looks like a generated secret rather than a word.
+///
+/// Uses the default detector sensitivity; see [`token_looks_like_secret`].
+pub fn looks_like_secret(token: &str) -> bool {
+    token_looks_like_secret(token, DEFAULT_SENSITIVITY)
+}
+
+/// Whether `token` is long enough, mixes letters and digits, and has a Shannon
+/// entropy above the threshold for `sensitivity` (~4.37 bits at 0.7).
+///
+/// A string can be no more random than its length and character classes
+/// allow, so the threshold is capped at a fraction of that maximum; short
+/// tokens are then not held to a bar only long ones can clear. English words,
+/// identifiers, hex digests and repetitive strings stay below it.
+fn token_looks_like_secret(token: &str, sensitivity: f64) -> bool {
+    if token.len() < ENTROPY_TOKEN_MIN_LEN || !has_mixed_alpha_digit(token) {
+        return false;
+    }
+    let max_entropy = (character_class_alphabet(token).min(token.len()) as f64).log2();
+    let threshold = (3.5 + sensitivity * 1.25).min(max_entropy * ENTROPY_CEILING_FRACTION);
+    shannon_entropy(token) >= threshold
+}
+
+/// A byte class test paired with the number of symbols in that class.
+type CharacterClass = (fn(&u8) -> bool, usize);
+
+/// Size of the alphabet spanned by the character classes present in `s`:
+/// lowercase, uppercase, digits, and anything else.
+fn character_class_alphabet(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let classes: [CharacterClass; 4] = [
+        (u8::is_ascii_lowercase, 26),
+        (u8::is_ascii_uppercase, 26),
+        (u8::is_ascii_digit, 10),
+        (|b: &u8| !b.is_ascii_alphanumeric(), 32),
+    ];
+    classes
+        .iter()
+        .filter(|(matches, _)| bytes.iter().any(matches))
+        .map(|(_, size)| size)
+        .sum()
+}
+
 /// Extract candidate tokens by splitting on characters outside the
 /// alphanumeric + common credential character set.
 fn extract_candidate_tokens(content: &str) -> Vec<&str> {
@@ -535,4 +578,31 @@ MIIEowIBAAKCAQEA0ZPr5JeyVDonXsKhfq...
         let e = shannon_entropy("abab");
         assert!((e - 1.0).abs() < 0.001);
     }
+
+    #[test]
+    fn looks_like_secret_flags_base64_tokens() {
+        assert!(looks_like_secret(
+            "q8Xv2LmN4pR7tY1wZ3kJ9bH6cF0dS5gA+/eUoViT"
+        ));
+        assert!(looks_like_secret(
+            "Zm9vYmFyQmF6UXV4MTIzNDU2Nzg5MGFiY2RlZmdo"
+        ));
+    }
+
+    #[test]
+    fn looks_like_secret_ignores_words_and_digests() {
+        for token in [
+            "the",
+            "password",
+            "internationalization",
+            "uncharacteristically",
+            "counterrevolutionaries",
+            "Supercalifragilistic2024",
+            "ab12ab12ab12ab12ab12ab12ab12ab12",
+            // Git object ids are everywhere in tool output.
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+        ] {
+            assert!(!looks_like_secret(token), "{token:?}");
+        }
+    }
 }
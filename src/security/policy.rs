@@ -896,21 +896,30 @@ impl SecurityPolicy {
         command: &str,
         approved: bool,
     ) -> Result<CommandRiskLevel, String> {
+        let deny = |reason: String| match self.suggest_alternative(command) {
+            Some(suggestion) => format!("{reason}. Suggestion: {suggestion}"),
+            None => reason,
+        };
+
         if !self.is_command_allowed(command) {
-            return Err(format!("Command not allowed by security policy: {command}"));
+            return Err(deny(format!(
+                "Command not allowed by security policy: {command}"
+            )));
         }
 
         let risk = self.command_risk_level(command);
 
         if risk == CommandRiskLevel::High {
             if self.block_high_risk_commands && !self.is_command_explicitly_allowed(command) {
-                return Err("Command blocked: high-risk command is disallowed by policy".into());
+                return Err(deny(
+                    "Command blocked: high-risk command is disallowed by policy".into(),
+                ));
             }
             if self.autonomy == AutonomyLevel::Supervised && !approved {
-                return Err(
+                return Err(deny(
                     "Command requires explicit approval (approved=true): high-risk operation"
                         .into(),
-                );
+                ));
             }
         }
 
@@ -919,14 +928,46 @@ impl SecurityPolicy {
             && self.require_approval_for_medium_risk
             && !approved
         {
-            return Err(
+            return Err(deny(
                 "Command requires explicit approval (approved=true): medium-risk operation".into(),
-            );
+            ));
         }
 
         Ok(risk)
     }
 
+    /// Suggest a capability that can do the job of a denied `command`.
+    ///
+    /// Looks at each segment's base command and returns the hint for the
+    /// first one with a safer equivalent, e.g. the `http_request` tool for
+    /// `curl`. Returns `None` when nothing better is available.
+    pub fn suggest_alternative(&self, command: &str) -> Option<String> {
+        split_unquoted_segments(command).iter().find_map(|segment| {
+            let cmd_part = skip_env_assignments(segment);
+            let base_raw = cmd_part.split_whitespace().next()?;
+            let base_owned = command_basename(base_raw).to_ascii_lowercase();
+            let suggestion = match strip_windows_exe_suffix(&base_owned) {
+                "rm" | "rmdir" | "del" | "erase" | "rd" => {
+                    "delete specific workspace files by path instead of removing \
+                     directories recursively, or ask the user to clean up the workspace"
+                }
+                "curl" | "wget" | "invoke-webrequest" | "iwr" => {
+                    "use the `http_request` tool for HTTP calls"
+                }
+                "find" => "use the `glob_search` tool to locate files",
+                "grep" | "rg" | "findstr" => "use the `content_search` tool to search files",
+                "git" => "use the `git_operations` tool for repository changes",
+                "cat" | "head" | "tail" | "type" | "more" | "less" => {
+                    "use the `file_read` tool to read files"
+                }
+                "tee" | "touch" => "use the `file_write` tool to create or write files",
+                "sed" | "awk" | "perl" => "use the `file_edit` tool to modify files",
+                _ => return None,
+            };
+            Some(suggestion.to_string())
+        })
+    }
+
     /// Check whether **every** segment of a command is explicitly listed in
     /// `allowed_commands` — i.e., matched by a concrete entry rather than by
     /// the wildcard `"*"`.
@@ -1796,6 +1837,40 @@ mod tests {
         assert_eq!(allowed.unwrap(), CommandRiskLevel::Medium);
     }
 
+    #[test]
+    fn validate_command_suggests_alternative_for_denied_command() {
+        let p = SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            allowed_commands: vec!["*".into()],
+            ..SecurityPolicy::default()
+        };
+
+        let err = p
+            .validate_command_execution("rm -rf build", true)
+            .unwrap_err();
+        assert!(err.contains("high-risk"));
+        assert!(err.contains("Suggestion: delete specific workspace files"));
+
+        let err = default_policy()
+            .validate_command_execution("curl https://example.com", true)
+            .unwrap_err();
+        assert!(err.contains("`http_request` tool"));
+    }
+
+    #[test]
+    fn validate_command_unrelated_denial_has_no_suggestion() {
+        let p = default_policy();
+        assert_eq!(p.suggest_alternative("nmap -sS 10.0.0.1"), None);
+
+        let err = p
+            .validate_command_execution("nmap -sS 10.0.0.1", true)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "Command not allowed by security policy: nmap -sS 10.0.0.1"
+        );
+    }
+
     #[test]
     fn validate_command_blocks_high_risk_via_wildcard() {
         // Wildcard allows the command through is_command_allowed, but
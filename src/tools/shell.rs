@@ -1,10 +1,16 @@
 use super::traits::{Tool, ToolResult};
-use crate::runtime::RuntimeAdapter;
+use crate::observability::command_trace::CommandExecution;
+use crate::runtime::exec::terminate_process_group;
+use crate::runtime::{CommandResult, ProcessRegistry, RuntimeAdapter, TimeoutReason};
 use crate::security::SecurityPolicy;
 use crate::security::traits::Sandbox;
+use crate::util::{
+    estimate_tokens, format_command_result, truncate_head_tail_lines, truncate_to_tokens,
+};
 use async_trait::async_trait;
 use serde_json::json;
 use std::collections::HashSet;
+use std::fmt::Write as _;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -12,6 +18,11 @@ use std::time::Duration;
 const SHELL_TIMEOUT_SECS: u64 = 60;
 /// Maximum output size in bytes (1MB).
 const MAX_OUTPUT_BYTES: usize = 1_048_576;
+/// Estimated LLM tokens each output stream may use in the agent's context.
+const MAX_OUTPUT_TOKENS: usize = 25_000;
+/// Lines kept from the start and the end of output over either limit.
+const TRUNCATED_HEAD_LINES: usize = 200;
+const TRUNCATED_TAIL_LINES: usize = 200;
 
 /// Environment variables safe to pass to shell commands.
 /// Only functional variables are included — never API keys or secrets.
@@ -171,12 +182,23 @@ impl Tool for ShellTool {
             });
         }
 
+        let timeout = self
+            .runtime
+            .default_command_timeout()
+            .unwrap_or(Duration::from_secs(SHELL_TIMEOUT_SECS));
+        if let Some(result) = self.runtime.replayed_result(command) {
+            self.runtime.observe_result(&result);
+            return Ok(tool_result(result, timeout));
+        }
+
         // Execute with timeout to prevent hanging commands.
         // Clear the environment to prevent leaking API keys and other secrets
         // (CWE-200), then re-add only safe, functional variables.
+        let submitted = command.to_string();
+        let command = self.runtime.preprocess_command(command);
         let mut cmd = match self
             .runtime
-            .build_shell_command(command, &self.security.workspace_dir)
+            .build_shell_command(&command, &self.security.workspace_dir)
         {
             Ok(cmd) => cmd,
             Err(e) => {
@@ -203,56 +225,150 @@ impl Tool for ShellTool {
             }
         }
 
-        let result =
-            tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
-
-        match result {
-            Ok(Ok(output)) => {
-                let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                // Truncate output to prevent OOM
-                if stdout.len() > MAX_OUTPUT_BYTES {
-                    let mut b = MAX_OUTPUT_BYTES.min(stdout.len());
-                    while b > 0 && !stdout.is_char_boundary(b) {
-                        b -= 1;
+        cmd.stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+        // Own process group, so a timeout or operator kill also reaches
+        // whatever the shell started.
+        #[cfg(unix)]
+        cmd.process_group(0);
+        let started = std::time::Instant::now();
+        let outcome = match cmd.spawn() {
+            Ok(mut child) => {
+                // Listed for operators until the command finishes.
+                let registration =
+                    ProcessRegistry::global().register(&command, self.runtime.name(), child.id());
+                let stdout = tokio::spawn(read_pipe(child.stdout.take()));
+                let stderr = tokio::spawn(read_pipe(child.stderr.take()));
+                let finished = async {
+                    let status = child.wait().await?;
+                    Ok(std::process::Output {
+                        status,
+                        stdout: stdout.await.unwrap_or_default(),
+                        stderr: stderr.await.unwrap_or_default(),
+                    })
+                };
+                let result = tokio::select! {
+                    result = tokio::time::timeout(timeout, finished) => result,
+                    () = registration.killed() => {
+                        Ok(Err(std::io::Error::other("killed by operator")))
                     }
-                    stdout.truncate(b);
-                    stdout.push_str("\n... [output truncated at 1MB]");
-                }
-                if stderr.len() > MAX_OUTPUT_BYTES {
-                    let mut b = MAX_OUTPUT_BYTES.min(stderr.len());
-                    while b > 0 && !stderr.is_char_boundary(b) {
-                        b -= 1;
-                    }
-                    stderr.truncate(b);
-                    stderr.push_str("\n... [stderr truncated at 1MB]");
+                };
+                if !matches!(result, Ok(Ok(_))) {
+                    terminate_process_group(&mut child, self.runtime.graceful_signal()).await;
                 }
+                result
+            }
+            Err(e) => Ok(Err(e)),
+        };
+        let execution = CommandExecution {
+            runtime: self.runtime.name(),
+            duration: started.elapsed(),
+            exit_code: match &outcome {
+                Ok(Ok(output)) => output.status.code(),
+                _ => None,
+            },
+            timed_out: outcome.is_err(),
+        };
+        crate::observability::command_trace::record_command_span(&execution);
+        crate::observability::runtime_metrics::record_command_execution(
+            execution.runtime,
+            execution.exit_code == Some(0),
+            execution.duration,
+        );
 
-                Ok(ToolResult {
-                    success: output.status.success(),
-                    output: stdout,
-                    error: if stderr.is_empty() {
-                        None
-                    } else {
-                        Some(stderr)
-                    },
-                })
+        let mut result = CommandResult {
+            command: submitted,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            timed_out: None,
+            duration: execution.duration,
+        };
+        match outcome {
+            Ok(Ok(output)) => {
+                result.exit_code = output.status.code();
+                result.stdout = String::from_utf8_lossy(&output.stdout).to_string();
+                result.stderr = String::from_utf8_lossy(&output.stderr).to_string();
             }
-            Ok(Err(e)) => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!("Failed to execute command: {e}")),
-            }),
-            Err(_) => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Command timed out after {SHELL_TIMEOUT_SECS}s and was killed"
-                )),
-            }),
+            Ok(Err(e)) => result.stderr = format!("Failed to execute command: {e}"),
+            Err(_) => result.timed_out = Some(TimeoutReason::Overall),
+        }
+        self.runtime.observe_result(&result);
+        Ok(tool_result(result, timeout))
+    }
+}
+
+/// The tool's view of a finished command, with output truncated to prevent OOM.
+fn tool_result(result: CommandResult, timeout: Duration) -> ToolResult {
+    if result.timed_out.is_some() {
+        return ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!(
+                "Command timed out after {:?} and was killed",
+                timeout
+            )),
+        };
+    }
+    let success = result.success();
+    let stdout = limit_output(result.stdout, "output");
+    let stderr = limit_output(result.stderr, "stderr");
+    let error = if success {
+        (!stderr.is_empty()).then_some(stderr)
+    } else {
+        // Say how the command ended, e.g. `[exited 127 in 5ms: command not found]`.
+        Some(format_command_result(&CommandResult {
+            stdout: String::new(),
+            stderr,
+            ..result
+        }))
+    };
+
+    ToolResult {
+        success,
+        output: stdout,
+        error,
+    }
+}
+
+/// Fit one output stream under [`MAX_OUTPUT_BYTES`] and, by
+/// [`estimate_tokens`], [`MAX_OUTPUT_TOKENS`].
+///
+/// Long output keeps its first and last lines, since failures usually show
+/// up at the end; cuts by bytes and then tokens remain the fallback for a
+/// few huge lines.
+fn limit_output(text: String, label: &str) -> String {
+    if text.len() <= MAX_OUTPUT_BYTES && estimate_tokens(&text) <= MAX_OUTPUT_TOKENS {
+        return text;
+    }
+    let mut text = truncate_head_tail_lines(&text, TRUNCATED_HEAD_LINES, TRUNCATED_TAIL_LINES);
+    if text.len() > MAX_OUTPUT_BYTES {
+        let mut b = MAX_OUTPUT_BYTES;
+        while b > 0 && !text.is_char_boundary(b) {
+            b -= 1;
         }
+        text.truncate(b);
+        let _ = write!(text, "\n... [{label} truncated at 1MB]");
+    }
+    if estimate_tokens(&text) > MAX_OUTPUT_TOKENS {
+        let kept = truncate_to_tokens(&text, MAX_OUTPUT_TOKENS).len();
+        text.truncate(kept);
+        let _ = write!(text, "\n... [{label} truncated to fit the context window]");
     }
+    text
+}
+
+/// Everything readable from `pipe` until EOF; empty if there is no pipe.
+async fn read_pipe(pipe: Option<impl tokio::io::AsyncRead + Unpin>) -> Vec<u8> {
+    use tokio::io::AsyncReadExt;
+
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut buf).await;
+    }
+    buf
 }
 
 #[cfg(test)]
@@ -707,6 +823,101 @@ mod tests {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn shell_timeout_kills_the_whole_process_group() {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            allowed_commands: vec!["*".into()],
+            workspace_dir: std::env::temp_dir(),
+            ..SecurityPolicy::default()
+        });
+        let runtime = Arc::new(crate::runtime::TimeoutRuntime::new(
+            NativeRuntime::new(),
+            Duration::from_millis(300),
+        ));
+        let tool = ShellTool::new(security, runtime);
+        let result = tool
+            .execute(json!({"command": "sleep 31.4159 | cat"}))
+            .await
+            .expect("timed out command should return a result");
+        assert!(!result.success);
+        assert!(result.error.as_deref().unwrap_or("").contains("timed out"));
+
+        // The pipeline's `sleep` is a grandchild of the spawned shell; it must
+        // not outlive the timeout.
+        let sleep_running = || {
+            std::fs::read_dir("/proc")
+                .unwrap()
+                .filter_map(|entry| std::fs::read(entry.ok()?.path().join("cmdline")).ok())
+                .any(|cmdline| cmdline == b"sleep\031.4159\0")
+        };
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        while sleep_running() && std::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!sleep_running());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shell_records_and_replays_through_session_runtimes() {
+        use crate::runtime::{RecordingRuntime, ReplayRuntime};
+
+        let workspace = tempfile::tempdir().unwrap();
+        let session = workspace.path().join("session.jsonl");
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: workspace.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let recorder = Arc::new(RecordingRuntime::new(NativeRuntime::new(), &session).unwrap());
+        let recorded = ShellTool::new(security.clone(), recorder)
+            .execute(json!({"command": "echo recorded"}))
+            .await
+            .unwrap();
+        assert_eq!(recorded.output, "recorded\n");
+
+        let replay = Arc::new(ReplayRuntime::load(&session).unwrap());
+        let replayed = ShellTool::new(security, replay)
+            .execute(json!({"command": "echo recorded"}))
+            .await
+            .unwrap();
+        assert!(replayed.success);
+        assert_eq!(replayed.output, recorded.output);
+    }
+
+    #[test]
+    fn long_output_keeps_its_first_and_last_lines() {
+        let stdout: String = (1..=200_000).map(|n| format!("line {n}\n")).collect();
+        let limited = limit_output(stdout, "output");
+        assert!(limited.len() <= MAX_OUTPUT_BYTES);
+        assert!(limited.starts_with("line 1\nline 2\n"));
+        assert!(limited.contains("\n... (199600 lines omitted)\n"));
+        assert!(limited.ends_with("line 200000\n"));
+
+        // Blank padding costs bytes but hardly any tokens.
+        let one_line = " ".repeat(MAX_OUTPUT_BYTES + 1);
+        let limited = limit_output(one_line, "stderr");
+        assert!(limited.ends_with("\n... [stderr truncated at 1MB]"));
+
+        assert_eq!(limit_output("short\n".into(), "output"), "short\n");
+    }
+
+    #[test]
+    fn wordy_output_is_cut_to_the_token_budget() {
+        let lines: String = (0..5_000).map(|_| "word ".repeat(20) + "\n").collect();
+        assert!(lines.len() < MAX_OUTPUT_BYTES);
+        let limited = limit_output(lines, "output");
+        assert!(limited.contains("(4600 lines omitted)"));
+        assert!(estimate_tokens(&limited) <= MAX_OUTPUT_TOKENS);
+
+        let one_line = "word ".repeat(40_000);
+        let limited = limit_output(one_line, "output");
+        assert!(limited.ends_with("\n... [output truncated to fit the context window]"));
+        assert!(estimate_tokens(&limited) < MAX_OUTPUT_TOKENS + 20);
+    }
+
     // ── Non-UTF8 binary output tests ────────────────────
 
     #[test]
@@ -768,6 +979,33 @@ mod tests {
         assert!(!result.success);
     }
 
+    #[tokio::test]
+    async fn shell_failure_error_ends_with_exit_footer() {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            allowed_commands: vec!["*".into()],
+            workspace_dir: std::env::temp_dir(),
+            ..SecurityPolicy::default()
+        });
+        let tool = ShellTool::new(security, test_runtime());
+        let result = tool
+            .execute(json!({"command": "ls nonexistent_dir_xyz"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.is_empty());
+        let error = result.error.unwrap();
+        assert!(error.starts_with("ls: "), "{error}");
+        assert!(error.contains("\n[exited 2 in "), "{error}");
+
+        let result = tool
+            .execute(json!({"command": "nonexistent_binary_xyz_12345"}))
+            .await
+            .unwrap();
+        let error = result.error.unwrap();
+        assert!(error.ends_with(": command not found]"), "{error}");
+    }
+
     #[tokio::test]
     async fn shell_captures_stderr_output() {
         let tool = ShellTool::new(test_security(AutonomyLevel::Full), test_runtime());
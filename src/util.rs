@@ -2,6 +2,16 @@
 //!
 //! This module contains reusable helper functions used across the codebase.
 
+use crate::runtime::{CommandResult, OutputChunk, TimeoutReason};
+use regex::Regex;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use std::path::Path;
+use std::time::Duration;
+
 /// Allowed serial device path prefixes — reject arbitrary paths for security.
 /// Used by hardware serial transport and peripherals.
 const SERIAL_ALLOWED_PATH_PREFIXES: &[&str] = &[
@@ -35,6 +45,10 @@ pub fn is_serial_path_allowed(path: &str) -> bool {
 /// * Original string if length <= `max_chars`
 /// * Truncated string with "..." appended if length > `max_chars`
 ///
+/// This is [`truncate_with_ellipsis_custom`] with [`DEFAULT_ELLIPSIS`] and the
+/// marker added to the budget, so input at most three characters over
+/// `max_chars` is returned whole rather than replaced by something longer.
+///
 /// # Examples
 /// ```ignore
 /// use rain_labs::util::truncate_with_ellipsis;
@@ -47,22 +61,859 @@ pub fn is_serial_path_allowed(path: &str) -> bool {
 ///
 /// // Multi-byte UTF-8 (emoji) - safe truncation
 /// assert_eq!(truncate_with_ellipsis("Hello 🦀 World", 8), "Hello 🦀...");
-/// assert_eq!(truncate_with_ellipsis("😀😀😀😀", 2), "😀😀...");
+/// assert_eq!(truncate_with_ellipsis("😀😀😀😀😀😀", 2), "😀😀...");
 ///
 /// // Empty string
 /// assert_eq!(truncate_with_ellipsis("", 10), "");
 /// ```
 pub fn truncate_with_ellipsis(s: &str, max_chars: usize) -> String {
-    match s.char_indices().nth(max_chars) {
-        Some((idx, _)) => {
-            let truncated = &s[..idx];
-            // Trim trailing whitespace for cleaner output
-            format!("{}...", truncated.trim_end())
-        }
+    truncate_with_ellipsis_custom(s, default_ellipsis_budget(max_chars), DEFAULT_ELLIPSIS)
+}
+
+/// [`truncate_with_ellipsis`] that borrows the input when it already fits.
+///
+/// A string of at most `max_chars` bytes cannot hold more than `max_chars`
+/// characters, so short input is returned without scanning or allocating.
+pub fn truncate_with_ellipsis_cow(s: &str, max_chars: usize) -> Cow<'_, str> {
+    let budget = default_ellipsis_budget(max_chars);
+    if s.len() <= budget || s.char_indices().nth(budget).is_none() {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(truncate_with_ellipsis_custom(s, budget, DEFAULT_ELLIPSIS))
+}
+
+/// Budget for [`truncate_with_ellipsis_custom`] that keeps `max_chars`
+/// characters of text ahead of [`DEFAULT_ELLIPSIS`].
+fn default_ellipsis_budget(max_chars: usize) -> usize {
+    max_chars.saturating_add(DEFAULT_ELLIPSIS.len())
+}
+
+/// Marker appended by [`truncate_with_ellipsis`].
+pub const DEFAULT_ELLIPSIS: &str = "...";
+
+/// Truncate a string so the result, marker included, is at most `max_chars` characters.
+///
+/// Unlike [`truncate_with_ellipsis`], the budget covers the marker too, so callers
+/// with a hard width limit (log columns, UI badges) can use markers such as
+/// `"[truncated]"`. A marker longer than the budget is itself cut to fit; an
+/// empty marker gives a plain character-boundary cut.
+///
+/// # Examples
+/// ```ignore
+/// use rain_labs::util::truncate_with_ellipsis_custom;
+///
+/// assert_eq!(truncate_with_ellipsis_custom("hello world", 8, "..."), "hello...");
+/// assert_eq!(truncate_with_ellipsis_custom("hello world", 5, ""), "hello");
+/// assert_eq!(truncate_with_ellipsis_custom("hello world", 4, "[truncated]"), "[tru");
+/// ```
+pub fn truncate_with_ellipsis_custom(s: &str, max_chars: usize, ellipsis: &str) -> String {
+    if s.len() <= max_chars || s.char_indices().nth(max_chars).is_none() {
+        return s.to_string();
+    }
+
+    let marker_chars = ellipsis.chars().count();
+    if marker_chars >= max_chars {
+        return ellipsis.chars().take(max_chars).collect();
+    }
+
+    match s.char_indices().nth(max_chars - marker_chars) {
+        Some((idx, _)) => append_marker(&s[..idx], ellipsis),
         None => s.to_string(),
     }
 }
 
+/// Where [`truncate_with_ellipsis_placed`] anchors its ellipsis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EllipsisPlacement {
+    /// Append `...` as [`truncate_with_ellipsis`] does.
+    #[default]
+    Trailing,
+    /// Anchor the marker to the text's dominant script direction.
+    ///
+    /// `...` is directionally neutral, so after right-to-left text shown in a
+    /// left-to-right context it renders on the wrong side. For RTL-dominant
+    /// text a right-to-left mark (U+200F) is inserted before it so it stays
+    /// at the visual end of the RTL run.
+    FollowScript,
+}
+
+/// Right-to-left mark, which gives neutral characters after it RTL direction.
+const RIGHT_TO_LEFT_MARK: char = '\u{200F}';
+
+/// [`truncate_with_ellipsis`] with control over how the marker is placed in
+/// bidirectional text.
+///
+/// [`EllipsisPlacement::Trailing`] matches [`truncate_with_ellipsis`] exactly.
+pub fn truncate_with_ellipsis_placed(
+    s: &str,
+    max_chars: usize,
+    placement: EllipsisPlacement,
+) -> String {
+    match (placement, truncate_with_ellipsis_cow(s, max_chars)) {
+        (EllipsisPlacement::FollowScript, Cow::Owned(mut truncated))
+            if is_rtl_dominant(&truncated) =>
+        {
+            truncated.insert(truncated.len() - DEFAULT_ELLIPSIS.len(), RIGHT_TO_LEFT_MARK);
+            truncated
+        }
+        (_, truncated) => truncated.into_owned(),
+    }
+}
+
+/// Whether strong right-to-left letters (Hebrew, Arabic, Syriac, Thaana,
+/// N'Ko and their presentation forms) outnumber other letters in `s`.
+fn is_rtl_dominant(s: &str) -> bool {
+    let is_rtl = |c: &char| {
+        matches!(c,
+            '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+    };
+    let letters = s.chars().filter(|c| c.is_alphabetic());
+    let (rtl, other): (Vec<char>, Vec<char>) = letters.partition(is_rtl);
+    rtl.len() > other.len()
+}
+
+/// Join a truncated prefix and marker, trimming trailing whitespace for cleaner output.
+fn append_marker(truncated: &str, marker: &str) -> String {
+    format!("{}{marker}", truncated.trim_end())
+}
+
+/// Truncate a JSON-encoded string to at most `max_chars` encoded characters
+/// without splitting an escape sequence.
+///
+/// `s` may be a full string literal (`"..."`) or just its body. The cut never
+/// lands inside `\n`-style escapes, `\uXXXX` escapes, or a `\uD83D\uDE00`
+/// surrogate pair, so the output stays valid JSON. When truncated, `...` is
+/// appended and a quoted literal gets its closing quote back.
+///
+/// # Examples
+/// ```ignore
+/// use rain_labs::util::truncate_json_string;
+///
+/// assert_eq!(truncate_json_string(r#""ab\u00e9cd""#, 4), r#""ab...""#);
+/// assert_eq!(truncate_json_string(r"ab\ncd", 3), r"ab...");
+/// ```
+pub fn truncate_json_string(s: &str, max_chars: usize) -> String {
+    // Every token is at least as many bytes as the chars it counts for.
+    if s.len() <= max_chars {
+        return s.to_string();
+    }
+    let (body, quoted) = match s.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+        Some(body) => (body, true),
+        None => (s, false),
+    };
+
+    let bytes = body.as_bytes();
+    let mut idx = 0;
+    let mut used = 0;
+    while idx < body.len() {
+        let token_len = json_token_len(body, idx);
+        // Escapes are ASCII, so byte length equals char count for them.
+        let token_chars = if bytes[idx] == b'\\' { token_len } else { 1 };
+        if used + token_chars > max_chars {
+            let truncated = &body[..idx];
+            return if quoted {
+                format!("\"{truncated}...\"")
+            } else {
+                format!("{truncated}...")
+            };
+        }
+        used += token_chars;
+        idx += token_len;
+    }
+    s.to_string()
+}
+
+/// Byte length of the JSON string token starting at `idx` in `body`.
+fn json_token_len(body: &str, idx: usize) -> usize {
+    let rest = &body[idx..];
+    if !rest.starts_with('\\') {
+        return rest.chars().next().map_or(1, char::len_utf8);
+    }
+    let is_unicode_escape = |s: &str| {
+        s.len() >= 6 && s.starts_with("\\u") && s.as_bytes()[2..6].iter().all(u8::is_ascii_hexdigit)
+    };
+    if !is_unicode_escape(rest) {
+        // Simple escape like `\n` or `\"`; a lone trailing backslash is one byte.
+        return rest.len().min(2);
+    }
+    let high_surrogate =
+        u16::from_str_radix(&rest[2..6], 16).is_ok_and(|unit| (0xD800..0xDC00).contains(&unit));
+    if high_surrogate && is_unicode_escape(&rest[6..]) {
+        12
+    } else {
+        6
+    }
+}
+
+/// Keep the first `head_lines` and last `tail_lines` lines of `s`, replacing
+/// the middle with a `... (M lines omitted)` marker line.
+///
+/// Kept lines retain their original endings (`\n` or `\r\n`) and the marker
+/// uses the ending of the line before it. Text with at most
+/// `head_lines + tail_lines` lines is returned unchanged.
+///
+/// # Examples
+/// ```ignore
+/// use rain_labs::util::truncate_head_tail_lines;
+///
+/// assert_eq!(
+///     truncate_head_tail_lines("1\n2\n3\n4\n5\n", 1, 1),
+///     "1\n... (3 lines omitted)\n5\n"
+/// );
+/// ```
+pub fn truncate_head_tail_lines(s: &str, head_lines: usize, tail_lines: usize) -> String {
+    let lines: Vec<&str> = s.split_inclusive('\n').collect();
+    let keep = head_lines.saturating_add(tail_lines);
+    if lines.len() <= keep {
+        return s.to_string();
+    }
+
+    let omitted = lines.len() - keep;
+    let head = &lines[..head_lines];
+    let tail = &lines[lines.len() - tail_lines..];
+    let ending = match head.last() {
+        Some(line) if line.ends_with("\r\n") => "\r\n",
+        _ => "\n",
+    };
+    let plural = if omitted == 1 { "" } else { "s" };
+
+    let mut out = head.concat();
+    let _ = write!(out, "... ({omitted} line{plural} omitted){ending}");
+    out.push_str(&tail.concat());
+    out
+}
+
+/// Character classes [`estimate_tokens`] groups into runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenClass {
+    Word,
+    Punct,
+    Space,
+    Newline,
+    Other,
+}
+
+impl TokenClass {
+    fn of(c: char) -> Self {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            Self::Word
+        } else if c == '\n' {
+            Self::Newline
+        } else if c.is_whitespace() {
+            Self::Space
+        } else if c.is_ascii() {
+            Self::Punct
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Estimated tokens for a run of `len` characters of this class.
+    fn tokens(self, len: usize) -> usize {
+        match self {
+            // About four bytes per token, but even a short word costs one.
+            Self::Word => ((len + 2) / 4).max(1),
+            // Symbols merge less readily than letters.
+            Self::Punct => len.div_ceil(3),
+            // A single space attaches to the following word; indentation
+            // and blank-line runs are a token of their own.
+            Self::Space => usize::from(len > 1),
+            Self::Newline => 1,
+            // Non-ASCII text (CJK, emoji, accents) is roughly a token a char.
+            Self::Other => len,
+        }
+    }
+}
+
+/// Estimate how many LLM tokens `s` would use.
+///
+/// This is an approximation, not a tokenizer: roughly four bytes per token
+/// for words, with punctuation, indentation, newlines and non-ASCII text
+/// counted more heavily. It tends to err high, which is the safe direction
+/// when fitting command output into a context window.
+pub fn estimate_tokens(s: &str) -> usize {
+    let mut tokens = 0;
+    let mut run: Option<(TokenClass, usize)> = None;
+    for class in s.chars().map(TokenClass::of) {
+        match &mut run {
+            Some((current, len)) if *current == class => *len += 1,
+            _ => {
+                if let Some((current, len)) = run {
+                    tokens += current.tokens(len);
+                }
+                run = Some((class, 1));
+            }
+        }
+    }
+    tokens + run.map_or(0, |(class, len)| class.tokens(len))
+}
+
+/// Return the longest prefix of `s` whose [`estimate_tokens`] is at most
+/// `max_tokens`, cut on a char boundary; `s` itself when it already fits.
+///
+/// Inherits the estimate's approximation, so leave headroom below a hard
+/// context limit.
+pub fn truncate_to_tokens(s: &str, max_tokens: usize) -> &str {
+    if estimate_tokens(s) <= max_tokens {
+        return s;
+    }
+    // The estimate never shrinks as a prefix grows, so binary search works.
+    let boundaries: Vec<usize> = s.char_indices().map(|(i, _)| i).collect();
+    let fits = boundaries.partition_point(|&end| estimate_tokens(&s[..end]) <= max_tokens);
+    &s[..boundaries[fits - 1]]
+}
+
+/// Convert CRLF and lone CR line endings to LF.
+///
+/// Returns `s` borrowed when it contains no `\r`, so already-normalized
+/// output costs no allocation.
+pub fn normalize_newlines(s: &str) -> Cow<'_, str> {
+    if !s.contains('\r') {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(s.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+/// Non-ASCII characters used by common terminal spinners: quarter circles,
+/// arcs, arrows, bouncing bars and dots. Braille patterns are matched by range.
+const SPINNER_GLYPHS: &str = "◐◓◑◒◴◷◶◵◜◝◞◟←↖↑↗→↘↓↙▁▂▃▄▅▆▇█·•●○";
+
+fn is_spinner_glyph(c: char) -> bool {
+    ('\u{2800}'..='\u{28FF}').contains(&c) || SPINNER_GLYPHS.contains(c)
+}
+
+/// Longest run of glyphs still treated as one spinner frame, e.g. `⣾⣽⣻`.
+const MAX_SPINNER_FRAME_CHARS: usize = 3;
+
+/// Whether `line` is a single frame of a progress spinner, such as `|`, `\`,
+/// `⠋` or `◐`, rather than real output.
+///
+/// Surrounding whitespace (including the `\r` spinners redraw with) is
+/// ignored. ASCII frames must be exactly one of `|/-\`, so `--` or `- item`
+/// are not mistaken for one; Unicode spinner glyphs may form a short run.
+pub fn is_spinner_frame(line: &str) -> bool {
+    let frame = line.trim();
+    let mut chars = frame.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => false,
+        (Some('|' | '/' | '-' | '\\'), None) => true,
+        _ => {
+            frame.chars().count() <= MAX_SPINNER_FRAME_CHARS && frame.chars().all(is_spinner_glyph)
+        }
+    }
+}
+
+/// `path` for display relative to `workspace`, so host directory layout is
+/// not exposed: `src/main.rs` for a file inside it, `.` for the workspace
+/// itself, and the full path for anything outside. Separators are shown as
+/// `/` on every platform.
+///
+/// The comparison is lexical; neither path is canonicalized or required to
+/// exist.
+pub fn relativize(path: &Path, workspace: &Path) -> String {
+    let shown = match path.strip_prefix(workspace) {
+        Ok(relative) if relative.as_os_str().is_empty() => return ".".to_string(),
+        Ok(relative) => relative.to_string_lossy(),
+        Err(_) => path.to_string_lossy(),
+    };
+    if cfg!(windows) {
+        shown.replace('\\', "/")
+    } else {
+        shown.into_owned()
+    }
+}
+
+/// Render `duration` compactly for humans: `350ms`, `1.2s`, `2m 5s`, `1h 3m`.
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    let secs = duration.as_secs();
+    if millis < 1000 {
+        format!("{millis}ms")
+    } else if secs < 10 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+/// What a conventional POSIX shell exit status means, for codes that are not
+/// just a program's own failure: `127` (command not found), `126` (not
+/// executable), `124` (from `timeout`) and `128 + n` for death by signal `n`.
+///
+/// Returns `None` for `0`, plain failures such as `1`, and anything unmapped.
+pub fn describe_exit_code(code: i32) -> Option<&'static str> {
+    let description = match code {
+        124 => "timed out",
+        126 => "command found but not executable",
+        127 => "command not found",
+        129 => "hangup (SIGHUP)",
+        130 => "interrupted (SIGINT)",
+        131 => "quit (SIGQUIT)",
+        134 => "aborted (SIGABRT)",
+        137 => "killed (SIGKILL)",
+        139 => "segmentation fault (SIGSEGV)",
+        141 => "broken pipe (SIGPIPE)",
+        143 => "terminated (SIGTERM)",
+        255 => "exit status out of range",
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// Combine a command's stdout and stderr and append a one-line exit footer,
+/// such as `[exited 0 in 1.2s]`, `[exited 127 in 5ms: command not found]` or
+/// `[killed by SIGKILL after 30s (timeout)]`.
+pub fn format_command_result(result: &CommandResult) -> String {
+    let mut out = String::new();
+    for stream in [&result.stdout, &result.stderr] {
+        if stream.is_empty() {
+            continue;
+        }
+        out.push_str(stream);
+        if !stream.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+
+    let elapsed = format_duration(result.duration);
+    let footer = match (result.timed_out, result.exit_code) {
+        (Some(reason), code) => {
+            let label = match reason {
+                TimeoutReason::Overall => "timeout",
+                TimeoutReason::Idle => "idle timeout",
+            };
+            match code {
+                // Exited on its graceful signal within the grace period.
+                Some(code) => format!("[exited {code} after {elapsed} ({label})]"),
+                // Escalated to SIGKILL; see `terminate_process_group`.
+                None => format!("[killed by SIGKILL after {elapsed} ({label})]"),
+            }
+        }
+        (None, Some(code)) => match describe_exit_code(code) {
+            Some(meaning) => format!("[exited {code} in {elapsed}: {meaning}]"),
+            None => format!("[exited {code} in {elapsed}]"),
+        },
+        (None, None) => format!("[no exit code after {elapsed}]"),
+    };
+    out.push_str(&footer);
+    out
+}
+
+/// Separators used when rendering numbers for people.
+///
+/// [`NumberFormat::US`] is the default; [`NumberFormat::for_locale`] picks
+/// comma-decimal styles such as `1.234,5` for locales that use them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Inserted between groups of three integer digits; `None` disables grouping.
+    pub grouping_separator: Option<char>,
+    /// Fraction digits to round to; trailing zeros are dropped.
+    pub max_fraction_digits: usize,
+}
+
+impl NumberFormat {
+    /// `1,234.5`
+    pub const US: Self = Self {
+        decimal_separator: '.',
+        grouping_separator: Some(','),
+        max_fraction_digits: 2,
+    };
+
+    /// `1.234,5`
+    pub const COMMA_DECIMAL: Self = Self {
+        decimal_separator: ',',
+        grouping_separator: Some('.'),
+        max_fraction_digits: 2,
+    };
+
+    /// `1 234,5`, grouped with a no-break space.
+    pub const SPACE_GROUPED: Self = Self {
+        decimal_separator: ',',
+        grouping_separator: Some('\u{a0}'),
+        max_fraction_digits: 2,
+    };
+
+    /// The conventional format for a locale tag such as `de-DE` or `fr_FR.UTF-8`,
+    /// falling back to [`NumberFormat::US`].
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => Self::COMMA_DECIMAL,
+            "fr" | "ru" | "pl" | "sv" | "nb" | "fi" | "cs" | "uk" => Self::SPACE_GROUPED,
+            _ => Self::US,
+        }
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::US
+    }
+}
+
+/// Render `n` with `fmt`'s separators, e.g. `1,234.5` or `1.234,5`.
+pub fn format_number(n: f64, fmt: &NumberFormat) -> String {
+    if !n.is_finite() {
+        return n.to_string();
+    }
+    let rendered = format!("{:.*}", fmt.max_fraction_digits, n.abs());
+    let (integer, fraction) = rendered.split_once('.').unwrap_or((&rendered, ""));
+    let fraction = fraction.trim_end_matches('0');
+
+    let mut out = String::with_capacity(rendered.len() + integer.len() / 3 + 1);
+    if n.is_sign_negative() && rendered.bytes().any(|b| matches!(b, b'1'..=b'9')) {
+        out.push('-');
+    }
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            if let Some(separator) = fmt.grouping_separator {
+                out.push(separator);
+            }
+        }
+        out.push(digit);
+    }
+    if !fraction.is_empty() {
+        out.push(fmt.decimal_separator);
+        out.push_str(fraction);
+    }
+    out
+}
+
+/// Render a byte count with binary units, e.g. `512 B`, `1.5 KiB` or `1,5 KiB`.
+pub fn humanize_bytes(bytes: u64, fmt: &NumberFormat) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let fmt = NumberFormat {
+        max_fraction_digits: 1,
+        ..*fmt
+    };
+    format!("{} {}", format_number(value, &fmt), UNITS[unit])
+}
+
+/// Exponential backoff with jitter and a cap, for retry loops.
+///
+/// Each [`next_delay`](Self::next_delay) doubles the un-jittered delay, up to
+/// `max`. Jitter shaves a random share (up to the jitter fraction) off each
+/// delay, so delays never exceed the cap and concurrent clients spread out.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    jitter: f64,
+    attempt: u32,
+}
+
+impl Backoff {
+    /// Start at `base`, doubling up to `max`, with no jitter.
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max: max.max(base),
+            jitter: 0.0,
+            attempt: 0,
+        }
+    }
+
+    /// Randomly reduce each delay by up to `fraction` (clamped to `0..=1`).
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Number of delays handed out since creation or the last [`reset`](Self::reset).
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Start over from `base`, e.g. after a success.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// The delay before the next retry.
+    pub fn next_delay(&mut self) -> Duration {
+        let factor = 1_u32.checked_shl(self.attempt).unwrap_or(u32::MAX);
+        let delay = self.base.saturating_mul(factor).min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 - self.jitter * rand::random::<f64>())
+    }
+
+    /// Like [`next_delay`](Self::next_delay), but waits at least as long as a
+    /// server-provided `Retry-After`, still capped at `max`.
+    pub fn next_delay_after(&mut self, retry_after: Option<Duration>) -> Duration {
+        let delay = self.next_delay();
+        retry_after.map_or(delay, |retry_after| delay.max(retry_after.min(self.max)))
+    }
+}
+
+/// Default pattern for [`ProgressExtractor`]: an integer or decimal followed by `%`.
+pub const DEFAULT_PROGRESS_PATTERN: &str = r"(\d+(?:\.\d+)?)\s*%";
+
+/// Extracts completion percentages from streamed command output.
+///
+/// Each chunk is split on `\n` and `\r` (progress bars redraw with carriage
+/// returns) and the last match in the chunk wins. Lines without a match leave
+/// the latest value unchanged, and values are clamped to `0..=100`.
+#[derive(Debug, Clone)]
+pub struct ProgressExtractor {
+    pattern: Regex,
+    latest: Option<u8>,
+}
+
+impl Default for ProgressExtractor {
+    fn default() -> Self {
+        Self {
+            pattern: Regex::new(DEFAULT_PROGRESS_PATTERN).expect("valid progress pattern"),
+            latest: None,
+        }
+    }
+}
+
+impl ProgressExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a custom pattern. The first capture group (or the whole match when
+    /// there is none) must contain the number.
+    pub fn with_pattern(pattern: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            pattern: Regex::new(pattern)?,
+            latest: None,
+        })
+    }
+
+    /// Scan a chunk and return the latest percentage seen so far.
+    pub fn feed(&mut self, chunk: &OutputChunk) -> Option<u8> {
+        for line in chunk.text.split(['\n', '\r']) {
+            if let Some(percent) = self.parse_line(line) {
+                self.latest = Some(percent);
+            }
+        }
+        self.latest
+    }
+
+    /// The most recent percentage extracted, if any.
+    pub fn latest(&self) -> Option<u8> {
+        self.latest
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn parse_line(&self, line: &str) -> Option<u8> {
+        let captures = self.pattern.captures_iter(line).last()?;
+        let matched = captures.get(1).or_else(|| captures.get(0))?.as_str();
+        let number: String = matched
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let value = number.parse::<f64>().ok()?;
+        Some(value.clamp(0.0, 100.0).round() as u8)
+    }
+}
+
+/// A terminal color from an SGR sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnsiColor {
+    /// Palette index: 0–7 standard, 8–15 bright, 16–255 extended.
+    Indexed(u8),
+    /// 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+/// A run of text sharing one set of display attributes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AnsiSpan {
+    pub text: String,
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+}
+
+impl AnsiSpan {
+    fn same_style(&self, other: &Self) -> bool {
+        self.fg == other.fg && self.bg == other.bg && self.bold == other.bold
+    }
+}
+
+/// Split terminal output into styled spans using its SGR (`ESC [ ... m`) codes.
+///
+/// Lets the dashboard render colors instead of stripping them. Other CSI and
+/// OSC sequences, and SGR codes this parser does not model, are dropped
+/// without affecting the text around them.
+pub fn parse_ansi(s: &str) -> Vec<AnsiSpan> {
+    let mut spans = Vec::new();
+    let mut current = AnsiSpan::default();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            current.text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                let mut params = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last != Some('m') {
+                    continue;
+                }
+                let mut next = AnsiSpan {
+                    text: String::new(),
+                    fg: current.fg,
+                    bg: current.bg,
+                    bold: current.bold,
+                };
+                apply_sgr(&mut next, &params);
+                if !next.same_style(&current) {
+                    let finished = std::mem::replace(&mut current, next);
+                    if !finished.text.is_empty() {
+                        spans.push(finished);
+                    }
+                }
+            }
+            Some(']') => {
+                // OSC: runs until BEL or ST (`ESC \`).
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if !current.text.is_empty() {
+        spans.push(current);
+    }
+    spans
+}
+
+/// Apply the `;`-separated SGR parameters in `params` to `span`'s style.
+fn apply_sgr(span: &mut AnsiSpan, params: &str) {
+    let mut codes = params.split(';').map(|code| {
+        if code.is_empty() {
+            Some(0)
+        } else {
+            code.parse::<u8>().ok()
+        }
+    });
+
+    while let Some(code) = codes.next() {
+        let Some(code) = code else { continue };
+        match code {
+            0 => {
+                span.fg = None;
+                span.bg = None;
+                span.bold = false;
+            }
+            1 => span.bold = true,
+            22 => span.bold = false,
+            30..=37 => span.fg = Some(AnsiColor::Indexed(code - 30)),
+            90..=97 => span.fg = Some(AnsiColor::Indexed(code - 90 + 8)),
+            40..=47 => span.bg = Some(AnsiColor::Indexed(code - 40)),
+            100..=107 => span.bg = Some(AnsiColor::Indexed(code - 100 + 8)),
+            39 => span.fg = None,
+            49 => span.bg = None,
+            38 | 48 => {
+                let color = match codes.next().flatten() {
+                    Some(5) => codes.next().flatten().map(AnsiColor::Indexed),
+                    Some(2) => {
+                        let (r, g, b) = (codes.next(), codes.next(), codes.next());
+                        match (r.flatten(), g.flatten(), b.flatten()) {
+                            (Some(r), Some(g), Some(b)) => Some(AnsiColor::Rgb(r, g, b)),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                if code == 38 {
+                    span.fg = color.or(span.fg);
+                } else {
+                    span.bg = color.or(span.bg);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TemplateError {
+    /// Placeholders with no matching variable, in order of first use.
+    #[error("unknown template variables: {}", .0.join(", "))]
+    UnknownVariables(Vec<String>),
+    /// A `{{` with no closing `}}`, at the given byte offset.
+    #[error("unclosed '{{{{' at byte {0}")]
+    Unclosed(usize),
+}
+
+/// Replace `{{name}}` placeholders in `template` with values from `vars`.
+///
+/// Whitespace inside the braces is ignored and `{{{{` renders a literal `{{`.
+/// Every unknown name is reported at once so callers can fix them together.
+pub fn render_template<S: BuildHasher>(
+    template: &str,
+    vars: &HashMap<String, String, S>,
+) -> Result<String, TemplateError> {
+    let mut out = String::with_capacity(template.len());
+    let mut unknown: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after_open = &rest[open..];
+        if let Some(after_literal) = after_open.strip_prefix("{{{{") {
+            out.push_str("{{");
+            rest = after_literal;
+            continue;
+        }
+        let Some(close) = after_open[2..].find("}}") else {
+            return Err(TemplateError::Unclosed(template.len() - after_open.len()));
+        };
+        let name = after_open[2..2 + close].trim();
+        match vars.get(name) {
+            Some(value) => out.push_str(value),
+            None if !unknown.iter().any(|known| known == name) => unknown.push(name.to_string()),
+            None => {}
+        }
+        rest = &after_open[2 + close + 2..];
+    }
+    out.push_str(rest);
+
+    if unknown.is_empty() {
+        Ok(out)
+    } else {
+        Err(TemplateError::UnknownVariables(unknown))
+    }
+}
+
 /// Utility enum for handling optional values.
 pub enum MaybeSet<T> {
     Set(T),
@@ -113,9 +964,10 @@ mod tests {
     #[test]
     fn test_truncate_emoji_multiple() {
         // Multiple emoji - safe truncation at character boundary
-        let s = "😀😀😀😀"; // 4 emoji, each 4 bytes = 16 bytes total
+        let s = "😀😀😀😀😀😀"; // 6 emoji, each 4 bytes = 24 bytes total
         assert_eq!(truncate_with_ellipsis(s, 2), "😀😀...");
-        assert_eq!(truncate_with_ellipsis(s, 3), "😀😀😀...");
+        // Cutting three emoji for a three-char marker would not shorten it.
+        assert_eq!(truncate_with_ellipsis(s, 3), s);
     }
 
     #[test]
@@ -128,8 +980,8 @@ mod tests {
     #[test]
     fn test_truncate_cjk_characters() {
         // CJK characters (Chinese - each is 3 bytes)
-        let s = "这是一个测试消息用来触发崩溃的中文"; // 21 characters
-        let result = truncate_with_ellipsis(s, 16);
+        let s = "这是一个测试消息用来触发崩溃的中文"; // 17 characters
+        let result = truncate_with_ellipsis(s, 12);
         assert!(result.ends_with("..."));
         assert!(result.is_char_boundary(result.len() - 1));
     }
@@ -145,7 +997,12 @@ mod tests {
     fn test_truncate_unicode_edge_case() {
         // Mix of 1-byte, 2-byte, 3-byte, and 4-byte characters
         let s = "aé你好🦀"; // 1 + 1 + 2 + 2 + 4 bytes = 10 bytes, 5 chars
-        assert_eq!(truncate_with_ellipsis(s, 3), "aé你...");
+        assert_eq!(truncate_with_ellipsis(s, 3), s);
+        assert_eq!(truncate_with_ellipsis(s, 1), "a...");
+        assert_eq!(
+            truncate_with_ellipsis(&s.repeat(2), 3),
+            truncate_with_ellipsis_custom(&s.repeat(2), 6, "...")
+        );
     }
 
     #[test]
@@ -162,4 +1019,505 @@ mod tests {
         // Edge case: max_chars = 0
         assert_eq!(truncate_with_ellipsis("hello", 0), "...");
     }
+
+    #[test]
+    fn test_truncate_custom_dots_fit_budget() {
+        assert_eq!(
+            truncate_with_ellipsis_custom("hello world", 8, "..."),
+            "hello..."
+        );
+        assert_eq!(truncate_with_ellipsis_custom("hello", 8, "..."), "hello");
+        assert_eq!(truncate_with_ellipsis_custom("😀😀😀😀", 3, "…"), "😀😀…");
+    }
+
+    #[test]
+    fn test_truncate_custom_empty_marker() {
+        assert_eq!(truncate_with_ellipsis_custom("hello world", 5, ""), "hello");
+        assert_eq!(truncate_with_ellipsis_custom("hello world", 6, ""), "hello");
+    }
+
+    #[test]
+    fn test_truncate_custom_marker_longer_than_budget() {
+        assert_eq!(
+            truncate_with_ellipsis_custom("hello world", 4, "[truncated]"),
+            "[tru"
+        );
+        assert_eq!(truncate_with_ellipsis_custom("hello", 0, "..."), "");
+        // Short strings are returned untouched even when the marker would not fit.
+        assert_eq!(truncate_with_ellipsis_custom("hi", 4, "[truncated]"), "hi");
+    }
+
+    #[test]
+    fn test_truncate_placed_anchors_marker_for_arabic() {
+        let arabic = "مرحبا بالعالم";
+        assert_eq!(
+            truncate_with_ellipsis_placed(arabic, 5, EllipsisPlacement::Trailing),
+            "مرحبا..."
+        );
+        assert_eq!(
+            truncate_with_ellipsis_placed(arabic, 5, EllipsisPlacement::FollowScript),
+            "مرحبا\u{200F}..."
+        );
+        assert_eq!(
+            truncate_with_ellipsis_placed(arabic, 5, EllipsisPlacement::Trailing),
+            truncate_with_ellipsis(arabic, 5)
+        );
+    }
+
+    #[test]
+    fn test_truncate_placed_leaves_ltr_and_short_text_alone() {
+        assert_eq!(
+            truncate_with_ellipsis_placed("hello world", 5, EllipsisPlacement::FollowScript),
+            "hello..."
+        );
+        assert_eq!(
+            truncate_with_ellipsis_placed("مرحبا", 10, EllipsisPlacement::FollowScript),
+            "مرحبا"
+        );
+    }
+
+    #[test]
+    fn test_truncate_json_cut_before_unicode_escape() {
+        assert_eq!(truncate_json_string(r#""ab\u00e9cd""#, 2), r#""ab...""#);
+        assert_eq!(
+            truncate_json_string(r#""ab\u00e9cd""#, 8),
+            r#""ab\u00e9...""#
+        );
+    }
+
+    #[test]
+    fn test_truncate_json_cut_inside_unicode_escape() {
+        // A budget of 4 would land inside `\u00e9`; the whole escape is dropped.
+        assert_eq!(truncate_json_string(r"ab\u00e9cd", 4), r"ab...");
+        // Surrogate pairs stay together.
+        assert_eq!(truncate_json_string(r"a\ud83d\ude00b", 8), r"a...");
+        assert_eq!(
+            truncate_json_string(r"a\ud83d\ude00b", 13),
+            r"a\ud83d\ude00..."
+        );
+    }
+
+    #[test]
+    fn test_truncate_json_does_not_split_newline_escape() {
+        assert_eq!(truncate_json_string(r#""ab\ncd""#, 3), r#""ab...""#);
+        assert_eq!(truncate_json_string(r#""ab\ncd""#, 4), r#""ab\n...""#);
+        assert_eq!(truncate_json_string(r#""ab\ncd""#, 10), r#""ab\ncd""#);
+    }
+
+    #[test]
+    fn head_tail_lines_omits_middle_and_keeps_line_endings() {
+        let text = "one\r\ntwo\r\nthree\r\nfour\r\nfive\r\nsix";
+        assert_eq!(
+            truncate_head_tail_lines(text, 2, 1),
+            "one\r\ntwo\r\n... (3 lines omitted)\r\nsix"
+        );
+        assert_eq!(
+            truncate_head_tail_lines("a\nb\nc\n", 1, 1),
+            "a\n... (1 line omitted)\nc\n"
+        );
+    }
+
+    #[test]
+    fn head_tail_lines_within_budget_is_unchanged() {
+        let text = "a\nb\nc\n";
+        assert_eq!(truncate_head_tail_lines(text, 2, 1), text);
+        assert_eq!(truncate_head_tail_lines(text, 5, 5), text);
+        assert_eq!(truncate_head_tail_lines("", 0, 0), "");
+    }
+
+    #[test]
+    fn truncate_cow_borrows_short_input() {
+        let input = "hello";
+        let output = truncate_with_ellipsis_cow(input, 10);
+        assert!(matches!(output, Cow::Borrowed(_)));
+        assert_eq!(output.as_ptr(), input.as_ptr());
+
+        // Multi-byte input over the byte budget but within the char budget.
+        assert!(matches!(
+            truncate_with_ellipsis_cow("😀😀", 2),
+            Cow::Borrowed("😀😀")
+        ));
+        assert!(matches!(
+            truncate_with_ellipsis_cow("hello world", 5),
+            Cow::Owned(ref s) if s == "hello..."
+        ));
+    }
+
+    #[test]
+    fn normalize_newlines_converts_crlf_and_lone_cr() {
+        assert_eq!(normalize_newlines("a\r\nb\r\n"), "a\nb\n");
+        assert_eq!(normalize_newlines("a\rb\r"), "a\nb\n");
+        assert_eq!(normalize_newlines("a\r\n\rb"), "a\n\nb");
+    }
+
+    #[test]
+    fn normalize_newlines_borrows_already_normalized_input() {
+        let input = "a\nb\n";
+        let output = normalize_newlines(input);
+        assert!(matches!(output, Cow::Borrowed(_)));
+        assert_eq!(output.as_ptr(), input.as_ptr());
+    }
+
+    #[test]
+    fn estimate_tokens_is_in_a_reasonable_band_for_english() {
+        let english = "The quick brown fox jumps over the lazy dog. Command output \
+                       is usually a mix of short words, numbers like 42, and the \
+                       occasional long identifier such as configuration_directory.";
+        let estimate = estimate_tokens(english);
+        // English runs about four characters per token.
+        let expected = english.len() / 4;
+        assert!(
+            estimate >= expected * 3 / 4 && estimate <= expected * 3 / 2,
+            "{estimate} vs ~{expected}"
+        );
+        assert_eq!(
+            estimate_tokens("The quick brown fox jumps over the lazy dog."),
+            10
+        );
+    }
+
+    #[test]
+    fn estimate_tokens_is_in_a_reasonable_band_for_code() {
+        let code = "fn main() {\n    let items = vec![1, 2, 3];\n    for item in &items {\n        \
+                    println!(\"{item:?}\");\n    }\n}\n";
+        let estimate = estimate_tokens(code);
+        // Code is denser, closer to three characters per token.
+        let expected = code.len() / 3;
+        assert!(
+            estimate >= expected * 3 / 4 && estimate <= expected * 3 / 2,
+            "{estimate} vs ~{expected}"
+        );
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("日本語"), 3);
+    }
+
+    #[test]
+    fn truncate_to_tokens_keeps_the_longest_fitting_prefix() {
+        let text = "alpha beta gamma delta epsilon";
+        assert_eq!(truncate_to_tokens(text, 100), text);
+        let cut = truncate_to_tokens(text, 3);
+        assert!(text.starts_with(cut));
+        assert!(estimate_tokens(cut) <= 3);
+        assert!(estimate_tokens(&text[..=cut.len()]) > 3);
+        assert_eq!(truncate_to_tokens("日本語", 2), "日本");
+        assert_eq!(truncate_to_tokens("anything", 0), "");
+    }
+
+    #[test]
+    fn is_spinner_frame_detects_common_frames() {
+        for frame in ["|", "/", "-", "\\", "\r|", " ⠋ ", "⣾⣽", "◐", "▃▅▇", "↗\r"] {
+            assert!(is_spinner_frame(frame), "{frame:?}");
+        }
+    }
+
+    #[test]
+    fn is_spinner_frame_ignores_normal_text() {
+        for line in [
+            "",
+            "  ",
+            "--",
+            "- item",
+            "a",
+            "ok",
+            "42%",
+            "⠋ Loading",
+            "●●●●",
+            "||",
+        ] {
+            assert!(!is_spinner_frame(line), "{line:?}");
+        }
+    }
+
+    fn command_result(exit_code: Option<i32>, millis: u64) -> CommandResult {
+        CommandResult {
+            command: "make".to_string(),
+            exit_code,
+            stdout: "built\n".to_string(),
+            stderr: String::new(),
+            timed_out: None,
+            duration: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn format_duration_picks_a_readable_unit() {
+        assert_eq!(format_duration(Duration::from_millis(350)), "350ms");
+        assert_eq!(format_duration(Duration::from_millis(1200)), "1.2s");
+        assert_eq!(format_duration(Duration::from_secs(30)), "30s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(3780)), "1h 3m");
+    }
+
+    #[test]
+    fn format_command_result_footer_for_success() {
+        assert_eq!(
+            format_command_result(&command_result(Some(0), 1200)),
+            "built\n[exited 0 in 1.2s]"
+        );
+    }
+
+    #[test]
+    fn format_command_result_footer_for_failure() {
+        let mut result = command_result(Some(2), 40);
+        result.stderr = "error: missing target".to_string();
+        assert_eq!(
+            format_command_result(&result),
+            "built\nerror: missing target\n[exited 2 in 40ms]"
+        );
+    }
+
+    #[test]
+    fn format_command_result_footer_for_timeout() {
+        let mut result = command_result(None, 30_000);
+        result.timed_out = Some(TimeoutReason::Overall);
+        assert_eq!(
+            format_command_result(&result),
+            "built\n[killed by SIGKILL after 30s (timeout)]"
+        );
+
+        result.timed_out = Some(TimeoutReason::Idle);
+        assert!(format_command_result(&result).ends_with("(idle timeout)]"));
+    }
+
+    #[test]
+    fn relativize_shows_workspace_paths_relative() {
+        let workspace = Path::new("/srv/agent/workspace");
+        assert_eq!(
+            relativize(&workspace.join("src").join("main.rs"), workspace),
+            "src/main.rs"
+        );
+        assert_eq!(relativize(workspace, workspace), ".");
+        assert_eq!(
+            relativize(Path::new("/srv/agent/workspace-old/notes.md"), workspace),
+            "/srv/agent/workspace-old/notes.md"
+        );
+        assert_eq!(relativize(Path::new("/etc/hosts"), workspace), "/etc/hosts");
+    }
+
+    #[test]
+    fn describe_exit_code_maps_shell_conventions() {
+        assert_eq!(describe_exit_code(127), Some("command not found"));
+        assert_eq!(
+            describe_exit_code(126),
+            Some("command found but not executable")
+        );
+        assert_eq!(describe_exit_code(130), Some("interrupted (SIGINT)"));
+        assert_eq!(describe_exit_code(0), None);
+        assert_eq!(describe_exit_code(42), None);
+    }
+
+    #[test]
+    fn format_command_result_footer_describes_known_codes() {
+        let result = command_result(Some(127), 5);
+        assert_eq!(
+            format_command_result(&result),
+            "built\n[exited 127 in 5ms: command not found]"
+        );
+    }
+
+    #[test]
+    fn format_number_us_style() {
+        let us = NumberFormat::default();
+        assert_eq!(format_number(1234.5, &us), "1,234.5");
+        assert_eq!(format_number(1_234_567.0, &us), "1,234,567");
+        assert_eq!(format_number(999.0, &us), "999");
+        assert_eq!(format_number(-0.126, &us), "-0.13");
+        assert_eq!(format_number(-0.001, &us), "0");
+    }
+
+    #[test]
+    fn format_number_comma_decimal_locales() {
+        let de = NumberFormat::for_locale("de-DE");
+        assert_eq!(de, NumberFormat::COMMA_DECIMAL);
+        assert_eq!(format_number(1234.5, &de), "1.234,5");
+
+        let fr = NumberFormat::for_locale("fr_FR.UTF-8");
+        assert_eq!(format_number(1234.5, &fr), "1\u{a0}234,5");
+
+        assert_eq!(NumberFormat::for_locale("en"), NumberFormat::US);
+        assert_eq!(NumberFormat::for_locale(""), NumberFormat::US);
+    }
+
+    #[test]
+    fn humanize_bytes_uses_binary_units_and_locale() {
+        let us = NumberFormat::US;
+        assert_eq!(humanize_bytes(512, &us), "512 B");
+        assert_eq!(humanize_bytes(1536, &us), "1.5 KiB");
+        assert_eq!(humanize_bytes(1024 * 1024, &us), "1 MiB");
+        assert_eq!(humanize_bytes(5 * 1024_u64.pow(3) / 2, &us), "2.5 GiB");
+
+        let de = NumberFormat::for_locale("de");
+        assert_eq!(humanize_bytes(1536, &de), "1,5 KiB");
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_cap() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1));
+        let delays: Vec<u128> = (0..6).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(backoff.attempt(), 6);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn backoff_jitter_stays_within_bounds() {
+        let mut backoff =
+            Backoff::new(Duration::from_millis(100), Duration::from_secs(1)).with_jitter(0.5);
+        for expected in [100, 200, 400, 800, 1000, 1000, 1000] {
+            let delay = backoff.next_delay().as_millis();
+            assert!(
+                (expected / 2..=expected).contains(&delay),
+                "{delay}ms outside [{}, {expected}]",
+                expected / 2
+            );
+        }
+    }
+
+    #[test]
+    fn backoff_honours_retry_after_up_to_cap() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(5));
+        assert_eq!(
+            backoff.next_delay_after(Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            backoff.next_delay_after(Some(Duration::from_secs(60))),
+            Duration::from_secs(5)
+        );
+        assert_eq!(backoff.next_delay_after(None), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn parse_ansi_reads_red_bold_span() {
+        let spans = parse_ansi("ok \x1b[1;31merror\x1b[0m done");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].text, "ok ");
+        assert_eq!(spans[0].fg, None);
+        assert_eq!(
+            spans[1],
+            AnsiSpan {
+                text: "error".into(),
+                fg: Some(AnsiColor::Indexed(1)),
+                bg: None,
+                bold: true,
+            }
+        );
+        assert_eq!(spans[2].text, " done");
+        assert!(!spans[2].bold);
+    }
+
+    #[test]
+    fn parse_ansi_handles_extended_colors_and_ignores_unknown_sequences() {
+        let spans = parse_ansi("\x1b[38;2;1;2;3;48;5;200mx\x1b[2K\x1b]0;title\x07y\x1b[5mz");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "xyz");
+        assert_eq!(spans[0].fg, Some(AnsiColor::Rgb(1, 2, 3)));
+        assert_eq!(spans[0].bg, Some(AnsiColor::Indexed(200)));
+        assert!(parse_ansi("").is_empty());
+    }
+
+    #[test]
+    fn progress_extractor_parses_npm_and_pip_lines() {
+        let mut progress = ProgressExtractor::new();
+        assert_eq!(
+            progress.feed(&OutputChunk::stdout(
+                "added 12 packages, audited 13 packages"
+            )),
+            None
+        );
+        assert_eq!(
+            progress.feed(&OutputChunk::stderr(
+                "[#####.............] 27% reify:lodash"
+            )),
+            Some(27)
+        );
+        assert_eq!(
+            progress.feed(&OutputChunk::stdout(
+                "Downloading numpy-2.1.0.whl  45%|████▌     | 7.2/16.0 MB"
+            )),
+            Some(45)
+        );
+        // Carriage-return redraws: the last update in the chunk wins.
+        assert_eq!(
+            progress.feed(&OutputChunk::stdout(
+                " 60%|██████\r 75%|███████\r 88.6%|████████"
+            )),
+            Some(89)
+        );
+    }
+
+    #[test]
+    fn progress_extractor_keeps_latest_on_unmatched_lines() {
+        let mut progress = ProgressExtractor::new();
+        progress.feed(&OutputChunk::stdout("Downloading... 47%"));
+        assert_eq!(
+            progress.feed(&OutputChunk::stdout("Resolving dependencies")),
+            Some(47)
+        );
+        assert_eq!(progress.latest(), Some(47));
+    }
+
+    #[test]
+    fn progress_extractor_clamps_and_supports_custom_pattern() {
+        let mut progress = ProgressExtractor::new();
+        assert_eq!(
+            progress.feed(&OutputChunk::stdout("overshoot 150%")),
+            Some(100)
+        );
+
+        let mut custom = ProgressExtractor::with_pattern(r"progress=(\d+)").unwrap();
+        assert_eq!(
+            custom.feed(&OutputChunk::stdout("step 3 progress=64 eta=2s")),
+            Some(64)
+        );
+        assert!(ProgressExtractor::with_pattern("(").is_err());
+    }
+
+    fn template_vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn render_template_substitutes_all_placeholders() {
+        let vars = template_vars(&[("target", "release"), ("jobs", "4")]);
+        assert_eq!(
+            render_template("cargo build --{{target}} -j {{ jobs }}", &vars).unwrap(),
+            "cargo build --release -j 4"
+        );
+    }
+
+    #[test]
+    fn render_template_reports_every_unknown_variable() {
+        let vars = template_vars(&[("known", "x")]);
+        let err =
+            render_template("{{missing}} {{known}} {{other}} {{missing}}", &vars).unwrap_err();
+        assert_eq!(
+            err,
+            TemplateError::UnknownVariables(vec!["missing".into(), "other".into()])
+        );
+        assert_eq!(
+            err.to_string(),
+            "unknown template variables: missing, other"
+        );
+    }
+
+    #[test]
+    fn render_template_escapes_double_braces() {
+        let vars = template_vars(&[("name", "world")]);
+        assert_eq!(
+            render_template("{{{{name}} is {{name}}", &vars).unwrap(),
+            "{{name}} is world"
+        );
+    }
+
+    #[test]
+    fn render_template_rejects_unclosed_placeholder() {
+        let err = render_template("echo {{oops", &HashMap::new()).unwrap_err();
+        assert_eq!(err, TemplateError::Unclosed(5));
+    }
 }